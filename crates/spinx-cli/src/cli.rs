@@ -0,0 +1,210 @@
+//! Command line arguments.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_spl::token::spl_token::native_mint;
+use clap::{Parser, Subcommand, ValueEnum};
+use spinx_contract::account::RandomnessProvider;
+
+#[derive(Debug, Parser)]
+#[command(name = "spinx", version, about = "Play and operate spinx coinflips")]
+pub struct Cli {
+    /// RPC URL, or mainnet-beta (m), devnet (d), testnet (t) or localhost (l)
+    #[arg(short, long, global = true, env = "SPINX_RPC_URL", default_value = "devnet")]
+    pub url: String,
+
+    /// Keypair file that signs and pays for transactions
+    #[arg(short, long, global = true, env = "SPINX_KEYPAIR", default_value = "~/.config/solana/id.json")]
+    pub keypair: String,
+
+    /// Simulate transactions and print their logs instead of sending them
+    #[arg(long, global = true)]
+    pub dry_run: bool,
+
+    /// Print results as JSON
+    #[arg(long, global = true)]
+    pub json: bool,
+
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Initializes the program, the keypair becomes the super admin
+    Init,
+
+    /// Creates a coinflip pool
+    Create {
+        /// Stake in base units of the mint, lamports for SOL
+        amount: u64,
+        /// Side of the creator, 0 or 1
+        #[arg(long)]
+        side: u8,
+        /// Mint of the stake, `sol` for a native SOL pool [default: the SPINX token]
+        #[arg(long, value_parser = parse_mint)]
+        mint: Option<Pubkey>,
+        /// Pool lifetime in seconds, 0 for the global maximum
+        #[arg(long, default_value_t = 0)]
+        ttl: u64,
+        /// Only this player may join until the invite times out
+        #[arg(long)]
+        invite: Option<Pubkey>,
+        /// Smallest joiner stake of a weighted pool
+        #[arg(long, requires = "joiner_max")]
+        joiner_min: Option<u64>,
+        /// Largest joiner stake of a weighted pool
+        #[arg(long, requires = "joiner_min")]
+        joiner_max: Option<u64>,
+    },
+
+    /// Joins a waiting pool and requests randomness
+    Join {
+        pool_id: u64,
+        /// Stake in base units [default: the creator's stake]
+        #[arg(long)]
+        amount: Option<u64>,
+        /// Side of the joiner [default: the side the creator left]
+        #[arg(long)]
+        side: Option<u8>,
+        /// Switchboard randomness account committed in the previous slot, when
+        /// Switchboard is the configured provider
+        #[arg(long)]
+        randomness: Option<Pubkey>,
+    },
+
+    /// Pays out a pool whose randomness is fulfilled
+    Settle { pool_id: u64 },
+
+    /// Returns the creator's stake from a pool nobody joined
+    Close { pool_id: u64 },
+
+    /// Returns the stake of an expired pool to its creator, anyone can send it
+    Expire { pool_id: u64 },
+
+    /// Returns both stakes of a pool whose randomness never arrived, anyone can send it
+    Refund { pool_id: u64 },
+
+    /// Prints a pool
+    ShowPool { pool_id: u64 },
+
+    /// Lists the open pools of a mint from the pool registry
+    ListPools {
+        /// Mint of the pools, `sol` for native SOL pools [default: the SPINX token]
+        #[arg(long, value_parser = parse_mint)]
+        mint: Option<Pubkey>,
+        /// Only the shard of this stake tier, the order of magnitude of the stake
+        #[arg(long)]
+        tier: Option<u8>,
+    },
+
+    /// Prints the global configuration, and a mint's if given
+    ShowConfig {
+        #[arg(long, value_parser = parse_mint)]
+        mint: Option<Pubkey>,
+    },
+
+    /// Configuration setters, signed by the super admin
+    #[command(subcommand)]
+    Admin(AdminCommand),
+}
+
+#[derive(Debug, Subcommand)]
+pub enum AdminCommand {
+    /// Default flat SOL fee of newly added mints, in lamports
+    SetFee { coinflip_fee: u64 },
+    /// Default minimum bet of newly added mints
+    SetMinAmount { min_amount: u64 },
+    SetTreasury { treasury_wallet: Pubkey },
+    /// Default mint of `create` and `list-pools`
+    SetSpinxToken { spinx_token: Pubkey },
+    /// Rake on the pot in basis points, at most 1000
+    SetRake { rake_bps: u16 },
+    /// Referrer's share of flat fees in basis points, at most 5000
+    SetReferralShare { referral_share_bps: u16 },
+    /// Seconds a joined pool waits for randomness before it can be refunded
+    SetRefundTimeout { refund_timeout: u64 },
+    /// Seconds an invite-only pool stays reserved
+    SetInviteTimeout { invite_timeout: u64 },
+    /// Longest lifetime of a waiting pool in seconds, 0 for no limit (defaults to 7 days)
+    SetMaxPoolTtl { max_pool_ttl: u64 },
+    SetRandomnessProvider {
+        provider: Provider,
+        /// Queue whose Switchboard randomness is accepted
+        #[arg(long, default_value_t = Pubkey::default())]
+        switchboard_queue: Pubkey,
+    },
+    ProposeAdmin { new_admin: Pubkey },
+    /// Signed by the proposed admin
+    AcceptAdmin,
+    CancelAdminProposal,
+    SetPauser { pauser: Pubkey },
+    /// Replaces the pause bitmask, see PAUSE_* in utils.rs
+    SetPauseFlags { pause_flags: u32 },
+    /// Adds bits to the pause bitmask, signed by the admin or the pauser
+    Pause { pause_flags: u32 },
+    /// Allowlists a mint with the global defaults
+    AddMint {
+        #[arg(value_parser = parse_mint)]
+        mint: Pubkey,
+        /// The mint's freeze authority, required when it has one
+        #[arg(long, default_value_t = Pubkey::default())]
+        approve_freeze_authority: Pubkey,
+    },
+    UpdateMint {
+        #[arg(value_parser = parse_mint)]
+        mint: Pubkey,
+        #[arg(long)]
+        min_amount: u64,
+        /// 0 for no limit
+        #[arg(long)]
+        max_amount: u64,
+        /// Flat SOL fee in lamports
+        #[arg(long)]
+        coinflip_fee: u64,
+    },
+    DisableMint {
+        #[arg(value_parser = parse_mint)]
+        mint: Pubkey,
+    },
+    EnableMint {
+        #[arg(value_parser = parse_mint)]
+        mint: Pubkey,
+    },
+    /// Grows GlobalData to the current layout after an upgrade
+    MigrateGlobalData,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum Provider {
+    Orao,
+    Switchboard,
+}
+
+impl From<Provider> for RandomnessProvider {
+    fn from(provider: Provider) -> Self {
+        match provider {
+            Provider::Orao => RandomnessProvider::Orao,
+            Provider::Switchboard => RandomnessProvider::Switchboard,
+        }
+    }
+}
+
+/// A mint address, or `sol` for the native mint of SOL pools.
+pub fn parse_mint(value: &str) -> Result<Pubkey, String> {
+    if value.eq_ignore_ascii_case("sol") {
+        return Ok(native_mint::ID);
+    }
+    value.parse().map_err(|_| format!("invalid mint `{value}`"))
+}
+
+/// The RPC URL of a cluster moniker, other values are taken as URLs.
+pub fn cluster_url(url: &str) -> String {
+    match url {
+        "m" | "mainnet-beta" => "https://api.mainnet-beta.solana.com",
+        "d" | "devnet" => "https://api.devnet.solana.com",
+        "t" | "testnet" => "https://api.testnet.solana.com",
+        "l" | "localhost" => "http://127.0.0.1:8899",
+        url => url,
+    }
+    .to_string()
+}
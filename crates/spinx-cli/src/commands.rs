@@ -0,0 +1,318 @@
+//! Runs a parsed command against the cluster and returns what to print.
+
+use std::path::PathBuf;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::AccountDeserialize;
+use anchor_spl::token::spl_token::native_mint;
+use serde_json::{json, Map, Value};
+use solana_keypair::{read_keypair_file, Keypair};
+use solana_signer::Signer;
+use solana_transaction::Transaction;
+use spinx_client::instructions::{self as ix, PoolOptions, Randomness, Referral};
+use spinx_client::pda::*;
+use spinx_client::state::{self, CoinflipPool, GlobalData, MintConfig, PlayerReferral, RandomnessProvider, StakeRange};
+
+use crate::cli::{cluster_url, AdminCommand, Cli, Command};
+use crate::error::{Error, Result};
+use crate::output;
+use crate::rpc::{Rpc, RpcAccount};
+
+/// Stake tiers of u64 amounts, see `stake_tier` in `utils.rs`.
+const STAKE_TIERS: u8 = 20;
+
+pub fn run(cli: Cli) -> Result<Value> {
+    let client = Client {
+        rpc: Rpc::new(cluster_url(&cli.url)),
+        keypair: cli.keypair,
+        dry_run: cli.dry_run,
+    };
+
+    match cli.command {
+        Command::Init => client.init(),
+        Command::Create { amount, side, mint, ttl, invite, joiner_min, joiner_max } => {
+            let options = PoolOptions {
+                ttl,
+                allowed_joiner: invite,
+                joiner_stake: joiner_min.zip(joiner_max).map(|(min_amount, max_amount)| StakeRange { min_amount, max_amount }),
+                transfer_fee: 0,
+            };
+            client.create(mint, side, amount, options)
+        }
+        Command::Join { pool_id, amount, side, randomness } => client.join(pool_id, amount, side, randomness),
+        Command::Settle { pool_id } => client.settle(pool_id),
+        Command::Close { pool_id } => client.close(pool_id),
+        Command::Expire { pool_id } => client.expire(pool_id),
+        Command::Refund { pool_id } => client.refund(pool_id),
+        Command::ShowPool { pool_id } => client.show_pool(pool_id),
+        Command::ListPools { mint, tier } => client.list_pools(mint, tier),
+        Command::ShowConfig { mint } => client.show_config(mint),
+        Command::Admin(command) => client.admin(command),
+    }
+}
+
+struct Client {
+    rpc: Rpc,
+    keypair: String,
+    dry_run: bool,
+}
+
+impl Client {
+    fn signer(&self) -> Result<Keypair> {
+        let path = match (self.keypair.strip_prefix("~/"), std::env::var_os("HOME")) {
+            (Some(path), Some(home)) => PathBuf::from(home).join(path),
+            _ => PathBuf::from(&self.keypair),
+        };
+        read_keypair_file(&path).map_err(|error| Error::Input(format!("keypair {}: {error}", path.display())))
+    }
+
+    fn optional_account<T: AccountDeserialize>(&self, address: &Pubkey) -> Result<Option<T>> {
+        self.rpc
+            .get_account(address)?
+            .map(|account| state::decode(&account.data).map_err(|error| Error::Input(format!("{address}: {error}"))))
+            .transpose()
+    }
+
+    fn account<T: AccountDeserialize>(&self, address: &Pubkey, name: &str) -> Result<T> {
+        self.optional_account(address)?
+            .ok_or_else(|| Error::Input(format!("{name} {address} not found")))
+    }
+
+    fn global_data(&self) -> Result<GlobalData> {
+        self.account(&global_data_address(), "global data")
+    }
+
+    fn pool(&self, pool_id: u64) -> Result<CoinflipPool> {
+        self.account(&pool_address(pool_id), &format!("pool {pool_id}"))
+    }
+
+    /// Owner of the mint account, the token program its builders take.
+    fn mint(&self, mint: &Pubkey) -> Result<RpcAccount> {
+        self.rpc.get_account(mint)?.ok_or_else(|| Error::Input(format!("mint {mint} not found")))
+    }
+
+    fn token_program(&self, mint: &Pubkey) -> Result<Pubkey> {
+        Ok(self.mint(mint)?.owner)
+    }
+
+    fn referral(&self, player: &Pubkey) -> Result<Referral> {
+        let referral: Option<PlayerReferral> = self.optional_account(&player_referral_address(player))?;
+        Ok(Referral::of(player, referral.as_ref()))
+    }
+
+    /// Signs `instructions` with the keypair and sends them, or only simulates
+    /// them with `--dry-run`. Adds the outcome to `output`.
+    fn execute(&self, signer: &Keypair, instructions: &[Instruction], mut output: Map<String, Value>) -> Result<Value> {
+        let blockhash = self.rpc.get_latest_blockhash()?;
+        let transaction = Transaction::new_signed_with_payer(instructions, Some(&signer.pubkey()), &[signer], blockhash);
+
+        if self.dry_run {
+            let simulation = self.rpc.simulate_transaction(&transaction)?;
+            if let Some(err) = simulation.err {
+                return Err(Error::Transaction { message: format!("simulation failed: {err}"), logs: simulation.logs });
+            }
+            output.insert("simulated".into(), json!(true));
+            output.insert("units_consumed".into(), json!(simulation.units_consumed));
+            output.insert("logs".into(), json!(simulation.logs));
+        } else {
+            output.insert("signature".into(), json!(self.rpc.send_and_confirm(&transaction)?));
+        }
+        Ok(Value::Object(output))
+    }
+
+    fn init(&self) -> Result<Value> {
+        let signer = self.signer()?;
+        let output = fields(json!({ "global_data": global_data_address().to_string() }));
+        self.execute(&signer, &[ix::initialize(&signer.pubkey())], output)
+    }
+
+    fn create(&self, mint: Option<Pubkey>, side: u8, amount: u64, options: PoolOptions) -> Result<Value> {
+        let signer = self.signer()?;
+        let creator = signer.pubkey();
+        let global = self.global_data()?;
+        let mint = mint.unwrap_or(global.spinx_token);
+        let referral = self.referral(&creator)?;
+
+        let instruction = if mint == native_mint::ID {
+            ix::create_sol_coinflip(&creator, &global, referral, side, amount, options)
+        } else {
+            // The program lists the pool by what the escrow receives after the transfer fee
+            let account = self.mint(&mint)?;
+            let transfer_fee = state::decode_transfer_fee(&account.data, self.rpc.get_epoch()?, amount);
+            let options = PoolOptions { transfer_fee, ..options };
+            ix::create_coinflip(&creator, &mint, &account.owner, &global, referral, side, amount, options)
+        };
+
+        let output = fields(json!({
+            "pool_id": global.next_pool_id,
+            "pool": pool_address(global.next_pool_id).to_string(),
+            "mint": mint.to_string(),
+        }));
+        self.execute(&signer, &[instruction], output)
+    }
+
+    fn join(&self, pool_id: u64, amount: Option<u64>, side: Option<u8>, random: Option<Pubkey>) -> Result<Value> {
+        let signer = self.signer()?;
+        let joiner = signer.pubkey();
+        let global = self.global_data()?;
+        let pool = self.pool(pool_id)?;
+        let amount = amount.unwrap_or(pool.creator_amount);
+        let side = side.unwrap_or(1 - pool.creator_set_number.min(1));
+
+        // A fresh seed per request, the public half of a throwaway keypair
+        let force = Keypair::new().pubkey().to_bytes();
+        let orao_treasury = self
+            .rpc
+            .get_account(&orao_network_state_address())?
+            .map(|account| state::decode_orao_treasury(&account.data))
+            .transpose()
+            .map_err(|error| Error::Input(format!("ORAO network state: {error}")))?;
+
+        let randomness = match global.randomness_provider {
+            RandomnessProvider::Orao => Randomness::orao(
+                force,
+                orao_treasury.ok_or_else(|| Error::Input("ORAO network state not found".into()))?,
+            ),
+            RandomnessProvider::Switchboard => {
+                let random = random.ok_or_else(|| {
+                    Error::Input("Switchboard is the randomness provider, pass the account committed in the previous slot with --randomness".into())
+                })?;
+                Randomness::switchboard(random, force, orao_treasury.unwrap_or(random))
+            }
+        };
+
+        let referral = self.referral(&joiner)?;
+        let instruction = if pool.is_native {
+            ix::join_sol_coinflip(&joiner, &pool, &global, referral, randomness, side, amount)
+        } else {
+            ix::join_coinflip(&joiner, &pool, &self.token_program(&pool.mint)?, &global, referral, randomness, side, amount)
+        };
+
+        let output = fields(json!({ "pool_id": pool_id, "random": randomness.random.to_string() }));
+        self.execute(&signer, &[instruction], output)
+    }
+
+    fn settle(&self, pool_id: u64) -> Result<Value> {
+        let signer = self.signer()?;
+        let global = self.global_data()?;
+        let pool = self.pool(pool_id)?;
+
+        let instruction = if pool.is_native {
+            ix::result_sol_coinflip(&pool, &global)
+        } else {
+            ix::result_coinflip(&pool, &self.token_program(&pool.mint)?, &global)
+        };
+        self.execute(&signer, &[instruction], fields(json!({ "pool_id": pool_id })))
+    }
+
+    fn close(&self, pool_id: u64) -> Result<Value> {
+        let signer = self.signer()?;
+        let pool = self.pool(pool_id)?;
+
+        let instruction = if pool.is_native {
+            ix::close_sol_coinflip(&signer.pubkey(), &pool, &self.global_data()?)
+        } else {
+            ix::close_coinflip(&signer.pubkey(), &pool, &self.token_program(&pool.mint)?)
+        };
+        self.execute(&signer, &[instruction], fields(json!({ "pool_id": pool_id })))
+    }
+
+    fn expire(&self, pool_id: u64) -> Result<Value> {
+        let signer = self.signer()?;
+        let pool = self.pool(pool_id)?;
+
+        let instruction = if pool.is_native {
+            ix::expire_sol_coinflip(&pool, &self.global_data()?)
+        } else {
+            ix::expire_coinflip(&pool, &self.token_program(&pool.mint)?)
+        };
+        self.execute(&signer, &[instruction], fields(json!({ "pool_id": pool_id })))
+    }
+
+    fn refund(&self, pool_id: u64) -> Result<Value> {
+        let signer = self.signer()?;
+        let pool = self.pool(pool_id)?;
+
+        let instruction = if pool.is_native {
+            ix::refund_sol_coinflip(&pool, &self.global_data()?)
+        } else {
+            ix::refund_coinflip(&pool, &self.token_program(&pool.mint)?)
+        };
+        self.execute(&signer, &[instruction], fields(json!({ "pool_id": pool_id })))
+    }
+
+    fn show_pool(&self, pool_id: u64) -> Result<Value> {
+        Ok(output::pool(&pool_address(pool_id), &self.pool(pool_id)?))
+    }
+
+    fn list_pools(&self, mint: Option<Pubkey>, tier: Option<u8>) -> Result<Value> {
+        let mint = match mint {
+            Some(mint) => mint,
+            None => self.global_data()?.spinx_token,
+        };
+        let tiers: Vec<u8> = tier.map_or_else(|| (0..STAKE_TIERS).collect(), |tier| vec![tier]);
+        let addresses: Vec<Pubkey> = tiers.iter().map(|tier| pool_registry_address(&mint, *tier)).collect();
+
+        let mut pools = vec![];
+        for account in self.rpc.get_multiple_accounts(&addresses)?.into_iter().flatten() {
+            let registry = state::decode_pool_registry(&account.data).map_err(|error| Error::Input(format!("pool registry: {error}")))?;
+            pools.extend(registry.pools.iter().map(|entry| output::registry_entry(registry.stake_tier, entry)));
+        }
+
+        Ok(json!({ "mint": mint.to_string(), "pools": pools }))
+    }
+
+    fn show_config(&self, mint: Option<Pubkey>) -> Result<Value> {
+        let mut output = fields(json!({ "global_data": output::global_data(&self.global_data()?) }));
+        if let Some(mint) = mint {
+            let config: MintConfig = self.account(&mint_config_address(&mint), "mint config")?;
+            output.insert("mint_config".into(), output::mint_config(&config));
+        }
+        Ok(Value::Object(output))
+    }
+
+    fn admin(&self, command: AdminCommand) -> Result<Value> {
+        let signer = self.signer()?;
+        let admin = &signer.pubkey();
+
+        let instruction = match command {
+            AdminCommand::SetFee { coinflip_fee } => ix::set_fee(admin, coinflip_fee),
+            AdminCommand::SetMinAmount { min_amount } => ix::set_min_amount(admin, min_amount),
+            AdminCommand::SetTreasury { treasury_wallet } => ix::set_treasury(admin, treasury_wallet),
+            AdminCommand::SetSpinxToken { spinx_token } => ix::set_spinx_token(admin, spinx_token),
+            AdminCommand::SetRake { rake_bps } => ix::set_rake(admin, rake_bps),
+            AdminCommand::SetReferralShare { referral_share_bps } => ix::set_referral_share(admin, referral_share_bps),
+            AdminCommand::SetRefundTimeout { refund_timeout } => ix::set_refund_timeout(admin, refund_timeout),
+            AdminCommand::SetInviteTimeout { invite_timeout } => ix::set_invite_timeout(admin, invite_timeout),
+            AdminCommand::SetMaxPoolTtl { max_pool_ttl } => ix::set_max_pool_ttl(admin, max_pool_ttl),
+            AdminCommand::SetRandomnessProvider { provider, switchboard_queue } => {
+                ix::set_randomness_provider(admin, provider.into(), switchboard_queue)
+            }
+            AdminCommand::ProposeAdmin { new_admin } => ix::propose_admin(admin, new_admin),
+            AdminCommand::AcceptAdmin => ix::accept_admin(admin),
+            AdminCommand::CancelAdminProposal => ix::cancel_admin_proposal(admin),
+            AdminCommand::SetPauser { pauser } => ix::set_pauser(admin, pauser),
+            AdminCommand::SetPauseFlags { pause_flags } => ix::set_pause_flags(admin, pause_flags),
+            AdminCommand::Pause { pause_flags } => ix::pause(admin, pause_flags),
+            AdminCommand::AddMint {
+                mint,
+                approve_freeze_authority,
+            } => ix::add_mint(admin, &mint, approve_freeze_authority),
+            AdminCommand::UpdateMint { mint, min_amount, max_amount, coinflip_fee } => {
+                ix::update_mint(admin, &mint, min_amount, max_amount, coinflip_fee)
+            }
+            AdminCommand::DisableMint { mint } => ix::disable_mint(admin, &mint),
+            AdminCommand::EnableMint { mint } => ix::enable_mint(admin, &mint),
+            AdminCommand::MigrateGlobalData => ix::migrate_global_data(admin),
+        };
+        self.execute(&signer, &[instruction], Map::new())
+    }
+}
+
+fn fields(value: Value) -> Map<String, Value> {
+    match value {
+        Value::Object(fields) => fields,
+        _ => Map::new(),
+    }
+}
@@ -0,0 +1,52 @@
+//! Errors of the CLI. Failed transactions are reported with the `SpinXError`
+//! found in their logs.
+
+use std::fmt;
+
+use serde_json::{json, Value};
+use spinx_client::error::{from_logs, SpinXError};
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug)]
+pub enum Error {
+    /// Bad arguments, a missing keypair file or an account that doesn't exist
+    Input(String),
+    /// The node couldn't be reached or answered with an error
+    Rpc(String),
+    /// The transaction or its simulation failed
+    Transaction { message: String, logs: Vec<String> },
+}
+
+impl Error {
+    pub fn spinx_error(&self) -> Option<SpinXError> {
+        match self {
+            Error::Transaction { logs, .. } => from_logs(logs),
+            _ => None,
+        }
+    }
+
+    pub fn to_json(&self) -> Value {
+        let logs = match self {
+            Error::Transaction { logs, .. } => logs.clone(),
+            _ => vec![],
+        };
+        json!({
+            "error": self.to_string(),
+            "code": self.spinx_error().map(u32::from),
+            "logs": logs,
+        })
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (self, self.spinx_error()) {
+            (_, Some(error)) => write!(f, "{} ({}): {}", error.name(), u32::from(error), error),
+            (Error::Input(message) | Error::Rpc(message), _) => f.write_str(message),
+            (Error::Transaction { message, .. }, None) => f.write_str(message),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
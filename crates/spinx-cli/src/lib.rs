@@ -0,0 +1,11 @@
+//! `spinx`, the command line client of the spinx program.
+//!
+//! Players create, join, settle and close pools, operators inspect pools and
+//! the configuration and run the admin setters. Every transaction can be
+//! simulated first with `--dry-run`.
+
+pub mod cli;
+pub mod commands;
+pub mod error;
+pub mod output;
+pub mod rpc;
@@ -0,0 +1,28 @@
+use std::process::ExitCode;
+
+use clap::Parser;
+use spinx_cli::cli::Cli;
+use spinx_cli::{commands, output};
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let json = cli.json;
+
+    match commands::run(cli) {
+        Ok(value) => {
+            output::print(&value, json);
+            ExitCode::SUCCESS
+        }
+        Err(error) if json => {
+            output::print(&error.to_json(), true);
+            ExitCode::FAILURE
+        }
+        Err(error) => {
+            eprintln!("error: {error}");
+            if let spinx_cli::error::Error::Transaction { logs, .. } = &error {
+                logs.iter().for_each(|log| eprintln!("  {log}"));
+            }
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,135 @@
+//! What commands print: JSON with `--json`, otherwise one `key: value` per line.
+
+use anchor_lang::prelude::Pubkey;
+use serde_json::{json, Value};
+use spinx_client::state::{CoinflipPool, GlobalData, MintConfig, RegistryEntry};
+
+pub fn print(value: &Value, json: bool) {
+    if json {
+        println!("{}", serde_json::to_string_pretty(value).unwrap());
+    } else {
+        print_fields(value, 0);
+    }
+}
+
+fn print_fields(value: &Value, indent: usize) {
+    let pad = " ".repeat(indent);
+    match value {
+        Value::Object(fields) => {
+            for (key, value) in fields {
+                match value {
+                    Value::Object(_) | Value::Array(_) => {
+                        println!("{pad}{key}:");
+                        print_fields(value, indent + 2);
+                    }
+                    _ => println!("{pad}{key}: {}", scalar(value)),
+                }
+            }
+        }
+        Value::Array(items) if items.is_empty() => println!("{pad}(none)"),
+        Value::Array(items) => {
+            for item in items {
+                match item {
+                    Value::Object(_) | Value::Array(_) => {
+                        println!("{pad}-");
+                        print_fields(item, indent + 2);
+                    }
+                    _ => println!("{pad}- {}", scalar(item)),
+                }
+            }
+        }
+        _ => println!("{pad}{}", scalar(value)),
+    }
+}
+
+fn scalar(value: &Value) -> String {
+    match value {
+        Value::String(value) => value.clone(),
+        Value::Null => "-".to_string(),
+        _ => value.to_string(),
+    }
+}
+
+/// `None` for keys left unset, like a public pool's allowed joiner.
+fn optional(key: &Pubkey) -> Value {
+    if *key == Pubkey::default() {
+        Value::Null
+    } else {
+        json!(key.to_string())
+    }
+}
+
+pub fn global_data(global: &GlobalData) -> Value {
+    json!({
+        "super_admin": global.super_admin.to_string(),
+        "pending_admin": optional(&global.pending_admin),
+        "pauser": optional(&global.pauser),
+        "treasury_wallet": global.treasury_wallet.to_string(),
+        "spinx_token": global.spinx_token.to_string(),
+        "coinflip_fee": global.coinflip_fee,
+        "min_amount": global.min_amount,
+        "rake_bps": global.rake_bps,
+        "referral_share_bps": global.referral_share_bps,
+        "refund_timeout": global.refund_timeout(),
+        "invite_timeout": global.invite_timeout(),
+        "max_pool_ttl": global.max_pool_ttl,
+        "pause_flags": global.pause_flags,
+        "randomness_provider": format!("{:?}", global.randomness_provider),
+        "switchboard_queue": optional(&global.switchboard_queue),
+        "next_pool_id": global.next_pool_id,
+        "next_jackpot_id": global.next_jackpot_id,
+        "next_referral_id": global.next_referral_id,
+        "last_season_id": global.last_season_id,
+    })
+}
+
+pub fn mint_config(config: &MintConfig) -> Value {
+    json!({
+        "mint": config.mint.to_string(),
+        "enabled": config.enabled,
+        "min_amount": config.min_amount,
+        "max_amount": config.max_amount,
+        "coinflip_fee": config.coinflip_fee,
+    })
+}
+
+pub fn pool(address: &Pubkey, pool: &CoinflipPool) -> Value {
+    json!({
+        "pool_id": pool.pool_id,
+        "address": address.to_string(),
+        "status": format!("{:?}", pool.status),
+        "winner": optional(&pool.winner),
+        "mint": pool.mint.to_string(),
+        "is_native": pool.is_native,
+        "creator": pool.creator_player.to_string(),
+        "creator_amount": pool.creator_amount,
+        "creator_set_number": pool.creator_set_number,
+        "joiner": optional(&pool.joiner_player),
+        "joiner_amount": pool.joiner_amount,
+        "joiner_set_number": pool.joiner_set_number,
+        "pool_amount": pool.pool_amount,
+        "rake_bps": pool.rake_bps,
+        "weighted": pool.weighted,
+        "joiner_min_amount": pool.joiner_min_amount,
+        "joiner_max_amount": pool.joiner_max_amount,
+        "allowed_joiner": optional(&pool.allowed_joiner),
+        "invite_expiry_ts": pool.invite_expiry_ts,
+        "start_ts": pool.start_ts,
+        "join_ts": pool.join_ts,
+        "expiry_ts": pool.expiry_ts,
+        "randomness_provider": format!("{:?}", pool.randomness_provider),
+        "random": optional(&pool.random),
+        "stake_tier": pool.stake_tier,
+    })
+}
+
+pub fn registry_entry(stake_tier: u8, entry: &RegistryEntry) -> Value {
+    json!({
+        "pool_id": entry.pool_id,
+        "stake_tier": stake_tier,
+        "amount": entry.amount,
+        "set_number": entry.set_number,
+        "weighted": entry.weighted,
+        "expiry_ts": entry.expiry_ts,
+    })
+}
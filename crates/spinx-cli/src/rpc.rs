@@ -0,0 +1,168 @@
+//! The JSON-RPC methods the CLI needs, over blocking HTTP.
+
+use std::str::FromStr;
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use anchor_lang::prelude::Pubkey;
+use base64::prelude::{Engine, BASE64_STANDARD};
+use serde_json::{json, Value};
+use solana_hash::Hash;
+use solana_transaction::Transaction;
+
+use crate::error::{Error, Result};
+
+const COMMITMENT: &str = "confirmed";
+const CONFIRM_TIMEOUT: Duration = Duration::from_secs(60);
+
+pub struct RpcAccount {
+    pub lamports: u64,
+    pub owner: Pubkey,
+    pub data: Vec<u8>,
+}
+
+/// Outcome of `simulateTransaction`, `err` is `None` when it succeeded.
+pub struct Simulation {
+    pub err: Option<Value>,
+    pub logs: Vec<String>,
+    pub units_consumed: Option<u64>,
+}
+
+pub struct Rpc {
+    url: String,
+    agent: ureq::Agent,
+}
+
+impl Rpc {
+    pub fn new(url: String) -> Self {
+        let agent = ureq::AgentBuilder::new().timeout(Duration::from_secs(30)).build();
+        Self { url, agent }
+    }
+
+    fn call(&self, method: &str, params: Value) -> Result<Value> {
+        let request = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+        let mut response: Value = self
+            .agent
+            .post(&self.url)
+            .send_json(request)
+            .map_err(|error| Error::Rpc(format!("{method}: {error}")))?
+            .into_json()
+            .map_err(|error| Error::Rpc(format!("{method}: {error}")))?;
+
+        // Preflight failures come back as errors carrying the simulation logs
+        if let Some(error) = response.get("error") {
+            let message = error["message"].as_str().unwrap_or("unknown error").to_string();
+            return Err(match logs_of(&error["data"]) {
+                Some(logs) => Error::Transaction { message, logs },
+                None => Error::Rpc(format!("{method}: {message}")),
+            });
+        }
+        Ok(response["result"].take())
+    }
+
+    pub fn get_account(&self, address: &Pubkey) -> Result<Option<RpcAccount>> {
+        let result = self.call(
+            "getAccountInfo",
+            json!([address.to_string(), { "encoding": "base64", "commitment": COMMITMENT }]),
+        )?;
+        account_of(&result["value"])
+    }
+
+    pub fn get_multiple_accounts(&self, addresses: &[Pubkey]) -> Result<Vec<Option<RpcAccount>>> {
+        let addresses: Vec<String> = addresses.iter().map(Pubkey::to_string).collect();
+        let result = self.call(
+            "getMultipleAccounts",
+            json!([addresses, { "encoding": "base64", "commitment": COMMITMENT }]),
+        )?;
+        let values = result["value"].as_array().ok_or_else(|| Error::Rpc("getMultipleAccounts: no accounts".into()))?;
+        values.iter().map(account_of).collect()
+    }
+
+    pub fn get_latest_blockhash(&self) -> Result<Hash> {
+        let result = self.call("getLatestBlockhash", json!([{ "commitment": COMMITMENT }]))?;
+        result["value"]["blockhash"]
+            .as_str()
+            .and_then(|blockhash| Hash::from_str(blockhash).ok())
+            .ok_or_else(|| Error::Rpc("getLatestBlockhash: no blockhash".into()))
+    }
+
+    pub fn get_epoch(&self) -> Result<u64> {
+        let result = self.call("getEpochInfo", json!([{ "commitment": COMMITMENT }]))?;
+        result["epoch"].as_u64().ok_or_else(|| Error::Rpc("getEpochInfo: no epoch".into()))
+    }
+
+    pub fn simulate_transaction(&self, transaction: &Transaction) -> Result<Simulation> {
+        let result = self.call(
+            "simulateTransaction",
+            json!([encode(transaction)?, {
+                "encoding": "base64",
+                "commitment": COMMITMENT,
+                "sigVerify": false,
+                "replaceRecentBlockhash": true,
+            }]),
+        )?;
+        let value = &result["value"];
+
+        Ok(Simulation {
+            err: Some(value["err"].clone()).filter(|err| !err.is_null()),
+            logs: logs_of(value).unwrap_or_default(),
+            units_consumed: value["unitsConsumed"].as_u64(),
+        })
+    }
+
+    /// Sends `transaction` and waits until it is confirmed.
+    pub fn send_and_confirm(&self, transaction: &Transaction) -> Result<String> {
+        let signature = self
+            .call(
+                "sendTransaction",
+                json!([encode(transaction)?, { "encoding": "base64", "preflightCommitment": COMMITMENT }]),
+            )?
+            .as_str()
+            .ok_or_else(|| Error::Rpc("sendTransaction: no signature".into()))?
+            .to_string();
+
+        let start = Instant::now();
+        while start.elapsed() < CONFIRM_TIMEOUT {
+            let result = self.call("getSignatureStatuses", json!([[signature]]))?;
+            let status = &result["value"][0];
+
+            if !status.is_null() {
+                if !status["err"].is_null() {
+                    return Err(Error::Transaction { message: format!("{signature} failed: {}", status["err"]), logs: vec![] });
+                }
+                if matches!(status["confirmationStatus"].as_str(), Some("confirmed" | "finalized")) {
+                    return Ok(signature);
+                }
+            }
+            sleep(Duration::from_millis(500));
+        }
+        Err(Error::Rpc(format!("{signature} was not confirmed within {} seconds", CONFIRM_TIMEOUT.as_secs())))
+    }
+}
+
+fn encode(transaction: &Transaction) -> Result<String> {
+    let bytes = bincode::serialize(transaction).map_err(|error| Error::Input(format!("transaction: {error}")))?;
+    Ok(BASE64_STANDARD.encode(bytes))
+}
+
+fn logs_of(value: &Value) -> Option<Vec<String>> {
+    value["logs"]
+        .as_array()
+        .map(|logs| logs.iter().filter_map(|log| log.as_str().map(String::from)).collect())
+}
+
+fn account_of(value: &Value) -> Result<Option<RpcAccount>> {
+    if value.is_null() {
+        return Ok(None);
+    }
+    let invalid = || Error::Rpc("malformed account".into());
+
+    Ok(Some(RpcAccount {
+        lamports: value["lamports"].as_u64().ok_or_else(invalid)?,
+        owner: value["owner"].as_str().and_then(|owner| owner.parse().ok()).ok_or_else(invalid)?,
+        data: value["data"][0]
+            .as_str()
+            .and_then(|data| BASE64_STANDARD.decode(data).ok())
+            .ok_or_else(invalid)?,
+    }))
+}
@@ -0,0 +1,79 @@
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::Discriminator;
+use anchor_spl::token::spl_token::native_mint;
+use clap::Parser;
+use spinx_cli::cli::{cluster_url, parse_mint, AdminCommand, Cli, Command};
+use spinx_cli::error::Error;
+use spinx_cli::output;
+use spinx_client::state::{self, CoinflipPool, PoolStatus};
+
+#[test]
+fn cluster_monikers_expand_to_urls() {
+    assert_eq!(cluster_url("d"), "https://api.devnet.solana.com");
+    assert_eq!(cluster_url("mainnet-beta"), "https://api.mainnet-beta.solana.com");
+    assert_eq!(cluster_url("l"), "http://127.0.0.1:8899");
+    assert_eq!(cluster_url("https://rpc.example.com"), "https://rpc.example.com");
+}
+
+#[test]
+fn mints_parse_with_sol_as_the_native_mint() {
+    let mint = Pubkey::new_unique();
+    assert_eq!(parse_mint("SOL"), Ok(native_mint::ID));
+    assert_eq!(parse_mint(&mint.to_string()), Ok(mint));
+    assert!(parse_mint("spinx").is_err());
+}
+
+#[test]
+fn commands_parse_with_global_flags() {
+    let cli = Cli::try_parse_from(["spinx", "create", "1000", "--side", "1", "--mint", "sol", "--dry-run"]).unwrap();
+    assert!(cli.dry_run && !cli.json);
+    assert!(matches!(cli.command, Command::Create { amount: 1000, side: 1, mint: Some(mint), ttl: 0, .. } if mint == native_mint::ID));
+
+    let cli = Cli::try_parse_from(["spinx", "--json", "-u", "l", "join", "7", "--side", "0"]).unwrap();
+    assert_eq!(cli.url, "l");
+    assert!(cli.json);
+    assert!(matches!(cli.command, Command::Join { pool_id: 7, amount: None, side: Some(0), randomness: None }));
+
+    let cli = Cli::try_parse_from(["spinx", "admin", "set-rake", "250", "--json"]).unwrap();
+    assert!(matches!(cli.command, Command::Admin(AdminCommand::SetRake { rake_bps: 250 })));
+
+    // A weighted pool needs both bounds of the joiner's stake
+    assert!(Cli::try_parse_from(["spinx", "create", "1000", "--side", "1", "--joiner-min", "10"]).is_err());
+}
+
+#[test]
+fn pools_print_unset_keys_as_null() {
+    let data = [CoinflipPool::DISCRIMINATOR, &[0; 512]].concat();
+    let mut pool = state::decode_coinflip_pool(&data).unwrap();
+    pool.pool_id = 3;
+    pool.status = PoolStatus::Processing;
+    pool.creator_player = Pubkey::new_unique();
+    pool.creator_amount = 500;
+
+    let address = Pubkey::new_unique();
+    let value = output::pool(&address, &pool);
+    assert_eq!(value["pool_id"], 3);
+    assert_eq!(value["address"], address.to_string());
+    assert_eq!(value["status"], "Processing");
+    assert_eq!(value["creator"], pool.creator_player.to_string());
+    assert_eq!(value["creator_amount"], 500);
+    assert!(value["joiner"].is_null() && value["winner"].is_null());
+}
+
+#[test]
+fn failed_transactions_report_their_spinx_error() {
+    let error = Error::Transaction {
+        message: "simulation failed".into(),
+        logs: vec![
+            "Program log: Instruction: SetRake".into(),
+            "Program log: AnchorError caused by account: global_data. Error Code: InvalidAdmin. Error Number: 6000. Error Message: Invalid Admin Address.".into(),
+        ],
+    };
+    assert_eq!(error.to_string(), "InvalidAdmin (6000): Invalid Admin Address");
+    assert_eq!(error.to_json()["code"], 6000);
+    assert_eq!(error.to_json()["logs"].as_array().map(Vec::len), Some(2));
+
+    let error = Error::Input("pool 9 not found".into());
+    assert_eq!(error.to_string(), "pool 9 not found");
+    assert!(error.to_json()["code"].is_null());
+}
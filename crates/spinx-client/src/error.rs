@@ -0,0 +1,97 @@
+//! Maps custom program error codes back to `SpinXError`.
+
+use anchor_lang::error::ERROR_CODE_OFFSET;
+use anchor_lang::solana_program::program_error::ProgramError;
+
+pub use spinx_contract::error::SpinXError;
+use SpinXError::*;
+
+/// Every `SpinXError` in declaration order, the first being code 6000.
+pub const ERRORS: [SpinXError; 65] = [
+    InvalidAdmin,
+    InvalidCreator,
+    InvalidClaimStatus,
+    InvalidPoolStatus,
+    InvalidForce,
+    AlreadyClaimed,
+    AlreadyDrawn,
+    AlreadyJoined,
+    NotWinner,
+    TokenNotAllowed,
+    OwnerMismatch,
+    InvalidAmount,
+    InvalidJoiner,
+    InvalidNumber,
+    AmountTooSmall,
+    ChallengeTaken,
+    ChallengeNotTaken,
+    ChallengeCompleted,
+    ChallengeCancelled,
+    NotChallengeCreator,
+    NotTreasuryAuthority,
+    InvalidVRFResult,
+    InsufficientFunds,
+    StillProcessing,
+    NoPendingAdmin,
+    InvalidAccountData,
+    InstructionPaused,
+    InvalidPauser,
+    AmountTooLarge,
+    UnsafeMintExtension,
+    InvalidPoolAsset,
+    InvalidRake,
+    RefundNotAvailable,
+    RandomnessFulfilled,
+    PoolExpired,
+    PoolNotExpired,
+    InvalidExpiry,
+    InvalidRandomnessAccount,
+    InvalidRandomnessProvider,
+    NotInvited,
+    InviteNotExpired,
+    JackpotFull,
+    JackpotClosed,
+    JackpotNotEnded,
+    NotEnoughPlayers,
+    NotInJackpot,
+    InvalidHouseConfig,
+    WithdrawalNotReady,
+    InvalidReferralShare,
+    SelfReferral,
+    ReferrerAlreadyBound,
+    ReferralCycle,
+    InvalidReferrer,
+    NoReferralRewards,
+    InvalidSeasonConfig,
+    SeasonNotEnded,
+    SeasonFinalized,
+    SeasonNotFinalized,
+    NoSeasonPrize,
+    SeasonClaimsOpen,
+    InvalidTreasury,
+    FreezeAuthorityNotApproved,
+    RandomnessForfeited,
+    WithdrawalExpired,
+    WithdrawalNotExpired,
+];
+
+pub fn from_code(code: u32) -> Option<SpinXError> {
+    code.checked_sub(ERROR_CODE_OFFSET).and_then(|index| ERRORS.get(index as usize)).copied()
+}
+
+pub fn from_program_error(error: &ProgramError) -> Option<SpinXError> {
+    match error {
+        ProgramError::Custom(code) => from_code(*code),
+        _ => None,
+    }
+}
+
+/// The error of a failed transaction or simulation, from the
+/// `Error Number: <code>` part of the line Anchor logs.
+pub fn from_logs<S: AsRef<str>>(logs: &[S]) -> Option<SpinXError> {
+    logs.iter().find_map(|log| {
+        let (_, rest) = log.as_ref().split_once("Error Number: ")?;
+        let code = rest.split(|c: char| !c.is_ascii_digit()).next()?;
+        from_code(code.parse().ok()?)
+    })
+}
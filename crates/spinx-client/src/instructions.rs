@@ -0,0 +1,1106 @@
+//! Builders for every spinx instruction.
+//!
+//! Builders take the signer, the decoded accounts they derive addresses from
+//! and the instruction's arguments. SPL builders also take the mint's token
+//! program, the owner of the mint account.
+
+// Builders take the instruction's arguments as they are, like the program's handlers
+#![allow(clippy::too_many_arguments)]
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::sysvar;
+use anchor_lang::system_program;
+use anchor_lang::{InstructionData, ToAccountMetas};
+use anchor_spl::token::spl_token::native_mint;
+use spinx_contract::account::{
+    CoinflipPool, GlobalData, HouseFlip, JackpotRound, PlayerReferral, RandomnessProvider, SeasonMetric, StakeRange,
+};
+use spinx_contract::utils::stake_tier;
+use spinx_contract::{accounts, instruction};
+
+use crate::pda::*;
+
+/// Optional arguments of `create_coinflip` and `create_sol_coinflip`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PoolOptions {
+    /// Pool lifetime in seconds, 0 for `max_pool_ttl`
+    pub ttl: u64,
+    /// Makes the pool invite-only
+    pub allowed_joiner: Option<Pubkey>,
+    /// Makes the pool weighted
+    pub joiner_stake: Option<StakeRange>,
+    /// Transfer fee of the mint on the stake, see `state::decode_transfer_fee`.
+    /// The pool is listed in the lobby of what the escrow receives.
+    pub transfer_fee: u64,
+}
+
+/// Referral accounts of an instruction charging the flat fee.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Referral {
+    /// The player's `PlayerReferral` PDA, passed even before they register
+    pub player_referral: Pubkey,
+    pub referrer_referral: Option<Pubkey>,
+}
+
+impl Referral {
+    /// The accounts of `player`, with their referrer's when their `PlayerReferral`
+    /// names one. The program requires it and pays it its share of the fee.
+    pub fn of(player: &Pubkey, referral: Option<&PlayerReferral>) -> Self {
+        Self {
+            player_referral: player_referral_address(player),
+            referrer_referral: referral
+                .filter(|referral| referral.referrer != Pubkey::default())
+                .map(|referral| player_referral_address(&referral.referrer)),
+        }
+    }
+}
+
+/// Provider accounts of an instruction requesting randomness.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Randomness {
+    pub force: [u8; 32],
+    /// ORAO request of `force`, or the committed Switchboard randomness account
+    pub random: Pubkey,
+    /// Treasury of the ORAO network state
+    pub treasury: Pubkey,
+}
+
+impl Randomness {
+    /// ORAO request seeded with `force`, one per request.
+    pub fn orao(force: [u8; 32], treasury: Pubkey) -> Self {
+        Self { force, random: randomness_address(&force), treasury }
+    }
+
+    /// A Switchboard randomness account committed in the previous slot. The
+    /// ORAO accounts are still passed but ignored.
+    pub fn switchboard(random: Pubkey, force: [u8; 32], treasury: Pubkey) -> Self {
+        Self { force, random, treasury }
+    }
+}
+
+fn spinx_ix(accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: spinx_contract::ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+// Admin
+
+pub fn initialize(admin: &Pubkey) -> Instruction {
+    spinx_ix(
+        accounts::Initialize {
+            admin: *admin,
+            global_data: global_data_address(),
+            system_program: system_program::ID,
+            token_program: anchor_spl::token::ID,
+            rent: sysvar::rent::ID,
+        },
+        instruction::Initialize {},
+    )
+}
+
+/// Any of the `UpdateGlobalData` instructions signed by `admin`.
+pub fn update_global_data(admin: &Pubkey, data: impl InstructionData) -> Instruction {
+    spinx_ix(
+        accounts::UpdateGlobalData {
+            admin: *admin,
+            global_data: global_data_address(),
+            event_authority: event_authority(),
+            program: spinx_contract::ID,
+        },
+        data,
+    )
+}
+
+pub fn set_fee(admin: &Pubkey, coinflip_fee: u64) -> Instruction {
+    update_global_data(admin, instruction::SetFee { coinflip_fee })
+}
+
+pub fn set_min_amount(admin: &Pubkey, min_amount: u64) -> Instruction {
+    update_global_data(admin, instruction::SetMinAmount { min_amount })
+}
+
+pub fn set_treasury(admin: &Pubkey, treasury_wallet: Pubkey) -> Instruction {
+    update_global_data(admin, instruction::SetTreasury { treasury_wallet })
+}
+
+pub fn set_spinx_token(admin: &Pubkey, spinx_token: Pubkey) -> Instruction {
+    update_global_data(admin, instruction::SetSpinxToken { spinx_token })
+}
+
+pub fn set_rake(admin: &Pubkey, rake_bps: u16) -> Instruction {
+    update_global_data(admin, instruction::SetRake { rake_bps })
+}
+
+pub fn set_referral_share(admin: &Pubkey, referral_share_bps: u16) -> Instruction {
+    update_global_data(admin, instruction::SetReferralShare { referral_share_bps })
+}
+
+pub fn set_refund_timeout(admin: &Pubkey, refund_timeout: u64) -> Instruction {
+    update_global_data(admin, instruction::SetRefundTimeout { refund_timeout })
+}
+
+pub fn set_invite_timeout(admin: &Pubkey, invite_timeout: u64) -> Instruction {
+    update_global_data(admin, instruction::SetInviteTimeout { invite_timeout })
+}
+
+pub fn set_max_pool_ttl(admin: &Pubkey, max_pool_ttl: u64) -> Instruction {
+    update_global_data(admin, instruction::SetMaxPoolTtl { max_pool_ttl })
+}
+
+pub fn set_randomness_provider(admin: &Pubkey, provider: RandomnessProvider, switchboard_queue: Pubkey) -> Instruction {
+    update_global_data(admin, instruction::SetRandomnessProvider { provider, switchboard_queue })
+}
+
+pub fn propose_admin(admin: &Pubkey, new_admin: Pubkey) -> Instruction {
+    update_global_data(admin, instruction::ProposeAdmin { new_admin })
+}
+
+pub fn cancel_admin_proposal(admin: &Pubkey) -> Instruction {
+    update_global_data(admin, instruction::CancelAdminProposal {})
+}
+
+pub fn set_pauser(admin: &Pubkey, pauser: Pubkey) -> Instruction {
+    update_global_data(admin, instruction::SetPauser { pauser })
+}
+
+pub fn set_pause_flags(admin: &Pubkey, pause_flags: u32) -> Instruction {
+    update_global_data(admin, instruction::SetPauseFlags { pause_flags })
+}
+
+pub fn accept_admin(new_admin: &Pubkey) -> Instruction {
+    spinx_ix(
+        accounts::AcceptAdmin {
+            new_admin: *new_admin,
+            global_data: global_data_address(),
+            event_authority: event_authority(),
+            program: spinx_contract::ID,
+        },
+        instruction::AcceptAdmin {},
+    )
+}
+
+/// Signed by the admin or the pauser.
+pub fn pause(authority: &Pubkey, pause_flags: u32) -> Instruction {
+    spinx_ix(
+        accounts::Pause {
+            authority: *authority,
+            global_data: global_data_address(),
+            event_authority: event_authority(),
+            program: spinx_contract::ID,
+        },
+        instruction::Pause { pause_flags },
+    )
+}
+
+pub fn migrate_global_data(admin: &Pubkey) -> Instruction {
+    spinx_ix(
+        accounts::MigrateGlobalData {
+            admin: *admin,
+            global_data: global_data_address(),
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: spinx_contract::ID,
+        },
+        instruction::MigrateGlobalData {},
+    )
+}
+
+pub fn migrate_coinflip_pool(payer: &Pubkey, pool_id: u64) -> Instruction {
+    spinx_ix(
+        accounts::MigrateCoinflipPool {
+            payer: *payer,
+            global_data: global_data_address(),
+            coinflip_pool: pool_address(pool_id),
+            system_program: system_program::ID,
+        },
+        instruction::MigrateCoinflipPool { pool_id },
+    )
+}
+
+// Mints
+
+pub fn add_mint(admin: &Pubkey, mint: &Pubkey, approved_freeze_authority: Pubkey) -> Instruction {
+    spinx_ix(
+        accounts::AddMint {
+            admin: *admin,
+            global_data: global_data_address(),
+            mint: *mint,
+            mint_config: mint_config_address(mint),
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: spinx_contract::ID,
+        },
+        instruction::AddMint {
+            approved_freeze_authority,
+        },
+    )
+}
+
+/// Any of the `UpdateMintConfig` instructions signed by `admin`.
+pub fn update_mint_config(admin: &Pubkey, mint: &Pubkey, data: impl InstructionData) -> Instruction {
+    spinx_ix(
+        accounts::UpdateMintConfig {
+            admin: *admin,
+            global_data: global_data_address(),
+            mint_config: mint_config_address(mint),
+            event_authority: event_authority(),
+            program: spinx_contract::ID,
+        },
+        data,
+    )
+}
+
+pub fn update_mint(admin: &Pubkey, mint: &Pubkey, min_amount: u64, max_amount: u64, coinflip_fee: u64) -> Instruction {
+    update_mint_config(admin, mint, instruction::UpdateMint { min_amount, max_amount, coinflip_fee })
+}
+
+pub fn disable_mint(admin: &Pubkey, mint: &Pubkey) -> Instruction {
+    update_mint_config(admin, mint, instruction::DisableMint {})
+}
+
+pub fn enable_mint(admin: &Pubkey, mint: &Pubkey) -> Instruction {
+    update_mint_config(admin, mint, instruction::EnableMint {})
+}
+
+// SPL pools
+
+/// Creates pool `global.next_pool_id`, which changes with every pool created.
+pub fn create_coinflip(
+    creator: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+    global: &GlobalData,
+    referral: Referral,
+    set_number: u8,
+    amount: u64,
+    options: PoolOptions,
+) -> Instruction {
+    let pool = pool_address(global.next_pool_id);
+
+    spinx_ix(
+        accounts::CreateCoinflip {
+            creator: *creator,
+            global_data: global_data_address(),
+            creator_ata: ata(creator, mint, token_program),
+            mint: *mint,
+            mint_config: mint_config_address(mint),
+            coinflip_pool: pool,
+            treasury_wallet: global.treasury_wallet,
+            player_referral: referral.player_referral,
+            referrer_referral: referral.referrer_referral,
+            creator_profile: player_profile_address(creator, mint),
+            pool_registry: pool_registry_address(mint, stake_tier(amount - options.transfer_fee)),
+            spl_escrow: ata(&pool, mint, token_program),
+            associated_token_program: anchor_spl::associated_token::ID,
+            system_program: system_program::ID,
+            token_program: *token_program,
+            event_authority: event_authority(),
+            program: spinx_contract::ID,
+        },
+        instruction::CreateCoinflip {
+            set_number,
+            amount,
+            ttl: options.ttl,
+            allowed_joiner: options.allowed_joiner,
+            joiner_stake: options.joiner_stake,
+        },
+    )
+}
+
+pub fn join_coinflip(
+    joiner: &Pubkey,
+    pool: &CoinflipPool,
+    token_program: &Pubkey,
+    global: &GlobalData,
+    referral: Referral,
+    randomness: Randomness,
+    set_number: u8,
+    amount: u64,
+) -> Instruction {
+    let address = pool_address(pool.pool_id);
+
+    spinx_ix(
+        accounts::JoinCoinflip {
+            joiner: *joiner,
+            global_data: global_data_address(),
+            joiner_ata: ata(joiner, &pool.mint, token_program),
+            mint: pool.mint,
+            mint_config: mint_config_address(&pool.mint),
+            coinflip_pool: address,
+            treasury_wallet: global.treasury_wallet,
+            player_referral: referral.player_referral,
+            referrer_referral: referral.referrer_referral,
+            joiner_profile: player_profile_address(joiner, &pool.mint),
+            pool_registry: pool_registry_of(pool),
+            spl_escrow: ata(&address, &pool.mint, token_program),
+            random: randomness.random,
+            treasury: randomness.treasury,
+            config: orao_network_state_address(),
+            vrf: orao_solana_vrf::ID,
+            associated_token_program: anchor_spl::associated_token::ID,
+            system_program: system_program::ID,
+            token_program: *token_program,
+            event_authority: event_authority(),
+            program: spinx_contract::ID,
+        },
+        instruction::JoinCoinflip { pool_id: pool.pool_id, force: randomness.force, set_number, amount },
+    )
+}
+
+/// Signed by the creator, or by the invited player for invite-only pools.
+pub fn close_coinflip(signer: &Pubkey, pool: &CoinflipPool, token_program: &Pubkey) -> Instruction {
+    let address = pool_address(pool.pool_id);
+
+    spinx_ix(
+        accounts::CloseCoinflip {
+            signer: *signer,
+            coinflip_pool: address,
+            spl_escrow: ata(&address, &pool.mint, token_program),
+            mint: pool.mint,
+            creator_ata: ata(signer, &pool.mint, token_program),
+            associated_token_program: anchor_spl::associated_token::ID,
+            token_program: *token_program,
+            system_program: system_program::ID,
+            pool_registry: pool_registry_of(pool),
+            event_authority: event_authority(),
+            program: spinx_contract::ID,
+        },
+        instruction::CloseCoinflip { pool_id: pool.pool_id },
+    )
+}
+
+/// Makes an invite-only pool of either kind public.
+pub fn open_coinflip(creator: &Pubkey, pool: &CoinflipPool) -> Instruction {
+    spinx_ix(
+        accounts::OpenCoinflip {
+            creator: *creator,
+            coinflip_pool: pool_address(pool.pool_id),
+            pool_registry: pool_registry_of(pool),
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: spinx_contract::ID,
+        },
+        instruction::OpenCoinflip { pool_id: pool.pool_id },
+    )
+}
+
+pub fn expire_coinflip(pool: &CoinflipPool, token_program: &Pubkey) -> Instruction {
+    let address = pool_address(pool.pool_id);
+
+    spinx_ix(
+        accounts::ExpireCoinflip {
+            coinflip_pool: address,
+            spl_escrow: ata(&address, &pool.mint, token_program),
+            mint: pool.mint,
+            creator_ata: ata(&pool.creator_player, &pool.mint, token_program),
+            token_program: *token_program,
+            creator: pool.creator_player,
+            pool_registry: pool_registry_of(pool),
+            event_authority: event_authority(),
+            program: spinx_contract::ID,
+        },
+        instruction::ExpireCoinflip { pool_id: pool.pool_id },
+    )
+}
+
+pub fn result_coinflip(pool: &CoinflipPool, token_program: &Pubkey, global: &GlobalData) -> Instruction {
+    let address = pool_address(pool.pool_id);
+    let season = season_address(&pool.mint);
+
+    spinx_ix(
+        accounts::ResultCoinflip {
+            coinflip_pool: address,
+            spl_escrow: ata(&address, &pool.mint, token_program),
+            mint: pool.mint,
+            creator_ata: ata(&pool.creator_player, &pool.mint, token_program),
+            joiner_ata: ata(&pool.joiner_player, &pool.mint, token_program),
+            global_data: global_data_address(),
+            treasury_ata: ata(&global.treasury_wallet, &pool.mint, token_program),
+            random: pool.random_account(),
+            token_program: *token_program,
+            system_program: system_program::ID,
+            creator: pool.creator_player,
+            creator_profile: player_profile_address(&pool.creator_player, &pool.mint),
+            joiner_profile: player_profile_address(&pool.joiner_player, &pool.mint),
+            season,
+            season_vault: ata(&season, &pool.mint, token_program),
+            event_authority: event_authority(),
+            program: spinx_contract::ID,
+        },
+        instruction::ResultCoinflip { pool_id: pool.pool_id, force: pool.force },
+    )
+}
+
+pub fn refund_coinflip(pool: &CoinflipPool, token_program: &Pubkey) -> Instruction {
+    let address = pool_address(pool.pool_id);
+
+    spinx_ix(
+        accounts::RefundCoinflip {
+            coinflip_pool: address,
+            spl_escrow: ata(&address, &pool.mint, token_program),
+            mint: pool.mint,
+            creator_ata: ata(&pool.creator_player, &pool.mint, token_program),
+            joiner_ata: ata(&pool.joiner_player, &pool.mint, token_program),
+            global_data: global_data_address(),
+            random: pool.random_account(),
+            token_program: *token_program,
+            creator: pool.creator_player,
+            event_authority: event_authority(),
+            program: spinx_contract::ID,
+        },
+        instruction::RefundCoinflip { pool_id: pool.pool_id },
+    )
+}
+
+// SOL pools
+
+/// Creates SOL pool `global.next_pool_id`, which changes with every pool created.
+pub fn create_sol_coinflip(
+    creator: &Pubkey,
+    global: &GlobalData,
+    referral: Referral,
+    set_number: u8,
+    amount: u64,
+    options: PoolOptions,
+) -> Instruction {
+    let pool = pool_address(global.next_pool_id);
+
+    spinx_ix(
+        accounts::CreateSolCoinflip {
+            creator: *creator,
+            global_data: global_data_address(),
+            mint_config: mint_config_address(&native_mint::ID),
+            coinflip_pool: pool,
+            sol_escrow: sol_escrow_address(&pool),
+            treasury_wallet: global.treasury_wallet,
+            player_referral: referral.player_referral,
+            referrer_referral: referral.referrer_referral,
+            creator_profile: player_profile_address(creator, &native_mint::ID),
+            pool_registry: pool_registry_address(&native_mint::ID, stake_tier(amount)),
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: spinx_contract::ID,
+        },
+        instruction::CreateSolCoinflip {
+            set_number,
+            amount,
+            ttl: options.ttl,
+            allowed_joiner: options.allowed_joiner,
+            joiner_stake: options.joiner_stake,
+        },
+    )
+}
+
+pub fn join_sol_coinflip(
+    joiner: &Pubkey,
+    pool: &CoinflipPool,
+    global: &GlobalData,
+    referral: Referral,
+    randomness: Randomness,
+    set_number: u8,
+    amount: u64,
+) -> Instruction {
+    let address = pool_address(pool.pool_id);
+
+    spinx_ix(
+        accounts::JoinSolCoinflip {
+            joiner: *joiner,
+            global_data: global_data_address(),
+            mint_config: mint_config_address(&native_mint::ID),
+            coinflip_pool: address,
+            sol_escrow: sol_escrow_address(&address),
+            treasury_wallet: global.treasury_wallet,
+            player_referral: referral.player_referral,
+            referrer_referral: referral.referrer_referral,
+            joiner_profile: player_profile_address(joiner, &native_mint::ID),
+            pool_registry: pool_registry_of(pool),
+            random: randomness.random,
+            treasury: randomness.treasury,
+            config: orao_network_state_address(),
+            vrf: orao_solana_vrf::ID,
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: spinx_contract::ID,
+        },
+        instruction::JoinSolCoinflip { pool_id: pool.pool_id, force: randomness.force, set_number, amount },
+    )
+}
+
+pub fn close_sol_coinflip(signer: &Pubkey, pool: &CoinflipPool, global: &GlobalData) -> Instruction {
+    let address = pool_address(pool.pool_id);
+
+    spinx_ix(
+        accounts::CloseSolCoinflip {
+            signer: *signer,
+            coinflip_pool: address,
+            sol_escrow: sol_escrow_address(&address),
+            global_data: global_data_address(),
+            treasury_wallet: global.treasury_wallet,
+            system_program: system_program::ID,
+            pool_registry: pool_registry_of(pool),
+            event_authority: event_authority(),
+            program: spinx_contract::ID,
+        },
+        instruction::CloseSolCoinflip { pool_id: pool.pool_id },
+    )
+}
+
+pub fn expire_sol_coinflip(pool: &CoinflipPool, global: &GlobalData) -> Instruction {
+    let address = pool_address(pool.pool_id);
+
+    spinx_ix(
+        accounts::ExpireSolCoinflip {
+            coinflip_pool: address,
+            sol_escrow: sol_escrow_address(&address),
+            creator: pool.creator_player,
+            global_data: global_data_address(),
+            treasury_wallet: global.treasury_wallet,
+            system_program: system_program::ID,
+            pool_registry: pool_registry_of(pool),
+            event_authority: event_authority(),
+            program: spinx_contract::ID,
+        },
+        instruction::ExpireSolCoinflip { pool_id: pool.pool_id },
+    )
+}
+
+pub fn result_sol_coinflip(pool: &CoinflipPool, global: &GlobalData) -> Instruction {
+    let address = pool_address(pool.pool_id);
+
+    spinx_ix(
+        accounts::ResultSolCoinflip {
+            coinflip_pool: address,
+            sol_escrow: sol_escrow_address(&address),
+            creator: pool.creator_player,
+            joiner: pool.joiner_player,
+            global_data: global_data_address(),
+            treasury_wallet: global.treasury_wallet,
+            random: pool.random_account(),
+            system_program: system_program::ID,
+            creator_profile: player_profile_address(&pool.creator_player, &pool.mint),
+            joiner_profile: player_profile_address(&pool.joiner_player, &pool.mint),
+            event_authority: event_authority(),
+            program: spinx_contract::ID,
+        },
+        instruction::ResultSolCoinflip { pool_id: pool.pool_id, force: pool.force },
+    )
+}
+
+pub fn refund_sol_coinflip(pool: &CoinflipPool, global: &GlobalData) -> Instruction {
+    let address = pool_address(pool.pool_id);
+
+    spinx_ix(
+        accounts::RefundSolCoinflip {
+            coinflip_pool: address,
+            sol_escrow: sol_escrow_address(&address),
+            creator: pool.creator_player,
+            joiner: pool.joiner_player,
+            global_data: global_data_address(),
+            treasury_wallet: global.treasury_wallet,
+            random: pool.random_account(),
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: spinx_contract::ID,
+        },
+        instruction::RefundSolCoinflip { pool_id: pool.pool_id },
+    )
+}
+
+// Jackpot rounds
+
+/// Creates round `global.next_jackpot_id`, which changes with every round created.
+pub fn create_jackpot(creator: &Pubkey, mint: &Pubkey, token_program: &Pubkey, global: &GlobalData, duration: u64) -> Instruction {
+    let round = jackpot_address(global.next_jackpot_id);
+
+    spinx_ix(
+        accounts::CreateJackpot {
+            creator: *creator,
+            global_data: global_data_address(),
+            mint: *mint,
+            mint_config: mint_config_address(mint),
+            jackpot_round: round,
+            spl_escrow: ata(&round, mint, token_program),
+            associated_token_program: anchor_spl::associated_token::ID,
+            system_program: system_program::ID,
+            token_program: *token_program,
+            event_authority: event_authority(),
+            program: spinx_contract::ID,
+        },
+        instruction::CreateJackpot { duration },
+    )
+}
+
+pub fn enter_jackpot(
+    player: &Pubkey,
+    round: &JackpotRound,
+    token_program: &Pubkey,
+    global: &GlobalData,
+    referral: Referral,
+    amount: u64,
+) -> Instruction {
+    let address = jackpot_address(round.round_id);
+
+    spinx_ix(
+        accounts::EnterJackpot {
+            player: *player,
+            global_data: global_data_address(),
+            player_ata: ata(player, &round.mint, token_program),
+            mint: round.mint,
+            mint_config: mint_config_address(&round.mint),
+            jackpot_round: address,
+            treasury_wallet: global.treasury_wallet,
+            player_referral: referral.player_referral,
+            referrer_referral: referral.referrer_referral,
+            spl_escrow: ata(&address, &round.mint, token_program),
+            system_program: system_program::ID,
+            token_program: *token_program,
+            event_authority: event_authority(),
+            program: spinx_contract::ID,
+        },
+        instruction::EnterJackpot { round_id: round.round_id, amount },
+    )
+}
+
+/// Jackpots always draw with ORAO, pass `Randomness::orao`.
+pub fn draw_jackpot(payer: &Pubkey, round_id: u64, randomness: Randomness) -> Instruction {
+    spinx_ix(
+        accounts::DrawJackpot {
+            payer: *payer,
+            jackpot_round: jackpot_address(round_id),
+            random: randomness.random,
+            treasury: randomness.treasury,
+            config: orao_network_state_address(),
+            vrf: orao_solana_vrf::ID,
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: spinx_contract::ID,
+        },
+        instruction::DrawJackpot { round_id, force: randomness.force },
+    )
+}
+
+/// Pays `winner`'s associated token account, `JackpotRound::winner_for` the
+/// fulfilled randomness.
+pub fn settle_jackpot(round: &JackpotRound, token_program: &Pubkey, global: &GlobalData, winner: &Pubkey) -> Instruction {
+    let address = jackpot_address(round.round_id);
+
+    spinx_ix(
+        accounts::SettleJackpot {
+            jackpot_round: address,
+            spl_escrow: ata(&address, &round.mint, token_program),
+            mint: round.mint,
+            winner_ata: ata(winner, &round.mint, token_program),
+            global_data: global_data_address(),
+            treasury_ata: ata(&global.treasury_wallet, &round.mint, token_program),
+            random: round.random_account(),
+            token_program: *token_program,
+            creator: round.creator,
+            event_authority: event_authority(),
+            program: spinx_contract::ID,
+        },
+        instruction::SettleJackpot { round_id: round.round_id },
+    )
+}
+
+/// Refunds the deposits of `players`, passing their associated token accounts.
+pub fn refund_jackpot(round: &JackpotRound, token_program: &Pubkey, global: &GlobalData, players: &[Pubkey]) -> Instruction {
+    let address = jackpot_address(round.round_id);
+
+    let mut ix = spinx_ix(
+        accounts::RefundJackpot {
+            jackpot_round: address,
+            spl_escrow: ata(&address, &round.mint, token_program),
+            mint: round.mint,
+            global_data: global_data_address(),
+            treasury_ata: ata(&global.treasury_wallet, &round.mint, token_program),
+            random: round.random_account(),
+            token_program: *token_program,
+            creator: round.creator,
+            event_authority: event_authority(),
+            program: spinx_contract::ID,
+        },
+        instruction::RefundJackpot { round_id: round.round_id },
+    );
+    ix.accounts.extend(
+        players
+            .iter()
+            .map(|player| AccountMeta::new(ata(player, &round.mint, token_program), false)),
+    );
+    ix
+}
+
+// House
+
+pub fn create_house_vault(
+    admin: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+    edge_bps: u16,
+    max_bet_bps: u16,
+    withdrawal_delay: u64,
+) -> Instruction {
+    let house_vault = house_vault_address(mint);
+
+    spinx_ix(
+        accounts::CreateHouseVault {
+            admin: *admin,
+            global_data: global_data_address(),
+            mint: *mint,
+            mint_config: mint_config_address(mint),
+            house_vault,
+            vault_ata: ata(&house_vault, mint, token_program),
+            share_mint: share_mint_address(&house_vault),
+            associated_token_program: anchor_spl::associated_token::ID,
+            system_program: system_program::ID,
+            token_program: *token_program,
+            event_authority: event_authority(),
+            program: spinx_contract::ID,
+        },
+        instruction::CreateHouseVault { edge_bps, max_bet_bps, withdrawal_delay },
+    )
+}
+
+pub fn update_house_vault(admin: &Pubkey, mint: &Pubkey, edge_bps: u16, max_bet_bps: u16, withdrawal_delay: u64) -> Instruction {
+    spinx_ix(
+        accounts::UpdateHouseVault {
+            admin: *admin,
+            global_data: global_data_address(),
+            house_vault: house_vault_address(mint),
+            event_authority: event_authority(),
+            program: spinx_contract::ID,
+        },
+        instruction::UpdateHouseVault { edge_bps, max_bet_bps, withdrawal_delay },
+    )
+}
+
+pub fn deposit_house_liquidity(provider: &Pubkey, mint: &Pubkey, token_program: &Pubkey, amount: u64) -> Instruction {
+    let house_vault = house_vault_address(mint);
+    let share_mint = share_mint_address(&house_vault);
+
+    spinx_ix(
+        accounts::DepositHouseLiquidity {
+            provider: *provider,
+            global_data: global_data_address(),
+            house_vault,
+            mint: *mint,
+            share_mint,
+            vault_ata: ata(&house_vault, mint, token_program),
+            provider_ata: ata(provider, mint, token_program),
+            provider_shares: ata(provider, &share_mint, token_program),
+            associated_token_program: anchor_spl::associated_token::ID,
+            system_program: system_program::ID,
+            token_program: *token_program,
+            event_authority: event_authority(),
+            program: spinx_contract::ID,
+        },
+        instruction::DepositHouseLiquidity { amount },
+    )
+}
+
+pub fn request_house_withdrawal(provider: &Pubkey, mint: &Pubkey, token_program: &Pubkey, shares: u64) -> Instruction {
+    let house_vault = house_vault_address(mint);
+    let share_mint = share_mint_address(&house_vault);
+
+    spinx_ix(
+        accounts::RequestHouseWithdrawal {
+            provider: *provider,
+            house_vault,
+            share_mint,
+            provider_shares: ata(provider, &share_mint, token_program),
+            vault_shares: ata(&house_vault, &share_mint, token_program),
+            house_withdrawal: house_withdrawal_address(&house_vault, provider),
+            associated_token_program: anchor_spl::associated_token::ID,
+            system_program: system_program::ID,
+            token_program: *token_program,
+            event_authority: event_authority(),
+            program: spinx_contract::ID,
+        },
+        instruction::RequestHouseWithdrawal { shares },
+    )
+}
+
+pub fn cancel_house_withdrawal(provider: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Instruction {
+    let house_vault = house_vault_address(mint);
+    let share_mint = share_mint_address(&house_vault);
+
+    spinx_ix(
+        accounts::CancelHouseWithdrawal {
+            provider: *provider,
+            house_vault,
+            house_withdrawal: house_withdrawal_address(&house_vault, provider),
+            share_mint,
+            vault_shares: ata(&house_vault, &share_mint, token_program),
+            provider_shares: ata(provider, &share_mint, token_program),
+            token_program: *token_program,
+            event_authority: event_authority(),
+            program: spinx_contract::ID,
+        },
+        instruction::CancelHouseWithdrawal {},
+    )
+}
+
+/// Drops `provider`'s request once its window has passed, returning the shares
+/// to their associated token account.
+pub fn expire_house_withdrawal(provider: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Instruction {
+    let house_vault = house_vault_address(mint);
+    let share_mint = share_mint_address(&house_vault);
+
+    spinx_ix(
+        accounts::ExpireHouseWithdrawal {
+            house_vault,
+            house_withdrawal: house_withdrawal_address(&house_vault, provider),
+            provider: *provider,
+            share_mint,
+            vault_shares: ata(&house_vault, &share_mint, token_program),
+            provider_shares: ata(provider, &share_mint, token_program),
+            token_program: *token_program,
+            event_authority: event_authority(),
+            program: spinx_contract::ID,
+        },
+        instruction::ExpireHouseWithdrawal {},
+    )
+}
+
+pub fn withdraw_house_liquidity(provider: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Instruction {
+    let house_vault = house_vault_address(mint);
+    let share_mint = share_mint_address(&house_vault);
+
+    spinx_ix(
+        accounts::WithdrawHouseLiquidity {
+            provider: *provider,
+            house_vault,
+            house_withdrawal: house_withdrawal_address(&house_vault, provider),
+            mint: *mint,
+            share_mint,
+            vault_ata: ata(&house_vault, mint, token_program),
+            provider_ata: ata(provider, mint, token_program),
+            vault_shares: ata(&house_vault, &share_mint, token_program),
+            token_program: *token_program,
+            event_authority: event_authority(),
+            program: spinx_contract::ID,
+        },
+        instruction::WithdrawHouseLiquidity {},
+    )
+}
+
+/// Flip `flip_id`, the vault's `next_flip_id`, against the house of `mint`.
+pub fn flip_vs_house(
+    player: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+    flip_id: u64,
+    global: &GlobalData,
+    referral: Referral,
+    randomness: Randomness,
+    set_number: u8,
+    amount: u64,
+) -> Instruction {
+    let house_vault = house_vault_address(mint);
+
+    spinx_ix(
+        accounts::FlipVsHouse {
+            player: *player,
+            global_data: global_data_address(),
+            player_ata: ata(player, mint, token_program),
+            mint: *mint,
+            mint_config: mint_config_address(mint),
+            house_vault,
+            vault_ata: ata(&house_vault, mint, token_program),
+            house_flip: house_flip_address(&house_vault, flip_id),
+            treasury_wallet: global.treasury_wallet,
+            player_referral: referral.player_referral,
+            referrer_referral: referral.referrer_referral,
+            random: randomness.random,
+            treasury: randomness.treasury,
+            config: orao_network_state_address(),
+            vrf: orao_solana_vrf::ID,
+            system_program: system_program::ID,
+            token_program: *token_program,
+            event_authority: event_authority(),
+            program: spinx_contract::ID,
+        },
+        instruction::FlipVsHouse { set_number, amount, force: randomness.force },
+    )
+}
+
+pub fn settle_house_flip(flip: &HouseFlip, token_program: &Pubkey) -> Instruction {
+    spinx_ix(
+        accounts::SettleHouseFlip {
+            house_flip: house_flip_address(&flip.house_vault, flip.flip_id),
+            house_vault: flip.house_vault,
+            vault_ata: ata(&flip.house_vault, &flip.mint, token_program),
+            mint: flip.mint,
+            player_ata: ata(&flip.player, &flip.mint, token_program),
+            random: flip.random_account(),
+            token_program: *token_program,
+            player: flip.player,
+            event_authority: event_authority(),
+            program: spinx_contract::ID,
+        },
+        instruction::SettleHouseFlip { flip_id: flip.flip_id },
+    )
+}
+
+pub fn refund_house_flip(flip: &HouseFlip, token_program: &Pubkey) -> Instruction {
+    spinx_ix(
+        accounts::RefundHouseFlip {
+            house_flip: house_flip_address(&flip.house_vault, flip.flip_id),
+            house_vault: flip.house_vault,
+            vault_ata: ata(&flip.house_vault, &flip.mint, token_program),
+            mint: flip.mint,
+            player_ata: ata(&flip.player, &flip.mint, token_program),
+            global_data: global_data_address(),
+            random: flip.random_account(),
+            token_program: *token_program,
+            player: flip.player,
+            event_authority: event_authority(),
+            program: spinx_contract::ID,
+        },
+        instruction::RefundHouseFlip { flip_id: flip.flip_id },
+    )
+}
+
+// Referrals
+
+pub fn register_referral(player: &Pubkey) -> Instruction {
+    spinx_ix(
+        accounts::RegisterReferral {
+            player: *player,
+            global_data: global_data_address(),
+            player_referral: player_referral_address(player),
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: spinx_contract::ID,
+        },
+        instruction::RegisterReferral {},
+    )
+}
+
+pub fn bind_referrer(player: &Pubkey, referrer: &Pubkey) -> Instruction {
+    spinx_ix(
+        accounts::BindReferrer {
+            player: *player,
+            global_data: global_data_address(),
+            player_referral: player_referral_address(player),
+            referrer_referral: player_referral_address(referrer),
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: spinx_contract::ID,
+        },
+        instruction::BindReferrer { referrer: *referrer },
+    )
+}
+
+pub fn claim_referral_rewards(player: &Pubkey) -> Instruction {
+    spinx_ix(
+        accounts::ClaimReferralRewards {
+            player: *player,
+            player_referral: player_referral_address(player),
+            event_authority: event_authority(),
+            program: spinx_contract::ID,
+        },
+        instruction::ClaimReferralRewards {},
+    )
+}
+
+// Seasons
+
+pub fn create_season(
+    admin: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+    start_ts: u64,
+    end_ts: u64,
+    metric: SeasonMetric,
+    prize_share_bps: u16,
+    payout_bps: Vec<u16>,
+) -> Instruction {
+    let season = season_address(mint);
+
+    spinx_ix(
+        accounts::CreateSeason {
+            admin: *admin,
+            global_data: global_data_address(),
+            mint: *mint,
+            mint_config: mint_config_address(mint),
+            season,
+            season_vault: ata(&season, mint, token_program),
+            associated_token_program: anchor_spl::associated_token::ID,
+            system_program: system_program::ID,
+            token_program: *token_program,
+            event_authority: event_authority(),
+            program: spinx_contract::ID,
+        },
+        instruction::CreateSeason { start_ts, end_ts, metric, prize_share_bps, payout_bps },
+    )
+}
+
+pub fn finalize_season(mint: &Pubkey, token_program: &Pubkey) -> Instruction {
+    let season = season_address(mint);
+
+    spinx_ix(
+        accounts::FinalizeSeason {
+            season,
+            season_vault: ata(&season, mint, token_program),
+            mint: *mint,
+            token_program: *token_program,
+            event_authority: event_authority(),
+            program: spinx_contract::ID,
+        },
+        instruction::FinalizeSeason {},
+    )
+}
+
+pub fn claim_season_prize(player: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Instruction {
+    let season = season_address(mint);
+
+    spinx_ix(
+        accounts::ClaimSeasonPrize {
+            player: *player,
+            season,
+            season_vault: ata(&season, mint, token_program),
+            mint: *mint,
+            player_ata: ata(player, mint, token_program),
+            token_program: *token_program,
+            event_authority: event_authority(),
+            program: spinx_contract::ID,
+        },
+        instruction::ClaimSeasonPrize {},
+    )
+}
+
+pub fn close_season(admin: &Pubkey, mint: &Pubkey, token_program: &Pubkey, global: &GlobalData) -> Instruction {
+    let season = season_address(mint);
+
+    spinx_ix(
+        accounts::CloseSeason {
+            admin: *admin,
+            global_data: global_data_address(),
+            season,
+            season_vault: ata(&season, mint, token_program),
+            mint: *mint,
+            treasury_ata: ata(&global.treasury_wallet, mint, token_program),
+            token_program: *token_program,
+            event_authority: event_authority(),
+            program: spinx_contract::ID,
+        },
+        instruction::CloseSeason {},
+    )
+}
+
+/// Fulfills a mock randomness request, signed by the admin.
+#[cfg(feature = "mock-vrf")]
+pub fn mock_fulfill(admin: &Pubkey, force: [u8; 32], randomness: [u8; 64]) -> Instruction {
+    spinx_ix(
+        accounts::MockFulfill {
+            admin: *admin,
+            global_data: global_data_address(),
+            random: randomness_address(&force),
+        },
+        instruction::MockFulfill { force, randomness },
+    )
+}
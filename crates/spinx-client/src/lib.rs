@@ -0,0 +1,14 @@
+//! Client side of the spinx program: PDA derivation, instruction builders,
+//! account decoders and error codes, for services that build their own
+//! transactions.
+//!
+//! Nothing here talks to a cluster. Callers fetch the accounts the builders
+//! need, decode them with `state` and send the instructions with their own
+//! RPC client.
+
+pub mod error;
+pub mod instructions;
+pub mod pda;
+pub mod state;
+
+pub use spinx_contract::ID;
@@ -0,0 +1,87 @@
+//! Addresses of the program's accounts, with the seeds the program checks.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
+use orao_solana_vrf::CONFIG_ACCOUNT_SEED;
+use spinx_contract::account::CoinflipPool;
+use spinx_contract::randomness;
+use spinx_contract::utils::*;
+
+pub fn global_data_address() -> Pubkey {
+    Pubkey::find_program_address(&[GLOBAL_AUTHORITY_SEED.as_bytes()], &spinx_contract::ID).0
+}
+
+pub fn mint_config_address(mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[MINT_CONFIG_SEED.as_bytes(), mint.as_ref()], &spinx_contract::ID).0
+}
+
+/// Pool ids are seeded little-endian.
+pub fn pool_address(pool_id: u64) -> Pubkey {
+    Pubkey::find_program_address(&[COINFLIP_SEED.as_bytes(), &pool_id.to_le_bytes()], &spinx_contract::ID).0
+}
+
+pub fn sol_escrow_address(pool: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[SOL_ESCROW_SEED.as_bytes(), pool.as_ref()], &spinx_contract::ID).0
+}
+
+pub fn jackpot_address(round_id: u64) -> Pubkey {
+    Pubkey::find_program_address(&[JACKPOT_SEED.as_bytes(), &round_id.to_le_bytes()], &spinx_contract::ID).0
+}
+
+pub fn house_vault_address(mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[HOUSE_VAULT_SEED.as_bytes(), mint.as_ref()], &spinx_contract::ID).0
+}
+
+pub fn house_flip_address(house_vault: &Pubkey, flip_id: u64) -> Pubkey {
+    Pubkey::find_program_address(&[HOUSE_FLIP_SEED.as_bytes(), house_vault.as_ref(), &flip_id.to_le_bytes()], &spinx_contract::ID).0
+}
+
+pub fn share_mint_address(house_vault: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[HOUSE_SHARES_SEED.as_bytes(), house_vault.as_ref()], &spinx_contract::ID).0
+}
+
+pub fn house_withdrawal_address(house_vault: &Pubkey, owner: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[HOUSE_WITHDRAWAL_SEED.as_bytes(), house_vault.as_ref(), owner.as_ref()], &spinx_contract::ID).0
+}
+
+pub fn player_referral_address(player: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[PLAYER_REFERRAL_SEED.as_bytes(), player.as_ref()], &spinx_contract::ID).0
+}
+
+/// Profiles of SOL pools are kept under the native mint.
+pub fn player_profile_address(player: &Pubkey, mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[PLAYER_PROFILE_SEED.as_bytes(), player.as_ref(), mint.as_ref()], &spinx_contract::ID).0
+}
+
+pub fn season_address(mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[SEASON_SEED.as_bytes(), mint.as_ref()], &spinx_contract::ID).0
+}
+
+/// Registry shard of a mint's pools with stakes of `stake_tier`, see `stake_tier` in `utils.rs`.
+pub fn pool_registry_address(mint: &Pubkey, stake_tier: u8) -> Pubkey {
+    Pubkey::find_program_address(&[POOL_REGISTRY_SEED.as_bytes(), mint.as_ref(), &[stake_tier]], &spinx_contract::ID).0
+}
+
+/// The registry shard a pool is listed in, from its recorded stake tier.
+pub fn pool_registry_of(pool: &CoinflipPool) -> Pubkey {
+    pool_registry_address(&pool.mint, pool.stake_tier)
+}
+
+/// Signer of the events emitted through `emit_cpi!`.
+pub fn event_authority() -> Pubkey {
+    Pubkey::find_program_address(&[b"__event_authority"], &spinx_contract::ID).0
+}
+
+pub fn ata(owner: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Pubkey {
+    get_associated_token_address_with_program_id(owner, mint, token_program)
+}
+
+/// ORAO randomness request for `force`, the mock provider's account with `mock-vrf`.
+pub fn randomness_address(force: &[u8; 32]) -> Pubkey {
+    randomness::randomness_address(force)
+}
+
+/// ORAO network state, the `config` account of instructions requesting randomness.
+pub fn orao_network_state_address() -> Pubkey {
+    Pubkey::find_program_address(&[CONFIG_ACCOUNT_SEED], &orao_solana_vrf::ID).0
+}
@@ -0,0 +1,53 @@
+//! Decoders for the program's accounts. Each checks the account discriminator.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::{AccountDeserialize, AnchorDeserialize, Discriminator, Result};
+use anchor_spl::token_2022::spl_token_2022::extension::transfer_fee::TransferFeeConfig;
+use anchor_spl::token_2022::spl_token_2022::extension::{BaseStateWithExtensions, StateWithExtensions};
+use anchor_spl::token_2022::spl_token_2022::state::Mint;
+use orao_solana_vrf::state::NetworkState;
+
+pub use spinx_contract::account::*;
+
+/// Decodes the data of any program account.
+pub fn decode<T: AccountDeserialize>(data: &[u8]) -> Result<T> {
+    T::try_deserialize(&mut &data[..])
+}
+
+pub fn decode_global_data(data: &[u8]) -> Result<GlobalData> {
+    decode(data)
+}
+
+pub fn decode_mint_config(data: &[u8]) -> Result<MintConfig> {
+    decode(data)
+}
+
+pub fn decode_coinflip_pool(data: &[u8]) -> Result<CoinflipPool> {
+    decode(data)
+}
+
+pub fn decode_pool_registry(data: &[u8]) -> Result<PoolRegistry> {
+    decode(data)
+}
+
+/// Treasury of the ORAO network state, paid by instructions requesting randomness.
+pub fn decode_orao_treasury(data: &[u8]) -> Result<Pubkey> {
+    decode::<NetworkState>(data).map(|state| state.config.treasury)
+}
+
+/// Transfer fee a mint charges on `amount` in `epoch`, 0 for mints without the
+/// Token-2022 extension.
+pub fn decode_transfer_fee(mint_data: &[u8], epoch: u64, amount: u64) -> u64 {
+    StateWithExtensions::<Mint>::unpack(mint_data)
+        .ok()
+        .and_then(|state| state.get_extension::<TransferFeeConfig>().ok().copied())
+        .and_then(|config| config.calculate_epoch_fee(epoch, amount))
+        .unwrap_or(0)
+}
+
+/// Decodes an event from the data of its `emit_cpi!` self-invocation, the
+/// event's discriminator followed by its fields. `None` for other events.
+pub fn decode_event<T: Discriminator + AnchorDeserialize>(data: &[u8]) -> Option<T> {
+    let data = data.strip_prefix(anchor_lang::event::EVENT_IX_TAG_LE)?;
+    T::deserialize(&mut data.strip_prefix(T::DISCRIMINATOR)?).ok()
+}
@@ -0,0 +1,58 @@
+//! Gates the suite behind a real SBF build of the program.
+//!
+//! The tests run spinx natively on the small runtime in `src/svm.rs`, which
+//! knows nothing of the SBF loader, compute budget or stack limits. Its results
+//! only count next to a `spinx_contract.so` built from the same sources, so the
+//! tests are compiled in (`cfg(sbf_build)`) when `anchor build` or
+//! `cargo build-sbf` left one that is newer than the program, and skipped
+//! otherwise. `SPINX_TESTS_NATIVE=1` runs them without it.
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+fn main() {
+    println!("cargo:rustc-check-cfg=cfg(sbf_build)");
+    println!("cargo:rerun-if-env-changed=SBF_OUT_DIR");
+    println!("cargo:rerun-if-env-changed=SPINX_TESTS_NATIVE");
+
+    let manifest_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+    let sources = manifest_dir.join("../../programs/spinx-contract/src");
+    let out_dir = env::var_os("SBF_OUT_DIR").map_or_else(|| manifest_dir.join("../../target/deploy"), PathBuf::from);
+    let program = out_dir.join("spinx_contract.so");
+
+    println!("cargo:rerun-if-changed={}", sources.display());
+    println!("cargo:rerun-if-changed={}", program.display());
+
+    if env::var_os("SPINX_TESTS_NATIVE").is_some() {
+        println!("cargo:rustc-cfg=sbf_build");
+        return;
+    }
+
+    match modified(&program) {
+        Some(built) if built >= newest(&sources) => println!("cargo:rustc-cfg=sbf_build"),
+        Some(_) => println!(
+            "cargo:warning=spinx tests skipped, {} is older than the program, rebuild it with `anchor build`",
+            program.display()
+        ),
+        None => println!(
+            "cargo:warning=spinx tests skipped, build the program with `anchor build` first or set SPINX_TESTS_NATIVE=1"
+        ),
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+/// Latest modification of any file under `dir`.
+fn newest(dir: &Path) -> SystemTime {
+    let entries = fs::read_dir(dir).into_iter().flatten().flatten();
+
+    entries.fold(SystemTime::UNIX_EPOCH, |latest, entry| {
+        let path = entry.path();
+        let modified = if path.is_dir() { newest(&path) } else { modified(&path).unwrap_or(SystemTime::UNIX_EPOCH) };
+        latest.max(modified)
+    })
+}
@@ -0,0 +1,764 @@
+//! A deployed and initialized spinx with one allowlisted SPL mint, plus
+//! builders for its instructions.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::{instruction::Instruction, program_error::ProgramError, program_pack::Pack, rent::Rent};
+use anchor_lang::{AccountDeserialize, AccountSerialize, AnchorDeserialize, AnchorSerialize, Discriminator, InstructionData};
+use anchor_spl::token::spl_token::native_mint;
+use orao_solana_vrf::state::{FulfilledRequest, NetworkConfiguration, NetworkState, RandomnessV2, RequestAccount};
+use spinx_client::instructions::{self as ix, Randomness, Referral};
+use spinx_client::state;
+use spinx_contract::account::{CoinflipPool, GlobalData, HouseFlip, HouseVault, HouseWithdrawal, JackpotRound, MintConfig, PlayerProfile, PlayerReferral, PoolRegistry, RandomnessProvider, Season, SeasonMetric};
+use spinx_contract::error::SpinXError;
+use spinx_contract::instruction;
+use spinx_contract::randomness::switchboard;
+use spinx_contract::utils::*;
+use solana_system_interface::instruction as system_instruction;
+use spl_associated_token_account::instruction::create_associated_token_account_idempotent;
+use spl_token_2022::extension::ExtensionType;
+
+use crate::programs::{orao_network_state, orao_randomness};
+use crate::svm::{Account, Svm};
+
+pub use spinx_client::instructions::PoolOptions;
+pub use spinx_client::pda::*;
+
+pub const SOL: u64 = 1_000_000_000;
+pub const DECIMALS: u8 = 9;
+/// Stake used by most tests, above the 10 token default minimum.
+pub const STAKE: u64 = 20 * SOL;
+pub const ORAO_FEE: u64 = 1_000_000;
+pub const WITHDRAWAL_DELAY: u64 = 24 * 60 * 60;
+pub const BANKROLL: u64 = 1_000 * SOL;
+pub const HOUSE_EDGE_BPS: u16 = 200;
+pub const HOUSE_MAX_BET_BPS: u16 = 500;
+
+/// The error a failed spinx instruction returns.
+pub fn spinx_error(error: SpinXError) -> ProgramError {
+    ProgramError::Custom(error.into())
+}
+
+/// The error an Anchor account constraint returns.
+pub fn anchor_error(error: anchor_lang::error::ErrorCode) -> ProgramError {
+    ProgramError::Custom(error.into())
+}
+
+/// `instruction` with every `from` account replaced by `to`.
+pub fn with_account(mut instruction: Instruction, from: &Pubkey, to: &Pubkey) -> Instruction {
+    for meta in instruction.accounts.iter_mut().filter(|meta| meta.pubkey == *from) {
+        meta.pubkey = *to;
+    }
+    instruction
+}
+
+/// A fresh key on the ed25519 curve, the only kind that can sign a transaction.
+pub fn wallet() -> Pubkey {
+    loop {
+        let key = Pubkey::new_unique();
+        if key.is_on_curve() {
+            return key;
+        }
+    }
+}
+
+/// Seed of the ORAO request, one per join.
+pub fn force(n: u8) -> [u8; 32] {
+    [n; 32]
+}
+
+/// A house for the default mint holding BANKROLL deposited by the admin, and
+/// a player holding 100 tokens.
+pub fn house_env() -> (Env, Pubkey) {
+    let mut env = Env::new();
+    env.house(HOUSE_EDGE_BPS, HOUSE_MAX_BET_BPS, BANKROLL);
+    let player = env.player(100 * SOL);
+    (env, player)
+}
+
+/// Tokens held by the default mint's house vault.
+pub fn vault_balance(env: &Env) -> u64 {
+    env.token_balance(&house_vault_address(&env.mint), &env.mint)
+}
+
+pub struct Env {
+    pub svm: Svm,
+    pub admin: Pubkey,
+    pub treasury: Pubkey,
+    pub mint: Pubkey,
+    pub orao_treasury: Pubkey,
+}
+
+impl Default for Env {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Env {
+    /// Initialized program, the ORAO network state and an allowlisted SPL mint.
+    pub fn new() -> Self {
+        let mut env = Self::uninitialized();
+        let admin = env.admin;
+
+        env.svm.process(env.initialize_ix(&admin)).unwrap();
+
+        let mint = env.mint;
+        env.svm.process(env.add_mint_ix(&mint)).unwrap();
+        env.create_token_account(&env.treasury.clone(), &mint, 0);
+
+        env
+    }
+
+    /// Everything but the `initialize` and `add_mint` calls.
+    pub fn uninitialized() -> Self {
+        let mut svm = Svm::new();
+        let admin = wallet();
+        let treasury = TREASURY_WALLET.parse::<Pubkey>().unwrap();
+        let orao_treasury = wallet();
+        let mint = wallet();
+
+        svm.airdrop(&admin, 100 * SOL);
+        svm.airdrop(&treasury, SOL);
+        svm.airdrop(&orao_treasury, SOL);
+
+        let network_state = NetworkState {
+            config: NetworkConfiguration {
+                authority: wallet(),
+                treasury: orao_treasury,
+                request_fee: ORAO_FEE,
+                fulfillment_authorities: vec![wallet()],
+                token_fee_config: None,
+            },
+            num_received: 0,
+        };
+        let mut data = Vec::new();
+        network_state.try_serialize(&mut data).unwrap();
+        svm.set_account(orao_network_state(), Account::new(SOL, data, orao_solana_vrf::ID));
+
+        let mut env = Self {
+            svm,
+            admin,
+            treasury,
+            mint,
+            orao_treasury,
+        };
+        env.create_mint(&mint, &anchor_spl::token::ID, None);
+        env
+    }
+
+    pub fn create_mint(&mut self, mint: &Pubkey, token_program: &Pubkey, freeze_authority: Option<Pubkey>) {
+        let mut data = vec![0; spl_token::state::Mint::LEN];
+        spl_token::state::Mint {
+            mint_authority: Some(self.admin).into(),
+            supply: 0,
+            decimals: DECIMALS,
+            is_initialized: true,
+            freeze_authority: freeze_authority.into(),
+        }
+        .pack_into_slice(&mut data);
+
+        let lamports = Rent::default().minimum_balance(data.len());
+        self.svm.set_account(*mint, Account::new(lamports, data, *token_program));
+    }
+
+    /// Creates a Token-2022 mint through the token program. `extensions` are
+    /// the instructions initializing `extension_types`, run before InitializeMint2.
+    pub fn create_token_2022_mint(
+        &mut self,
+        mint: &Pubkey,
+        freeze_authority: Option<&Pubkey>,
+        extension_types: &[ExtensionType],
+        extensions: Vec<Instruction>,
+    ) {
+        let space = ExtensionType::try_calculate_account_len::<spl_token_2022::state::Mint>(extension_types).unwrap();
+        let mut instructions = vec![system_instruction::create_account(
+            &self.admin,
+            mint,
+            Rent::default().minimum_balance(space),
+            space as u64,
+            &spl_token_2022::ID,
+        )];
+        instructions.extend(extensions);
+        instructions.push(
+            spl_token_2022::instruction::initialize_mint2(&spl_token_2022::ID, mint, &self.admin, freeze_authority, DECIMALS).unwrap(),
+        );
+
+        self.svm.process_transaction(&instructions).unwrap();
+    }
+
+    /// Creates the associated token account of `owner` through the ATA program
+    /// and mints `amount` into it, for mints whose accounts need extensions.
+    pub fn mint_to(&mut self, owner: &Pubkey, mint: &Pubkey, amount: u64) -> Pubkey {
+        let token_program = self.svm.account(mint).unwrap().owner;
+        let address = ata(owner, mint, &token_program);
+
+        let instructions = [
+            create_associated_token_account_idempotent(&self.admin, owner, mint, &token_program),
+            spl_token_2022::instruction::mint_to_checked(&token_program, mint, &address, &self.admin, &[], amount, DECIMALS)
+                .unwrap(),
+        ];
+        self.svm.process_transaction(&instructions).unwrap();
+        address
+    }
+
+    /// Sends `amount` from `owner`'s associated token account to any token
+    /// account, like a stranger sending tokens to an escrow.
+    pub fn send_tokens(&mut self, owner: &Pubkey, mint: &Pubkey, destination: &Pubkey, amount: u64) {
+        let token_program = self.svm.account(mint).unwrap().owner;
+        let source = ata(owner, mint, &token_program);
+
+        let ix = spl_token_2022::instruction::transfer_checked(&token_program, &source, mint, destination, owner, &[], amount, DECIMALS)
+            .unwrap();
+        self.svm.process(ix).unwrap();
+    }
+
+    /// Writes an initialized associated token account holding `amount`.
+    pub fn create_token_account(&mut self, owner: &Pubkey, mint: &Pubkey, amount: u64) -> Pubkey {
+        let token_program = self.svm.account(mint).unwrap().owner;
+        let address = ata(owner, mint, &token_program);
+
+        let mut data = vec![0; spl_token::state::Account::LEN];
+        spl_token::state::Account {
+            mint: *mint,
+            owner: *owner,
+            amount,
+            state: spl_token::state::AccountState::Initialized,
+            ..Default::default()
+        }
+        .pack_into_slice(&mut data);
+
+        let lamports = Rent::default().minimum_balance(data.len());
+        self.svm.set_account(address, Account::new(lamports, data, token_program));
+        address
+    }
+
+    /// A funded player with `tokens` of the default mint.
+    pub fn player(&mut self, tokens: u64) -> Pubkey {
+        let player = wallet();
+        self.svm.airdrop(&player, 10 * SOL);
+        let mint = self.mint;
+        self.create_token_account(&player, &mint, tokens);
+        player
+    }
+
+    pub fn token_balance(&self, owner: &Pubkey, mint: &Pubkey) -> u64 {
+        let token_program = self.svm.account(mint).unwrap().owner;
+        self.svm
+            .account(&ata(owner, mint, &token_program))
+            .map_or(0, |account| spl_token_2022::state::Account::unpack_from_slice(&account.data[..165]).unwrap().amount)
+    }
+
+    fn read<T: AccountDeserialize>(&self, address: &Pubkey) -> Option<T> {
+        self.svm
+            .account(address)
+            .map(|account| T::try_deserialize(&mut &account.data[..]).unwrap())
+    }
+
+    pub fn global_data(&self) -> GlobalData {
+        self.read(&global_data_address()).unwrap()
+    }
+
+    pub fn mint_config(&self, mint: &Pubkey) -> Option<MintConfig> {
+        self.read(&mint_config_address(mint))
+    }
+
+    pub fn pool(&self, pool_id: u64) -> Option<CoinflipPool> {
+        self.read(&pool_address(pool_id))
+    }
+
+    pub fn jackpot(&self, round_id: u64) -> Option<JackpotRound> {
+        self.read(&jackpot_address(round_id))
+    }
+
+    pub fn house_vault(&self, mint: &Pubkey) -> Option<HouseVault> {
+        self.read(&house_vault_address(mint))
+    }
+
+    pub fn house_flip(&self, mint: &Pubkey, flip_id: u64) -> Option<HouseFlip> {
+        self.read(&house_flip_address(&house_vault_address(mint), flip_id))
+    }
+
+    pub fn house_withdrawal(&self, owner: &Pubkey) -> Option<HouseWithdrawal> {
+        self.read(&house_withdrawal_address(&house_vault_address(&self.mint), owner))
+    }
+
+    pub fn player_referral(&self, player: &Pubkey) -> Option<PlayerReferral> {
+        self.read(&player_referral_address(player))
+    }
+
+    pub fn profile(&self, player: &Pubkey, mint: &Pubkey) -> Option<PlayerProfile> {
+        self.read(&player_profile_address(player, mint))
+    }
+
+    /// Overwrites the stored profile of `profile.player` in `profile.mint`.
+    pub fn set_profile(&mut self, profile: &PlayerProfile) {
+        let address = player_profile_address(&profile.player, &profile.mint);
+        let mut account = self.svm.account(&address).unwrap().clone();
+        profile.try_serialize(&mut &mut account.data[..]).unwrap();
+        self.svm.set_account(address, account);
+    }
+
+    pub fn pool_registry(&self, mint: &Pubkey, stake_tier: u8) -> Option<PoolRegistry> {
+        self.read(&pool_registry_address(mint, stake_tier))
+    }
+
+    /// Ids of the pools listed in a registry shard, oldest first.
+    pub fn lobby(&self, mint: &Pubkey, stake_tier: u8) -> Vec<u64> {
+        self.pool_registry(mint, stake_tier).map_or(vec![], |registry| registry.pools.iter().map(|entry| entry.pool_id).collect())
+    }
+
+    /// The registry shard a pool is listed in, from its recorded stake tier.
+    pub fn registry_of(&self, pool_id: u64) -> Pubkey {
+        self.pool(pool_id).map_or(pool_registry_address(&self.mint, 0), |pool| pool_registry_address(&pool.mint, pool.stake_tier))
+    }
+
+    pub fn season(&self, mint: &Pubkey) -> Option<Season> {
+        self.read(&season_address(mint))
+    }
+
+    /// The optional referral accounts of a fee paying instruction, passed
+    /// whenever `player` is registered, like a client would.
+    pub fn referral(&self, player: &Pubkey) -> Referral {
+        Referral::of(player, self.player_referral(player).as_ref())
+    }
+
+    pub fn token_program(&self, mint: &Pubkey) -> Pubkey {
+        self.svm.account(mint).unwrap().owner
+    }
+
+    /// LP shares of the default mint's house vault held by `owner`.
+    pub fn shares(&self, owner: &Pubkey) -> u64 {
+        self.token_balance(owner, &share_mint_address(&house_vault_address(&self.mint)))
+    }
+
+    /// Events of type `T` emitted by the last transaction.
+    pub fn events<T: Discriminator + AnchorDeserialize>(&self) -> Vec<T> {
+        self.svm
+            .events()
+            .iter()
+            .filter(|event| event.data.starts_with(T::DISCRIMINATOR))
+            .map(|event| T::deserialize(&mut &event.data[T::DISCRIMINATOR.len()..]).unwrap())
+            .collect()
+    }
+
+    pub fn next_pool_id(&self) -> u64 {
+        self.global_data().next_pool_id
+    }
+
+    pub fn next_jackpot_id(&self) -> u64 {
+        self.global_data().next_jackpot_id
+    }
+
+    /// Fulfills the ORAO request for `force`, the low byte decides the coin.
+    pub fn fulfill(&mut self, force: [u8; 32], randomness: u64) {
+        let address = orao_randomness(&force);
+        let mut account = self.svm.account(&address).expect("randomness was not requested").clone();
+
+        let mut value = [0u8; 64];
+        value[..8].copy_from_slice(&randomness.to_le_bytes());
+        let fulfilled = RandomnessV2 {
+            request: RequestAccount::Fulfilled(FulfilledRequest {
+                client: spinx_contract::ID,
+                seed: force,
+                randomness: value,
+            }),
+        };
+
+        account.data.fill(0);
+        fulfilled.try_serialize(&mut &mut account.data[..]).unwrap();
+        self.svm.set_account(address, account);
+    }
+
+    /// Selects Switchboard for new requests, returning the queue it accepts.
+    pub fn use_switchboard(&mut self) -> Pubkey {
+        let queue = wallet();
+        let admin = self.admin;
+        let ix = self.admin_ix(
+            &admin,
+            instruction::SetRandomnessProvider {
+                provider: RandomnessProvider::Switchboard,
+                switchboard_queue: queue,
+            },
+        );
+        self.svm.process(ix).unwrap();
+        queue
+    }
+
+    /// Writes a Switchboard randomness account on `queue` committed at
+    /// `seed_slot`, revealed with `value` when `reveal_slot` is past it.
+    pub fn switchboard_randomness(&mut self, address: &Pubkey, queue: &Pubkey, seed_slot: u64, reveal_slot: u64, value: u64) {
+        let mut revealed = [0u8; 32];
+        revealed[..8].copy_from_slice(&value.to_le_bytes());
+
+        let mut data = switchboard::DISCRIMINATOR.to_vec();
+        switchboard::RandomnessAccountData {
+            authority: wallet(),
+            queue: *queue,
+            seed_slothash: [0; 32],
+            seed_slot,
+            oracle: wallet(),
+            reveal_slot,
+            value: revealed,
+        }
+        .serialize(&mut data)
+        .unwrap();
+
+        let lamports = Rent::default().minimum_balance(data.len());
+        self.svm.set_account(*address, Account::new(lamports, data, SWITCHBOARD_PROGRAM_ID));
+    }
+
+    pub fn initialize_ix(&self, admin: &Pubkey) -> Instruction {
+        ix::initialize(admin)
+    }
+
+    /// Any of the `UpdateGlobalData` setters signed by `admin`.
+    pub fn admin_ix(&self, admin: &Pubkey, data: impl InstructionData) -> Instruction {
+        ix::update_global_data(admin, data)
+    }
+
+    pub fn accept_admin_ix(&self, new_admin: &Pubkey) -> Instruction {
+        ix::accept_admin(new_admin)
+    }
+
+    pub fn pause_ix(&self, authority: &Pubkey, pause_flags: u32) -> Instruction {
+        ix::pause(authority, pause_flags)
+    }
+
+    pub fn migrate_global_data_ix(&self, admin: &Pubkey) -> Instruction {
+        ix::migrate_global_data(admin)
+    }
+
+    pub fn migrate_pool_ix(&self, payer: &Pubkey, pool_id: u64) -> Instruction {
+        ix::migrate_coinflip_pool(payer, pool_id)
+    }
+
+    pub fn add_mint_ix(&self, mint: &Pubkey) -> Instruction {
+        ix::add_mint(&self.admin, mint, Pubkey::default())
+    }
+
+    pub fn add_mint_approving_ix(&self, mint: &Pubkey, freeze_authority: &Pubkey) -> Instruction {
+        ix::add_mint(&self.admin, mint, *freeze_authority)
+    }
+
+    pub fn mint_config_ix(&self, admin: &Pubkey, mint: &Pubkey, data: impl InstructionData) -> Instruction {
+        ix::update_mint_config(admin, mint, data)
+    }
+
+    pub fn create_ix(&self, creator: &Pubkey, mint: &Pubkey, set_number: u8, amount: u64, ttl: u64) -> Instruction {
+        self.create_ix_with(creator, mint, set_number, amount, PoolOptions { ttl, ..Default::default() })
+    }
+
+    pub fn create_ix_with(&self, creator: &Pubkey, mint: &Pubkey, set_number: u8, amount: u64, options: PoolOptions) -> Instruction {
+        // Listed by what the escrow receives, as a client computes it from the mint
+        let epoch = self.svm.clock().epoch;
+        let transfer_fee = self.svm.account(mint).map_or(0, |account| state::decode_transfer_fee(&account.data, epoch, amount));
+        let options = PoolOptions { transfer_fee, ..options };
+
+        ix::create_coinflip(
+            creator,
+            mint,
+            &self.token_program(mint),
+            &self.global_data(),
+            self.referral(creator),
+            set_number,
+            amount,
+            options,
+        )
+    }
+
+    /// Creates a pool in the default mint and returns its id.
+    pub fn create_pool(&mut self, creator: &Pubkey, set_number: u8, amount: u64) -> Result<u64, ProgramError> {
+        let pool_id = self.next_pool_id();
+        let ix = self.create_ix(creator, &self.mint.clone(), set_number, amount, 0);
+        self.svm.process(ix)?;
+        Ok(pool_id)
+    }
+
+    pub fn join_ix(&self, pool_id: u64, joiner: &Pubkey, force: [u8; 32], set_number: u8, amount: u64) -> Instruction {
+        let pool = self.pool(pool_id).unwrap();
+
+        ix::join_coinflip(
+            joiner,
+            &pool,
+            &self.token_program(&pool.mint),
+            &self.global_data(),
+            self.referral(joiner),
+            Randomness::orao(force, self.orao_treasury),
+            set_number,
+            amount,
+        )
+    }
+
+    pub fn join_pool(&mut self, pool_id: u64, joiner: &Pubkey, force: [u8; 32], set_number: u8, amount: u64) -> Result<(), ProgramError> {
+        let ix = self.join_ix(pool_id, joiner, force, set_number, amount);
+        self.svm.process(ix)
+    }
+
+    /// Plays one pool of `amount` between the two players, the creator on 0 and the joiner on 1.
+    /// Even randomness makes the creator win.
+    pub fn play(&mut self, creator: &Pubkey, joiner: &Pubkey, n: u8, amount: u64, randomness: u64) {
+        let pool_id = self.create_pool(creator, 0, amount).unwrap();
+        self.join_pool(pool_id, joiner, force(n), 1, amount).unwrap();
+        self.fulfill(force(n), randomness);
+        self.svm.process(self.result_ix(pool_id, force(n))).unwrap();
+    }
+
+    pub fn close_ix(&self, pool_id: u64, signer: &Pubkey) -> Instruction {
+        let pool = self.pool(pool_id).unwrap();
+        ix::close_coinflip(signer, &pool, &self.token_program(&pool.mint))
+    }
+
+    /// Makes an invite-only pool of either kind public.
+    pub fn open_ix(&self, pool_id: u64, creator: &Pubkey) -> Instruction {
+        ix::open_coinflip(creator, &self.pool(pool_id).unwrap())
+    }
+
+    pub fn expire_ix(&self, pool_id: u64) -> Instruction {
+        let pool = self.pool(pool_id).unwrap();
+        ix::expire_coinflip(&pool, &self.token_program(&pool.mint))
+    }
+
+    /// Settles with `force`, which the program checks against the pool's.
+    pub fn result_ix(&self, pool_id: u64, force: [u8; 32]) -> Instruction {
+        let pool = self.pool(pool_id).unwrap();
+        let ix = ix::result_coinflip(&pool, &self.token_program(&pool.mint), &self.global_data());
+        Instruction { data: instruction::ResultCoinflip { pool_id, force }.data(), ..ix }
+    }
+
+    pub fn refund_ix(&self, pool_id: u64) -> Instruction {
+        let pool = self.pool(pool_id).unwrap();
+        ix::refund_coinflip(&pool, &self.token_program(&pool.mint))
+    }
+
+    /// Allowlists SOL pools through the native mint.
+    pub fn enable_sol(&mut self) {
+        let ix = self.add_mint_ix(&native_mint::ID);
+        self.svm.set_account(
+            native_mint::ID,
+            Account::new(SOL, native_mint_data(), anchor_spl::token::ID),
+        );
+        self.svm.process(ix).unwrap();
+    }
+
+    pub fn create_sol_ix(&self, creator: &Pubkey, set_number: u8, amount: u64, ttl: u64) -> Instruction {
+        self.create_sol_ix_with(creator, set_number, amount, PoolOptions { ttl, ..Default::default() })
+    }
+
+    pub fn create_sol_ix_with(&self, creator: &Pubkey, set_number: u8, amount: u64, options: PoolOptions) -> Instruction {
+        ix::create_sol_coinflip(creator, &self.global_data(), self.referral(creator), set_number, amount, options)
+    }
+
+    /// Creates a SOL pool and returns its id.
+    pub fn create_sol_pool(&mut self, creator: &Pubkey, set_number: u8, amount: u64, ttl: u64) -> Result<u64, ProgramError> {
+        let pool_id = self.next_pool_id();
+        let ix = self.create_sol_ix(creator, set_number, amount, ttl);
+        self.svm.process(ix)?;
+        Ok(pool_id)
+    }
+
+    pub fn join_sol_ix(&self, pool_id: u64, joiner: &Pubkey, force: [u8; 32], set_number: u8, amount: u64) -> Instruction {
+        ix::join_sol_coinflip(
+            joiner,
+            &self.pool(pool_id).unwrap(),
+            &self.global_data(),
+            self.referral(joiner),
+            Randomness::orao(force, self.orao_treasury),
+            set_number,
+            amount,
+        )
+    }
+
+    pub fn join_sol_pool(&mut self, pool_id: u64, joiner: &Pubkey, force: [u8; 32], set_number: u8, amount: u64) -> Result<(), ProgramError> {
+        let ix = self.join_sol_ix(pool_id, joiner, force, set_number, amount);
+        self.svm.process(ix)
+    }
+
+    pub fn close_sol_ix(&self, pool_id: u64, signer: &Pubkey) -> Instruction {
+        ix::close_sol_coinflip(signer, &self.pool(pool_id).unwrap(), &self.global_data())
+    }
+
+    pub fn expire_sol_ix(&self, pool_id: u64) -> Instruction {
+        ix::expire_sol_coinflip(&self.pool(pool_id).unwrap(), &self.global_data())
+    }
+
+    /// Settles with `force`, which the program checks against the pool's.
+    pub fn result_sol_ix(&self, pool_id: u64, force: [u8; 32]) -> Instruction {
+        let ix = ix::result_sol_coinflip(&self.pool(pool_id).unwrap(), &self.global_data());
+        Instruction { data: instruction::ResultSolCoinflip { pool_id, force }.data(), ..ix }
+    }
+
+    pub fn refund_sol_ix(&self, pool_id: u64) -> Instruction {
+        ix::refund_sol_coinflip(&self.pool(pool_id).unwrap(), &self.global_data())
+    }
+
+    pub fn create_jackpot_ix(&self, creator: &Pubkey, mint: &Pubkey, duration: u64) -> Instruction {
+        ix::create_jackpot(creator, mint, &self.token_program(mint), &self.global_data(), duration)
+    }
+
+    /// Creates a round of the default mint and returns its id.
+    pub fn create_jackpot(&mut self, creator: &Pubkey, duration: u64) -> Result<u64, ProgramError> {
+        let round_id = self.next_jackpot_id();
+        let ix = self.create_jackpot_ix(creator, &self.mint.clone(), duration);
+        self.svm.process(ix).map(|_| round_id)
+    }
+
+    pub fn enter_jackpot_ix(&self, round_id: u64, player: &Pubkey, amount: u64) -> Instruction {
+        let round = self.jackpot(round_id).unwrap();
+        ix::enter_jackpot(player, &round, &self.token_program(&round.mint), &self.global_data(), self.referral(player), amount)
+    }
+
+    pub fn enter_jackpot(&mut self, round_id: u64, player: &Pubkey, amount: u64) -> Result<(), ProgramError> {
+        let ix = self.enter_jackpot_ix(round_id, player, amount);
+        self.svm.process(ix)
+    }
+
+    pub fn draw_jackpot_ix(&self, round_id: u64, payer: &Pubkey, force: [u8; 32]) -> Instruction {
+        ix::draw_jackpot(payer, round_id, Randomness::orao(force, self.orao_treasury))
+    }
+
+    /// Settles the round paying `winner`'s associated token account.
+    pub fn settle_jackpot_ix(&self, round_id: u64, winner: &Pubkey) -> Instruction {
+        let round = self.jackpot(round_id).unwrap();
+        ix::settle_jackpot(&round, &self.token_program(&round.mint), &self.global_data(), winner)
+    }
+
+    /// Refunds the deposits of `players`, passing their associated token accounts.
+    pub fn refund_jackpot_ix(&self, round_id: u64, players: &[Pubkey]) -> Instruction {
+        let round = self.jackpot(round_id).unwrap();
+        ix::refund_jackpot(&round, &self.token_program(&round.mint), &self.global_data(), players)
+    }
+
+    pub fn create_house_vault_ix(&self, mint: &Pubkey, edge_bps: u16, max_bet_bps: u16, withdrawal_delay: u64) -> Instruction {
+        ix::create_house_vault(&self.admin, mint, &self.token_program(mint), edge_bps, max_bet_bps, withdrawal_delay)
+    }
+
+    pub fn update_house_vault_ix(&self, admin: &Pubkey, mint: &Pubkey, edge_bps: u16, max_bet_bps: u16, withdrawal_delay: u64) -> Instruction {
+        ix::update_house_vault(admin, mint, edge_bps, max_bet_bps, withdrawal_delay)
+    }
+
+    /// A house vault for the default mint with WITHDRAWAL_DELAY, holding `bankroll` deposited by the admin.
+    pub fn house(&mut self, edge_bps: u16, max_bet_bps: u16, bankroll: u64) {
+        let (admin, mint) = (self.admin, self.mint);
+
+        self.svm.process(self.create_house_vault_ix(&mint, edge_bps, max_bet_bps, WITHDRAWAL_DELAY)).unwrap();
+        self.create_token_account(&admin, &mint, bankroll);
+        self.deposit(&admin, bankroll).unwrap();
+    }
+
+    pub fn deposit_ix(&self, provider: &Pubkey, amount: u64) -> Instruction {
+        ix::deposit_house_liquidity(provider, &self.mint, &self.token_program(&self.mint), amount)
+    }
+
+    pub fn deposit(&mut self, provider: &Pubkey, amount: u64) -> Result<(), ProgramError> {
+        let ix = self.deposit_ix(provider, amount);
+        self.svm.process(ix)
+    }
+
+    pub fn request_withdrawal_ix(&self, provider: &Pubkey, shares: u64) -> Instruction {
+        ix::request_house_withdrawal(provider, &self.mint, &self.token_program(&self.mint), shares)
+    }
+
+    pub fn cancel_withdrawal_ix(&self, provider: &Pubkey) -> Instruction {
+        ix::cancel_house_withdrawal(provider, &self.mint, &self.token_program(&self.mint))
+    }
+
+    pub fn expire_withdrawal_ix(&self, provider: &Pubkey) -> Instruction {
+        ix::expire_house_withdrawal(provider, &self.mint, &self.token_program(&self.mint))
+    }
+
+    pub fn withdraw_ix(&self, provider: &Pubkey) -> Instruction {
+        ix::withdraw_house_liquidity(provider, &self.mint, &self.token_program(&self.mint))
+    }
+
+    pub fn flip_ix(&self, player: &Pubkey, force: [u8; 32], set_number: u8, amount: u64) -> Instruction {
+        let mint = self.mint;
+        let flip_id = self.house_vault(&mint).map_or(0, |vault| vault.next_flip_id);
+
+        ix::flip_vs_house(
+            player,
+            &mint,
+            &self.token_program(&mint),
+            flip_id,
+            &self.global_data(),
+            self.referral(player),
+            Randomness::orao(force, self.orao_treasury),
+            set_number,
+            amount,
+        )
+    }
+
+    pub fn flip_switchboard_ix(&self, player: &Pubkey, random: &Pubkey, set_number: u8, amount: u64) -> Instruction {
+        let mint = self.mint;
+        let flip_id = self.house_vault(&mint).map_or(0, |vault| vault.next_flip_id);
+
+        ix::flip_vs_house(
+            player,
+            &mint,
+            &self.token_program(&mint),
+            flip_id,
+            &self.global_data(),
+            self.referral(player),
+            Randomness::switchboard(*random, force(1), self.orao_treasury),
+            set_number,
+            amount,
+        )
+    }
+
+    /// Flips against the default mint's house and returns the flip id.
+    pub fn flip(&mut self, player: &Pubkey, force: [u8; 32], set_number: u8, amount: u64) -> Result<u64, ProgramError> {
+        let flip_id = self.house_vault(&self.mint).unwrap().next_flip_id;
+        let ix = self.flip_ix(player, force, set_number, amount);
+        self.svm.process(ix).map(|_| flip_id)
+    }
+
+    pub fn settle_flip_ix(&self, flip_id: u64) -> Instruction {
+        let flip = self.house_flip(&self.mint, flip_id).unwrap();
+        ix::settle_house_flip(&flip, &self.token_program(&flip.mint))
+    }
+
+    pub fn refund_flip_ix(&self, flip_id: u64) -> Instruction {
+        let flip = self.house_flip(&self.mint, flip_id).unwrap();
+        ix::refund_house_flip(&flip, &self.token_program(&flip.mint))
+    }
+
+    pub fn register_referral_ix(&self, player: &Pubkey) -> Instruction {
+        ix::register_referral(player)
+    }
+
+    pub fn bind_referrer_ix(&self, player: &Pubkey, referrer: &Pubkey) -> Instruction {
+        ix::bind_referrer(player, referrer)
+    }
+
+    pub fn claim_referral_ix(&self, player: &Pubkey) -> Instruction {
+        ix::claim_referral_rewards(player)
+    }
+
+    pub fn create_season_ix(&self, mint: &Pubkey, start_ts: u64, end_ts: u64, metric: SeasonMetric, prize_share_bps: u16, payout_bps: Vec<u16>) -> Instruction {
+        ix::create_season(&self.admin, mint, &self.token_program(mint), start_ts, end_ts, metric, prize_share_bps, payout_bps)
+    }
+
+    pub fn finalize_season_ix(&self, mint: &Pubkey) -> Instruction {
+        ix::finalize_season(mint, &self.token_program(mint))
+    }
+
+    pub fn claim_season_prize_ix(&self, player: &Pubkey, mint: &Pubkey) -> Instruction {
+        ix::claim_season_prize(player, mint, &self.token_program(mint))
+    }
+
+    pub fn close_season_ix(&self, admin: &Pubkey, mint: &Pubkey) -> Instruction {
+        ix::close_season(admin, mint, &self.token_program(mint), &self.global_data())
+    }
+}
+
+fn native_mint_data() -> Vec<u8> {
+    let mut data = vec![0; spl_token::state::Mint::LEN];
+    spl_token::state::Mint {
+        mint_authority: None.into(),
+        supply: 0,
+        decimals: native_mint::DECIMALS,
+        is_initialized: true,
+        freeze_authority: None.into(),
+    }
+    .pack_into_slice(&mut data);
+    data
+}
@@ -0,0 +1,9 @@
+//! Offline integration tests for the spinx program.
+//!
+//! `Svm` runs the program natively together with the SPL programs it calls,
+//! `Env` sets up an initialized deployment. The tests live in `tests/` and
+//! are only compiled next to a real SBF build of the program, see `build.rs`.
+
+pub mod env;
+pub mod programs;
+pub mod svm;
@@ -0,0 +1,202 @@
+//! Programs the runtime can execute: spinx and the SPL programs through their
+//! native processors, plus minimal system and ORAO implementations.
+
+use anchor_lang::prelude::{AccountInfo, Pubkey};
+use anchor_lang::solana_program::{
+    entrypoint::ProgramResult,
+    program::{invoke, invoke_signed},
+    program_error::ProgramError,
+    rent::Rent,
+};
+use anchor_lang::system_program;
+use solana_system_interface::instruction as system_instruction;
+use anchor_lang::{AccountDeserialize, AccountSerialize, Discriminator};
+use orao_solana_vrf::state::{NetworkState, PendingRequest, RandomnessV2, RequestAccount};
+use orao_solana_vrf::{CONFIG_ACCOUNT_SEED, RANDOMNESS_ACCOUNT_SEED};
+
+pub const PROGRAM_IDS: &[Pubkey] = &[
+    spinx_contract::ID,
+    system_program::ID,
+    spl_token::ID,
+    spl_token_2022::ID,
+    spl_associated_token_account::ID,
+    orao_solana_vrf::ID,
+];
+
+pub fn process<'a>(program_id: &Pubkey, accounts: &'a [AccountInfo<'a>], data: &[u8]) -> ProgramResult {
+    if *program_id == spinx_contract::ID {
+        spinx_contract::entry(program_id, accounts, data)
+    } else if *program_id == system_program::ID {
+        process_system(accounts, data)
+    } else if *program_id == spl_token::ID {
+        spl_token::processor::Processor::process(program_id, accounts, data)
+    } else if *program_id == spl_token_2022::ID {
+        spl_token_2022::processor::Processor::process(program_id, accounts, data)
+    } else if *program_id == spl_associated_token_account::ID {
+        spl_associated_token_account::processor::process_instruction(program_id, accounts, data)
+    } else if *program_id == orao_solana_vrf::ID {
+        process_orao(accounts, data)
+    } else {
+        Err(ProgramError::IncorrectProgramId)
+    }
+}
+
+fn read_u64(data: &[u8], offset: usize) -> Result<u64, ProgramError> {
+    data.get(offset..offset + 8)
+        .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap()))
+        .ok_or(ProgramError::InvalidInstructionData)
+}
+
+fn read_pubkey(data: &[u8], offset: usize) -> Result<Pubkey, ProgramError> {
+    data.get(offset..offset + 32)
+        .map(|bytes| Pubkey::try_from(bytes).unwrap())
+        .ok_or(ProgramError::InvalidInstructionData)
+}
+
+fn account<'a, 'b>(accounts: &'b [AccountInfo<'a>], index: usize) -> Result<&'b AccountInfo<'a>, ProgramError> {
+    accounts.get(index).ok_or(ProgramError::NotEnoughAccountKeys)
+}
+
+fn debit(from: &AccountInfo, to: &AccountInfo, lamports: u64) -> ProgramResult {
+    if !from.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if *from.owner != system_program::ID || !from.data_is_empty() {
+        return Err(ProgramError::InvalidArgument);
+    }
+    if from.lamports() < lamports {
+        return Err(ProgramError::InsufficientFunds);
+    }
+
+    **from.try_borrow_mut_lamports()? -= lamports;
+    **to.try_borrow_mut_lamports()? += lamports;
+    Ok(())
+}
+
+fn allocate(account: &AccountInfo, space: u64, owner: &Pubkey) -> ProgramResult {
+    if !account.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if *account.owner != system_program::ID || !account.data_is_empty() {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    account.resize(space as usize)?;
+    account.assign(owner);
+    Ok(())
+}
+
+// CreateAccount, Assign, Transfer and Allocate, the ones spinx and the SPL
+// programs use
+fn process_system(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let tag = data
+        .get(..4)
+        .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
+        .ok_or(ProgramError::InvalidInstructionData)?;
+
+    match tag {
+        0 => {
+            let lamports = read_u64(data, 4)?;
+            let space = read_u64(data, 12)?;
+            let owner = read_pubkey(data, 20)?;
+            let from = account(accounts, 0)?;
+            let to = account(accounts, 1)?;
+
+            if to.lamports() > 0 {
+                return Err(ProgramError::AccountAlreadyInitialized);
+            }
+            allocate(to, space, &owner)?;
+            debit(from, to, lamports)
+        }
+        1 => {
+            let owner = read_pubkey(data, 4)?;
+            let target = account(accounts, 0)?;
+
+            if !target.is_signer {
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+            target.assign(&owner);
+            Ok(())
+        }
+        2 => debit(account(accounts, 0)?, account(accounts, 1)?, read_u64(data, 4)?),
+        8 => allocate(account(accounts, 0)?, read_u64(data, 4)?, &system_program::ID),
+        _ => Err(ProgramError::InvalidInstructionData),
+    }
+}
+
+/// Address of the ORAO network state.
+pub fn orao_network_state() -> Pubkey {
+    Pubkey::find_program_address(&[CONFIG_ACCOUNT_SEED], &orao_solana_vrf::ID).0
+}
+
+/// Address of the ORAO randomness request for `seed`.
+pub fn orao_randomness(seed: &[u8; 32]) -> Pubkey {
+    Pubkey::find_program_address(&[RANDOMNESS_ACCOUNT_SEED, seed], &orao_solana_vrf::ID).0
+}
+
+// request_v2 as ORAO runs it: checks the network state and treasury, charges
+// the request fee and creates a pending request. Fulfilling is up to the test.
+fn process_orao(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    if data.get(..8) != Some(orao_solana_vrf::instruction::RequestV2::DISCRIMINATOR) {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let seed: [u8; 32] = data
+        .get(8..40)
+        .and_then(|seed| seed.try_into().ok())
+        .ok_or(ProgramError::InvalidInstructionData)?;
+
+    let payer = account(accounts, 0)?;
+    let network_state = account(accounts, 1)?;
+    let treasury = account(accounts, 2)?;
+    let request = account(accounts, 3)?;
+    let system = account(accounts, 4)?;
+
+    if *network_state.key != orao_network_state() || *network_state.owner != orao_solana_vrf::ID {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let config = NetworkState::try_deserialize(&mut &network_state.data.borrow()[..])
+        .map_err(|_| ProgramError::InvalidAccountData)?
+        .config;
+    if *treasury.key != config.treasury {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let (address, bump) = Pubkey::find_program_address(&[RANDOMNESS_ACCOUNT_SEED, &seed], &orao_solana_vrf::ID);
+    if *request.key != address {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    let space = 8 + RandomnessV2::PENDING_SIZE;
+    let infos = [payer.clone(), request.clone(), system.clone()];
+    invoke_signed(
+        &system_instruction::create_account(
+            payer.key,
+            request.key,
+            Rent::default().minimum_balance(space),
+            space as u64,
+            &orao_solana_vrf::ID,
+        ),
+        &infos,
+        &[&[RANDOMNESS_ACCOUNT_SEED, &seed, &[bump]]],
+    )?;
+
+    let pending = RandomnessV2 {
+        request: RequestAccount::Pending(PendingRequest {
+            client: *payer.key,
+            seed,
+            responses: Vec::new(),
+        }),
+    };
+    pending
+        .try_serialize(&mut &mut request.data.borrow_mut()[..])
+        .map_err(|_| ProgramError::AccountDataTooSmall)?;
+
+    if config.request_fee > 0 {
+        invoke(
+            &system_instruction::transfer(payer.key, treasury.key, config.request_fee),
+            &[payer.clone(), treasury.clone(), system.clone()],
+        )?;
+    }
+
+    Ok(())
+}
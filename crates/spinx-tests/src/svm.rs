@@ -0,0 +1,476 @@
+//! A small in-process runtime, enough to run spinx and the programs it calls
+//! without a validator.
+//!
+//! Accounts are serialized in the loader's aligned layout and handed to the
+//! native program entrypoints, so account reallocation behaves as on chain.
+//! Cross-program invocations go through the `SyscallStubs` hook and are
+//! dispatched to the SPL token, Token-2022 and associated token processors, a
+//! minimal system program and a stand-in for ORAO. Program logs go to stdout,
+//! where the test harness captures them.
+//!
+//! Transactions are checked like the runtime does where it matters to spinx:
+//! signers of top level instructions have to be wallets, keys on the ed25519
+//! curve (there are no keypairs, so any wallet counts as signed), lamports
+//! can only move between accounts, and no writable account may end up below
+//! rent exemption unless it already was.
+//!
+//! It is not the SBF loader: no compute budget, stack or heap limits. The
+//! suite is gated on a real SBF build of the program for that reason.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::mem::size_of;
+use std::sync::Once;
+
+use anchor_lang::prelude::{AccountInfo, Clock, Pubkey, Rent};
+use anchor_lang::solana_program::{
+    entrypoint::{deserialize, ProgramResult, BPF_ALIGN_OF_U128, MAX_PERMITTED_DATA_INCREASE, NON_DUP_MARKER},
+    epoch_schedule::EpochSchedule,
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    program_stubs::{set_syscall_stubs, SyscallStubs},
+    sysvar,
+};
+use anchor_lang::system_program;
+use solana_sdk_ids::bpf_loader_upgradeable;
+
+use crate::programs;
+
+/// Owner, balance and data of an account.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Account {
+    pub lamports: u64,
+    pub data: Vec<u8>,
+    pub owner: Pubkey,
+    pub executable: bool,
+}
+
+impl Account {
+    pub fn new(lamports: u64, data: Vec<u8>, owner: Pubkey) -> Self {
+        Self {
+            lamports,
+            data,
+            owner,
+            executable: false,
+        }
+    }
+}
+
+/// Event emitted by a program through a self invocation, as raw bytes
+/// starting with the event discriminator.
+#[derive(Clone, Debug)]
+pub struct EmittedEvent {
+    pub program_id: Pubkey,
+    pub data: Vec<u8>,
+}
+
+#[derive(Default)]
+struct Context {
+    clock: Clock,
+    programs: Vec<Pubkey>,
+    return_data: Option<(Pubkey, Vec<u8>)>,
+    events: Vec<EmittedEvent>,
+}
+
+thread_local! {
+    static CONTEXT: RefCell<Context> = RefCell::new(Context::default());
+}
+
+fn current_program() -> Pubkey {
+    CONTEXT.with(|context| *context.borrow().programs.last().expect("no program is executing"))
+}
+
+struct Stubs;
+
+impl SyscallStubs for Stubs {
+    fn sol_log_compute_units(&self) {}
+
+    fn sol_remaining_compute_units(&self) -> u64 {
+        1_400_000
+    }
+
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+        signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        let caller = current_program();
+        let signers = signers_seeds
+            .iter()
+            .map(|seeds| Pubkey::create_program_address(seeds, &caller))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| ProgramError::InvalidSeeds)?;
+
+        let mut callee_infos = Vec::with_capacity(instruction.accounts.len());
+        for meta in &instruction.accounts {
+            let info = account_infos
+                .iter()
+                .find(|info| *info.key == meta.pubkey)
+                .ok_or(ProgramError::NotEnoughAccountKeys)?;
+
+            if meta.is_signer && !info.is_signer && !signers.contains(&meta.pubkey) {
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+            if meta.is_writable && !info.is_writable {
+                return Err(ProgramError::InvalidArgument);
+            }
+
+            let mut info = info.clone();
+            info.is_signer = meta.is_signer;
+            info.is_writable = meta.is_writable;
+            callee_infos.push(info);
+        }
+        // Programs want `&'a [AccountInfo<'a>]`, the handful of leaked infos
+        // per invocation lives as long as the test
+        let callee_infos = Vec::leak(callee_infos);
+
+        if instruction.program_id == caller && instruction.data.starts_with(anchor_lang::event::EVENT_IX_TAG_LE) {
+            return record_event(caller, callee_infos, &instruction.data);
+        }
+
+        execute(&instruction.program_id, callee_infos, &instruction.data)
+    }
+
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        let clock = CONTEXT.with(|context| context.borrow().clock.clone());
+        unsafe { *(var_addr as *mut Clock) = clock };
+        0
+    }
+
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        unsafe { *(var_addr as *mut Rent) = Rent::default() };
+        0
+    }
+
+    fn sol_get_epoch_schedule_sysvar(&self, var_addr: *mut u8) -> u64 {
+        unsafe { *(var_addr as *mut EpochSchedule) = EpochSchedule::default() };
+        0
+    }
+
+    fn sol_get_return_data(&self) -> Option<(Pubkey, Vec<u8>)> {
+        CONTEXT.with(|context| context.borrow().return_data.clone())
+    }
+
+    fn sol_set_return_data(&self, data: &[u8]) {
+        let program_id = current_program();
+        CONTEXT.with(|context| {
+            context.borrow_mut().return_data = if data.is_empty() { None } else { Some((program_id, data.to_vec())) };
+        });
+    }
+
+    fn sol_get_stack_height(&self) -> u64 {
+        CONTEXT.with(|context| context.borrow().programs.len() as u64)
+    }
+}
+
+// Anchor's emit_cpi! invokes the program itself, signed by its event authority
+fn record_event(program_id: Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let (event_authority, _) = Pubkey::find_program_address(&[b"__event_authority"], &program_id);
+    let authority = accounts.first().ok_or(ProgramError::NotEnoughAccountKeys)?;
+    if *authority.key != event_authority || !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let data = data[anchor_lang::event::EVENT_IX_TAG_LE.len()..].to_vec();
+    CONTEXT.with(|context| context.borrow_mut().events.push(EmittedEvent { program_id, data }));
+    Ok(())
+}
+
+fn execute<'a>(program_id: &Pubkey, accounts: &'a [AccountInfo<'a>], data: &[u8]) -> ProgramResult {
+    CONTEXT.with(|context| {
+        let mut context = context.borrow_mut();
+        context.programs.push(*program_id);
+        context.return_data = None;
+    });
+
+    let result = programs::process(program_id, accounts, data);
+
+    CONTEXT.with(|context| context.borrow_mut().programs.pop());
+    result
+}
+
+/// The runtime: accounts, the clock and the output of the last transaction.
+pub struct Svm {
+    accounts: HashMap<Pubkey, Account>,
+    clock: Clock,
+    events: Vec<EmittedEvent>,
+}
+
+impl Default for Svm {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Svm {
+    pub fn new() -> Self {
+        static STUBS: Once = Once::new();
+        STUBS.call_once(|| {
+            set_syscall_stubs(Box::new(Stubs));
+        });
+
+        let mut svm = Self {
+            accounts: HashMap::new(),
+            clock: Clock {
+                slot: 1,
+                unix_timestamp: 1_700_000_000,
+                ..Clock::default()
+            },
+            events: Vec::new(),
+        };
+
+        for program_id in programs::PROGRAM_IDS {
+            svm.accounts.insert(
+                *program_id,
+                Account {
+                    lamports: 1,
+                    data: Vec::new(),
+                    owner: bpf_loader_upgradeable::ID,
+                    executable: true,
+                },
+            );
+        }
+        svm.write_sysvars();
+
+        svm
+    }
+
+    fn write_sysvars(&mut self) {
+        let rent = Rent::default();
+        let mut data = Vec::new();
+        data.extend_from_slice(&rent.lamports_per_byte_year.to_le_bytes());
+        data.extend_from_slice(&rent.exemption_threshold.to_le_bytes());
+        data.push(rent.burn_percent);
+        self.accounts.insert(sysvar::rent::ID, Account::new(1, data, sysvar::ID));
+
+        let clock = &self.clock;
+        let mut data = Vec::new();
+        data.extend_from_slice(&clock.slot.to_le_bytes());
+        data.extend_from_slice(&clock.epoch_start_timestamp.to_le_bytes());
+        data.extend_from_slice(&clock.epoch.to_le_bytes());
+        data.extend_from_slice(&clock.leader_schedule_epoch.to_le_bytes());
+        data.extend_from_slice(&clock.unix_timestamp.to_le_bytes());
+        self.accounts.insert(sysvar::clock::ID, Account::new(1, data, sysvar::ID));
+    }
+
+    pub fn clock(&self) -> &Clock {
+        &self.clock
+    }
+
+    /// Moves the clock forward, each call also advances one slot.
+    pub fn warp(&mut self, seconds: i64) {
+        self.clock.unix_timestamp += seconds;
+        self.clock.slot += 1;
+        self.write_sysvars();
+    }
+
+    pub fn warp_to_slot(&mut self, slot: u64) {
+        self.clock.slot = slot;
+        self.write_sysvars();
+    }
+
+    pub fn account(&self, key: &Pubkey) -> Option<&Account> {
+        self.accounts.get(key)
+    }
+
+    pub fn set_account(&mut self, key: Pubkey, account: Account) {
+        self.accounts.insert(key, account);
+    }
+
+    pub fn lamports(&self, key: &Pubkey) -> u64 {
+        self.accounts.get(key).map_or(0, |account| account.lamports)
+    }
+
+    pub fn airdrop(&mut self, key: &Pubkey, lamports: u64) {
+        self.accounts
+            .entry(*key)
+            .or_insert_with(|| Account::new(0, Vec::new(), system_program::ID))
+            .lamports += lamports;
+    }
+
+    /// Events emitted through `emit_cpi!` by the last transaction.
+    pub fn events(&self) -> &[EmittedEvent] {
+        &self.events
+    }
+
+    pub fn process(&mut self, instruction: Instruction) -> Result<(), ProgramError> {
+        self.process_transaction(&[instruction])
+    }
+
+    /// Runs the instructions in order, all of them or none take effect.
+    pub fn process_transaction(&mut self, instructions: &[Instruction]) -> Result<(), ProgramError> {
+        let metas = dedup_metas(&instructions.iter().flat_map(|instruction| instruction.accounts.clone()).collect::<Vec<_>>());
+
+        // Only wallets can sign a transaction, program addresses sign through invoke_signed
+        if metas.iter().any(|meta| meta.is_signer && !meta.pubkey.is_on_curve()) {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        CONTEXT.with(|context| {
+            let mut context = context.borrow_mut();
+            context.clock = self.clock.clone();
+            context.events.clear();
+        });
+
+        let mut accounts = self.accounts.clone();
+        let result = instructions
+            .iter()
+            .try_for_each(|instruction| process_instruction(&mut accounts, instruction))
+            .and_then(|_| check_rent_states(&self.accounts, &accounts, &metas));
+
+        CONTEXT.with(|context| {
+            self.events = std::mem::take(&mut context.borrow_mut().events);
+        });
+
+        if result.is_ok() {
+            self.accounts = accounts;
+        }
+        result
+    }
+}
+
+fn process_instruction(accounts: &mut HashMap<Pubkey, Account>, instruction: &Instruction) -> Result<(), ProgramError> {
+    let metas = dedup_metas(&instruction.accounts);
+    let before: u128 = metas.iter().map(|meta| lamports_of(accounts, &meta.pubkey) as u128).sum();
+
+    if !accounts.get(&instruction.program_id).is_some_and(|program| program.executable) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let mut input = serialize(accounts, instruction, &metas);
+    let (program_id, infos, data) = unsafe { deserialize(input.as_mut_ptr() as *mut u8) };
+    execute(program_id, &infos, data)?;
+
+    let mut after: u128 = 0;
+    for meta in &metas {
+        let info = infos.iter().find(|info| *info.key == meta.pubkey).unwrap();
+        let lamports = info.lamports();
+        after += lamports as u128;
+
+        if !meta.is_writable {
+            continue;
+        }
+        if lamports == 0 {
+            accounts.remove(info.key);
+        } else {
+            let executable = accounts.get(info.key).is_some_and(|account| account.executable);
+            accounts.insert(
+                *info.key,
+                Account {
+                    lamports,
+                    data: info.data.borrow().to_vec(),
+                    owner: *info.owner,
+                    executable,
+                },
+            );
+        }
+    }
+
+    // Lamports can only move between accounts
+    if before != after {
+        return Err(ProgramError::Custom(u32::MAX));
+    }
+    Ok(())
+}
+
+#[derive(PartialEq)]
+enum RentState {
+    Uninitialized,
+    RentPaying { data_size: usize, lamports: u64 },
+    RentExempt,
+}
+
+fn rent_state(account: Option<&Account>) -> RentState {
+    match account {
+        None => RentState::Uninitialized,
+        Some(account) if account.lamports == 0 => RentState::Uninitialized,
+        Some(account) if Rent::default().is_exempt(account.lamports, account.data.len()) => RentState::RentExempt,
+        Some(account) => RentState::RentPaying { data_size: account.data.len(), lamports: account.lamports },
+    }
+}
+
+// The runtime rejects a transaction that leaves a writable account rent paying,
+// unless it was rent paying with the same size before and didn't gain lamports
+fn check_rent_states(before: &HashMap<Pubkey, Account>, after: &HashMap<Pubkey, Account>, metas: &[AccountMeta]) -> Result<(), ProgramError> {
+    for meta in metas.iter().filter(|meta| meta.is_writable) {
+        let allowed = match (rent_state(before.get(&meta.pubkey)), rent_state(after.get(&meta.pubkey))) {
+            (_, RentState::Uninitialized | RentState::RentExempt) => true,
+            (RentState::RentPaying { data_size, lamports }, RentState::RentPaying { data_size: size, lamports: balance }) => {
+                size == data_size && balance <= lamports
+            }
+            _ => false,
+        };
+        if !allowed {
+            return Err(ProgramError::AccountNotRentExempt);
+        }
+    }
+    Ok(())
+}
+
+fn lamports_of(accounts: &HashMap<Pubkey, Account>, key: &Pubkey) -> u64 {
+    accounts.get(key).map_or(0, |account| account.lamports)
+}
+
+// One entry per account, with the union of the signer and writable flags
+fn dedup_metas(metas: &[AccountMeta]) -> Vec<AccountMeta> {
+    let mut unique: Vec<AccountMeta> = Vec::new();
+    for meta in metas {
+        match unique.iter_mut().find(|existing| existing.pubkey == meta.pubkey) {
+            Some(existing) => {
+                existing.is_signer |= meta.is_signer;
+                existing.is_writable |= meta.is_writable;
+            }
+            None => unique.push(meta.clone()),
+        }
+    }
+    unique
+}
+
+// Same layout the loader hands to on chain programs, see
+// solana_program_entrypoint::deserialize
+fn serialize(accounts: &HashMap<Pubkey, Account>, instruction: &Instruction, metas: &[AccountMeta]) -> Vec<u128> {
+    let mut bytes: Vec<u8> = Vec::new();
+    bytes.extend_from_slice(&(instruction.accounts.len() as u64).to_le_bytes());
+
+    for (index, meta) in instruction.accounts.iter().enumerate() {
+        // Later occurrences of an account point at its first position
+        let first = instruction.accounts.iter().position(|other| other.pubkey == meta.pubkey).unwrap();
+        if first != index {
+            bytes.push(first as u8);
+            bytes.extend_from_slice(&[0; 7]);
+            continue;
+        }
+
+        let unique = metas.iter().find(|unique| unique.pubkey == meta.pubkey).unwrap();
+        let account = accounts
+            .get(&meta.pubkey)
+            .cloned()
+            .unwrap_or_else(|| Account::new(0, Vec::new(), system_program::ID));
+
+        bytes.push(NON_DUP_MARKER);
+        bytes.push(unique.is_signer as u8);
+        bytes.push(unique.is_writable as u8);
+        bytes.push(account.executable as u8);
+        bytes.extend_from_slice(&[0; 4]);
+        bytes.extend_from_slice(meta.pubkey.as_ref());
+        bytes.extend_from_slice(account.owner.as_ref());
+        bytes.extend_from_slice(&account.lamports.to_le_bytes());
+        bytes.extend_from_slice(&(account.data.len() as u64).to_le_bytes());
+        bytes.extend_from_slice(&account.data);
+        bytes.resize(bytes.len() + MAX_PERMITTED_DATA_INCREASE, 0);
+        bytes.resize(bytes.len().next_multiple_of(BPF_ALIGN_OF_U128), 0);
+        bytes.extend_from_slice(&u64::MAX.to_le_bytes());
+    }
+
+    bytes.extend_from_slice(&(instruction.data.len() as u64).to_le_bytes());
+    bytes.extend_from_slice(&instruction.data);
+    bytes.extend_from_slice(instruction.program_id.as_ref());
+
+    // u128 backing keeps every u64 field aligned
+    let mut input = vec![0u128; bytes.len().div_ceil(size_of::<u128>())];
+    unsafe {
+        std::ptr::copy_nonoverlapping(bytes.as_ptr(), input.as_mut_ptr() as *mut u8, bytes.len());
+    }
+    input
+}
@@ -0,0 +1,288 @@
+#![cfg(sbf_build)]
+
+use anchor_lang::prelude::Pubkey;
+use spinx_contract::account::{GlobalData, RandomnessProvider};
+use spinx_contract::error::SpinXError;
+use spinx_contract::event::*;
+use spinx_contract::instruction;
+use spinx_contract::utils::*;
+use spinx_tests::env::*;
+
+#[test]
+fn initialize_sets_defaults() {
+    let env = Env::new();
+    let global_data = env.global_data();
+
+    assert_eq!(global_data.super_admin, env.admin);
+    assert_eq!(global_data.treasury_wallet, TREASURY_WALLET.parse::<Pubkey>().unwrap());
+    assert_eq!(global_data.spinx_token, SPINX_TOKEN_ADDRESS.parse::<Pubkey>().unwrap());
+    assert_eq!(global_data.coinflip_fee, COINFLIP_FEE);
+    assert_eq!(global_data.min_amount, 10 * SOL);
+    assert_eq!(global_data.next_pool_id, 1);
+    assert_eq!(global_data.pause_flags, 0);
+    assert_eq!(global_data.rake_bps, 0);
+    assert_eq!(global_data.refund_timeout(), DEFAULT_REFUND_TIMEOUT);
+    assert_eq!(global_data.randomness_provider, RandomnessProvider::Orao);
+}
+
+#[test]
+fn initialize_runs_once() {
+    let mut env = Env::new();
+    let admin = env.admin;
+
+    assert!(env.svm.process(env.initialize_ix(&admin)).is_err());
+}
+
+#[test]
+fn setters_update_global_data_and_emit_events() {
+    let mut env = Env::new();
+    let admin = env.admin;
+    let treasury = wallet();
+
+    env.svm.process(env.admin_ix(&admin, instruction::SetFee { coinflip_fee: 7 })).unwrap();
+    let event = &env.events::<FeeUpdated>()[0];
+    assert_eq!((event.admin, event.old_fee, event.new_fee), (admin, COINFLIP_FEE, 7));
+
+    env.svm.process(env.admin_ix(&admin, instruction::SetMinAmount { min_amount: 3 })).unwrap();
+    let event = &env.events::<MinAmountUpdated>()[0];
+    assert_eq!((event.old_min_amount, event.new_min_amount), (10 * SOL, 3));
+
+    env.svm.process(env.admin_ix(&admin, instruction::SetTreasury { treasury_wallet: treasury })).unwrap();
+    let event = &env.events::<TreasuryUpdated>()[0];
+    assert_eq!((event.old_treasury_wallet, event.new_treasury_wallet), (env.treasury, treasury));
+
+    let spinx_token = wallet();
+    env.svm.process(env.admin_ix(&admin, instruction::SetSpinxToken { spinx_token })).unwrap();
+    let event = &env.events::<SpinxTokenUpdated>()[0];
+    assert_eq!((event.old_spinx_token, event.new_spinx_token), (SPINX_TOKEN_ADDRESS.parse().unwrap(), spinx_token));
+
+    env.svm.process(env.admin_ix(&admin, instruction::SetRake { rake_bps: MAX_RAKE_BPS })).unwrap();
+    let event = &env.events::<RakeUpdated>()[0];
+    assert_eq!((event.old_rake_bps, event.new_rake_bps), (0, MAX_RAKE_BPS));
+
+    env.svm.process(env.admin_ix(&admin, instruction::SetRefundTimeout { refund_timeout: 60 })).unwrap();
+    let event = &env.events::<RefundTimeoutUpdated>()[0];
+    assert_eq!((event.old_refund_timeout, event.new_refund_timeout), (0, 60));
+
+    env.svm.process(env.admin_ix(&admin, instruction::SetMaxPoolTtl { max_pool_ttl: 600 })).unwrap();
+    let event = &env.events::<MaxPoolTtlUpdated>()[0];
+    assert_eq!((event.old_max_pool_ttl, event.new_max_pool_ttl), (DEFAULT_MAX_POOL_TTL, 600));
+
+    let global_data = env.global_data();
+    assert_eq!(global_data.coinflip_fee, 7);
+    assert_eq!(global_data.min_amount, 3);
+    assert_eq!(global_data.treasury_wallet, treasury);
+    assert_eq!(global_data.spinx_token, spinx_token);
+    assert_eq!(global_data.rake_bps, MAX_RAKE_BPS);
+    assert_eq!(global_data.refund_timeout(), 60);
+    assert_eq!(global_data.max_pool_ttl, 600);
+}
+
+#[test]
+fn setters_validate_their_values() {
+    let mut env = Env::new();
+    let admin = env.admin;
+
+    let ix = env.admin_ix(&admin, instruction::SetMinAmount { min_amount: 0 });
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::InvalidAmount)));
+
+    let ix = env.admin_ix(&admin, instruction::SetTreasury { treasury_wallet: Pubkey::default() });
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::InvalidTreasury)));
+
+    let ix = env.admin_ix(&admin, instruction::SetSpinxToken { spinx_token: Pubkey::default() });
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::TokenNotAllowed)));
+
+    let ix = env.admin_ix(&admin, instruction::SetRake { rake_bps: MAX_RAKE_BPS + 1 });
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::InvalidRake)));
+
+    let ix = env.admin_ix(&admin, instruction::SetRefundTimeout { refund_timeout: 0 });
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::InvalidAmount)));
+
+    let ix = env.admin_ix(&admin, instruction::SetRefundTimeout { refund_timeout: MAX_REFUND_TIMEOUT + 1 });
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::InvalidAmount)));
+
+    let ix = env.admin_ix(
+        &admin,
+        instruction::SetRandomnessProvider {
+            provider: RandomnessProvider::Switchboard,
+            switchboard_queue: Pubkey::default(),
+        },
+    );
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::InvalidRandomnessProvider)));
+}
+
+#[test]
+fn setters_require_super_admin() {
+    let mut env = Env::new();
+    let stranger = env.player(0);
+
+    let ix = env.admin_ix(&stranger, instruction::SetFee { coinflip_fee: 0 });
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::InvalidAdmin)));
+
+    let ix = env.admin_ix(&stranger, instruction::SetPauseFlags { pause_flags: 0 });
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::InvalidAdmin)));
+
+    let ix = env.admin_ix(&stranger, instruction::ProposeAdmin { new_admin: stranger });
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::InvalidAdmin)));
+}
+
+#[test]
+fn set_randomness_provider_switches_new_joins() {
+    let mut env = Env::new();
+    let admin = env.admin;
+    let queue = wallet();
+
+    let ix = env.admin_ix(
+        &admin,
+        instruction::SetRandomnessProvider {
+            provider: RandomnessProvider::Switchboard,
+            switchboard_queue: queue,
+        },
+    );
+    env.svm.process(ix).unwrap();
+
+    let event = &env.events::<RandomnessProviderUpdated>()[0];
+    assert_eq!(event.old_provider, RandomnessProvider::Orao);
+    assert_eq!(event.new_provider, RandomnessProvider::Switchboard);
+    assert_eq!(event.switchboard_queue, queue);
+
+    let global_data = env.global_data();
+    assert_eq!(global_data.randomness_provider, RandomnessProvider::Switchboard);
+    assert_eq!(global_data.switchboard_queue, queue);
+}
+
+#[test]
+fn admin_handover() {
+    let mut env = Env::new();
+    let admin = env.admin;
+    let new_admin = env.player(0);
+
+    let ix = env.admin_ix(&admin, instruction::ProposeAdmin { new_admin: Pubkey::default() });
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::InvalidAdmin)));
+
+    let ix = env.accept_admin_ix(&new_admin);
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::NoPendingAdmin)));
+
+    env.svm.process(env.admin_ix(&admin, instruction::ProposeAdmin { new_admin })).unwrap();
+    assert_eq!(env.events::<AdminProposed>()[0].pending_admin, new_admin);
+    assert_eq!(env.global_data().pending_admin, new_admin);
+    // Proposing does not hand anything over yet
+    assert_eq!(env.global_data().super_admin, admin);
+
+    let stranger = env.player(0);
+    let ix = env.accept_admin_ix(&stranger);
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::InvalidAdmin)));
+
+    env.svm.process(env.accept_admin_ix(&new_admin)).unwrap();
+    let event = &env.events::<AdminChanged>()[0];
+    assert_eq!((event.old_admin, event.new_admin), (admin, new_admin));
+
+    let global_data = env.global_data();
+    assert_eq!(global_data.super_admin, new_admin);
+    assert_eq!(global_data.pending_admin, Pubkey::default());
+
+    let ix = env.admin_ix(&admin, instruction::SetFee { coinflip_fee: 0 });
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::InvalidAdmin)));
+    env.svm.process(env.admin_ix(&new_admin, instruction::SetFee { coinflip_fee: 0 })).unwrap();
+}
+
+#[test]
+fn cancel_admin_proposal() {
+    let mut env = Env::new();
+    let admin = env.admin;
+    let new_admin = env.player(0);
+
+    let ix = env.admin_ix(&admin, instruction::CancelAdminProposal {});
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::NoPendingAdmin)));
+
+    env.svm.process(env.admin_ix(&admin, instruction::ProposeAdmin { new_admin })).unwrap();
+    env.svm.process(env.admin_ix(&admin, instruction::CancelAdminProposal {})).unwrap();
+    assert_eq!(env.events::<AdminProposalCancelled>()[0].pending_admin, new_admin);
+
+    let ix = env.accept_admin_ix(&new_admin);
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::NoPendingAdmin)));
+    assert_eq!(env.global_data().super_admin, admin);
+}
+
+#[test]
+fn pauser_can_only_add_pause_bits() {
+    let mut env = Env::new();
+    let admin = env.admin;
+    let pauser = env.player(0);
+
+    let ix = env.pause_ix(&pauser, PAUSE_CREATE_COINFLIP);
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::InvalidPauser)));
+
+    env.svm.process(env.admin_ix(&admin, instruction::SetPauser { pauser })).unwrap();
+    let event = &env.events::<PauserUpdated>()[0];
+    assert_eq!((event.old_pauser, event.new_pauser), (Pubkey::default(), pauser));
+
+    env.svm.process(env.pause_ix(&pauser, PAUSE_CREATE_COINFLIP)).unwrap();
+    env.svm.process(env.pause_ix(&pauser, PAUSE_JOIN_COINFLIP)).unwrap();
+    let event = &env.events::<PauseFlagsUpdated>()[0];
+    assert_eq!(event.authority, pauser);
+    assert_eq!((event.old_flags, event.new_flags), (PAUSE_CREATE_COINFLIP, PAUSE_CREATE_COINFLIP | PAUSE_JOIN_COINFLIP));
+
+    // The pauser is not the admin, so it can't clear bits
+    let ix = env.admin_ix(&pauser, instruction::SetPauseFlags { pause_flags: 0 });
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::InvalidAdmin)));
+
+    env.svm.process(env.pause_ix(&admin, PAUSE_ALL)).unwrap();
+    assert!(env.global_data().is_paused(PAUSE_ALL));
+
+    env.svm.process(env.admin_ix(&admin, instruction::SetPauseFlags { pause_flags: 0 })).unwrap();
+    assert_eq!(env.global_data().pause_flags, 0);
+}
+
+#[test]
+fn paused_instructions_fail() {
+    let mut env = Env::new();
+    let admin = env.admin;
+    let creator = env.player(100 * SOL);
+    let joiner = env.player(100 * SOL);
+    let pool_id = env.create_pool(&creator, 0, STAKE).unwrap();
+
+    env.svm.process(env.pause_ix(&admin, PAUSE_JOIN_COINFLIP)).unwrap();
+    assert_eq!(env.join_pool(pool_id, &joiner, force(1), 1, STAKE), Err(spinx_error(SpinXError::InstructionPaused)));
+    env.create_pool(&creator, 0, STAKE).unwrap();
+
+    env.svm.process(env.pause_ix(&admin, PAUSE_CREATE_COINFLIP)).unwrap();
+    assert_eq!(env.create_pool(&creator, 0, STAKE), Err(spinx_error(SpinXError::InstructionPaused)));
+
+    // Closing is never pausable
+    env.svm.process(env.admin_ix(&admin, instruction::SetPauseFlags { pause_flags: PAUSE_ALL })).unwrap();
+    env.svm.process(env.close_ix(pool_id, &creator)).unwrap();
+}
+
+#[test]
+fn migrate_global_data_grows_older_layouts() {
+    let mut env = Env::new();
+    let admin = env.admin;
+    let new_len = 8 + std::mem::size_of::<GlobalData>();
+
+    // Drop everything after min_amount, the layout before the admin handover
+    let old_len = 8 + 32 + 32 + 8 + 32 + 8 + 8;
+    let mut account = env.svm.account(&global_data_address()).unwrap().clone();
+    account.data.truncate(old_len);
+    env.svm.set_account(global_data_address(), account);
+
+    let stranger = env.player(0);
+    let ix = env.migrate_global_data_ix(&stranger);
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::InvalidAdmin)));
+
+    env.svm.process(env.migrate_global_data_ix(&admin)).unwrap();
+    let event = &env.events::<GlobalDataMigrated>()[0];
+    assert_eq!((event.old_len, event.new_len), (old_len as u64, new_len as u64));
+
+    let global_data = env.global_data();
+    assert_eq!(env.svm.account(&global_data_address()).unwrap().data.len(), new_len);
+    assert_eq!(global_data.next_pool_id, 1);
+    assert_eq!(global_data.pending_admin, Pubkey::default());
+    assert_eq!(global_data.pause_flags, 0);
+    assert_eq!(global_data.refund_timeout(), DEFAULT_REFUND_TIMEOUT);
+    assert_eq!(global_data.max_pool_ttl, DEFAULT_MAX_POOL_TTL);
+
+    // Running it again on the current layout is a no-op
+    env.svm.process(env.migrate_global_data_ix(&admin)).unwrap();
+    assert_eq!(env.events::<GlobalDataMigrated>()[0].old_len, new_len as u64);
+}
@@ -0,0 +1,98 @@
+#![cfg(sbf_build)]
+
+use anchor_lang::event::EVENT_IX_TAG_LE;
+use spinx_client::error::{self, SpinXError, ERRORS};
+use spinx_client::instructions::{self as ix, Referral};
+use spinx_client::state;
+use spinx_contract::event::{PoolClosed, PoolCreated};
+use spinx_tests::env::*;
+
+#[test]
+fn error_codes_map_back_to_spinx_errors() {
+    for (index, error) in ERRORS.iter().enumerate() {
+        let code = u32::from(*error);
+        assert_eq!(code, 6000 + index as u32);
+        assert_eq!(error::from_code(code).map(u32::from), Some(code));
+    }
+
+    assert!(error::from_code(5999).is_none());
+    assert!(error::from_code(6000 + ERRORS.len() as u32).is_none());
+    assert_eq!(ERRORS.last().map(|error| error.name()), Some(SpinXError::WithdrawalNotExpired.name()));
+}
+
+#[test]
+fn failed_instructions_decode_their_error() {
+    let mut env = Env::new();
+    let stranger = wallet();
+
+    let error = env.svm.process(ix::set_fee(&stranger, 7)).unwrap_err();
+    assert_eq!(error::from_program_error(&error).map(|error| error.name()), Some("InvalidAdmin".to_string()));
+
+    let logs = [
+        "Program log: Instruction: SetFee",
+        "Program log: AnchorError caused by account: global_data. Error Code: InvalidAdmin. Error Number: 6000. Error Message: Invalid Admin Address.",
+    ];
+    assert_eq!(error::from_logs(&logs).map(u32::from), Some(6000));
+    assert!(error::from_logs(&["Program log: AnchorError occurred. Error Code: AccountNotInitialized. Error Number: 3012."]).is_none());
+}
+
+#[test]
+fn decoders_read_the_program_accounts() {
+    let mut env = Env::new();
+    let mint = env.mint;
+    let creator = env.player(100 * SOL);
+    let pool_id = env.create_pool(&creator, 1, STAKE).unwrap();
+
+    let data = &env.svm.account(&global_data_address()).unwrap().data;
+    assert_eq!(state::decode_global_data(data).unwrap().next_pool_id, pool_id + 1);
+    assert!(state::decode_coinflip_pool(data).is_err());
+
+    let pool = state::decode_coinflip_pool(&env.svm.account(&pool_address(pool_id)).unwrap().data).unwrap();
+    assert_eq!((pool.pool_id, pool.creator_player, pool.mint), (pool_id, creator, mint));
+
+    let registry = state::decode_pool_registry(&env.svm.account(&pool_registry_of(&pool)).unwrap().data).unwrap();
+    assert_eq!(registry.pools[0].pool_id, pool_id);
+
+    let data = &env.svm.account(&mint_config_address(&mint)).unwrap().data;
+    assert_eq!(state::decode_mint_config(data).unwrap().mint, mint);
+
+    let data = &env.svm.account(&orao_network_state_address()).unwrap().data;
+    assert_eq!(state::decode_orao_treasury(data).unwrap(), env.orao_treasury);
+}
+
+#[test]
+fn events_decode_from_their_self_invocation() {
+    let mut env = Env::new();
+    let creator = env.player(100 * SOL);
+    let pool_id = env.create_pool(&creator, 1, STAKE).unwrap();
+
+    let data = [EVENT_IX_TAG_LE, &env.svm.events()[0].data].concat();
+    assert_eq!(state::decode_event::<PoolCreated>(&data).unwrap().pool_id, pool_id);
+    assert!(state::decode_event::<PoolClosed>(&data).is_none());
+    assert!(state::decode_event::<PoolCreated>(&env.svm.events()[0].data).is_none());
+}
+
+#[test]
+fn referral_accounts_follow_registration() {
+    let mut env = Env::new();
+    let player = wallet();
+    let referrer = wallet();
+    env.svm.airdrop(&player, SOL);
+    env.svm.airdrop(&referrer, SOL);
+
+    let unbound = Referral { player_referral: player_referral_address(&player), referrer_referral: None };
+    assert_eq!(Referral::of(&player, env.player_referral(&player).as_ref()), unbound);
+
+    env.svm.process(ix::register_referral(&referrer)).unwrap();
+    env.svm.process(ix::register_referral(&player)).unwrap();
+    assert_eq!(Referral::of(&player, env.player_referral(&player).as_ref()), unbound);
+
+    env.svm.process(ix::bind_referrer(&player, &referrer)).unwrap();
+    assert_eq!(
+        Referral::of(&player, env.player_referral(&player).as_ref()),
+        Referral {
+            player_referral: player_referral_address(&player),
+            referrer_referral: Some(player_referral_address(&referrer)),
+        }
+    );
+}
@@ -0,0 +1,371 @@
+#![cfg(sbf_build)]
+
+use anchor_lang::prelude::Pubkey;
+use spinx_contract::account::{PoolStatus, RandomnessProvider};
+use spinx_contract::error::SpinXError;
+use spinx_contract::event::*;
+use spinx_contract::instruction;
+use spinx_contract::utils::*;
+use spinx_tests::env::*;
+use spinx_tests::programs::orao_randomness;
+
+struct Game {
+    env: Env,
+    creator: Pubkey,
+    joiner: Pubkey,
+    pool_id: u64,
+}
+
+/// A pool where the creator picked 0, joined by a player who picked 1.
+fn joined_game(rake_bps: u16) -> Game {
+    let mut env = Env::new();
+    let admin = env.admin;
+    env.svm.process(env.admin_ix(&admin, instruction::SetRake { rake_bps })).unwrap();
+
+    let creator = env.player(100 * SOL);
+    let joiner = env.player(100 * SOL);
+    let pool_id = env.create_pool(&creator, 0, STAKE).unwrap();
+    env.join_pool(pool_id, &joiner, force(1), 1, STAKE).unwrap();
+
+    Game { env, creator, joiner, pool_id }
+}
+
+#[test]
+fn create_escrows_the_stake() {
+    let mut env = Env::new();
+    let creator = env.player(100 * SOL);
+    let mint = env.mint;
+    let lamports = env.svm.lamports(&creator);
+    let treasury = env.svm.lamports(&env.treasury);
+    let now = env.svm.clock().unix_timestamp as u64;
+
+    let pool_id = env.create_pool(&creator, 1, STAKE).unwrap();
+    assert_eq!(pool_id, 1);
+    assert_eq!(env.next_pool_id(), 2);
+
+    let event = &env.events::<PoolCreated>()[0];
+    assert_eq!((event.pool_id, event.pool, event.creator, event.mint), (1, pool_address(1), creator, mint));
+    assert_eq!((event.amount, event.set_number, event.fee, event.expiry_ts), (STAKE, 1, COINFLIP_FEE, now + DEFAULT_MAX_POOL_TTL));
+    assert!(!event.is_native);
+
+    let pool = env.pool(pool_id).unwrap();
+    assert_eq!(pool.status, PoolStatus::Waiting);
+    assert_eq!(pool.creator_player, creator);
+    assert_eq!(pool.creator_amount, STAKE);
+    assert_eq!(pool.pool_amount, STAKE);
+    assert_eq!(pool.creator_set_number, 1);
+    assert_eq!(pool.start_ts, env.svm.clock().unix_timestamp as u64);
+
+    assert_eq!(env.token_balance(&creator, &mint), 100 * SOL - STAKE);
+    assert_eq!(env.token_balance(&pool_address(pool_id), &mint), STAKE);
+    assert_eq!(env.svm.lamports(&env.treasury), treasury + COINFLIP_FEE);
+    assert!(env.svm.lamports(&creator) < lamports - COINFLIP_FEE);
+}
+
+#[test]
+fn create_validates_its_arguments() {
+    let mut env = Env::new();
+    let creator = env.player(100 * SOL);
+
+    assert_eq!(env.create_pool(&creator, 2, STAKE), Err(spinx_error(SpinXError::InvalidNumber)));
+    assert_eq!(env.create_pool(&creator, 0, 10 * SOL - 1), Err(spinx_error(SpinXError::AmountTooSmall)));
+    assert!(env.create_pool(&creator, 0, 200 * SOL).is_err());
+    assert_eq!(env.next_pool_id(), 1);
+}
+
+#[test]
+fn join_requests_randomness() {
+    let Game { env, joiner, pool_id, .. } = joined_game(0);
+
+    let event = &env.events::<PoolJoined>()[0];
+    assert_eq!((event.pool_id, event.joiner, event.amount, event.set_number), (pool_id, joiner, STAKE, 1));
+    assert_eq!((event.fee, event.pool_amount), (COINFLIP_FEE, 2 * STAKE));
+
+    let event = &env.events::<RandomnessRequested>()[0];
+    assert_eq!((event.force, event.random), (force(1), orao_randomness(&force(1))));
+    assert_eq!(event.provider, RandomnessProvider::Orao);
+
+    let pool = env.pool(pool_id).unwrap();
+    assert_eq!(pool.status, PoolStatus::Processing);
+    assert_eq!(pool.joiner_player, joiner);
+    assert_eq!(pool.joiner_set_number, 1);
+    assert_eq!(pool.pool_amount, 2 * STAKE);
+    assert_eq!(pool.random, orao_randomness(&force(1)));
+    assert_eq!(pool.join_ts, env.svm.clock().unix_timestamp as u64);
+
+    assert_eq!(env.svm.lamports(&env.orao_treasury), SOL + ORAO_FEE);
+    assert_eq!(env.token_balance(&pool_address(pool_id), &env.mint), 2 * STAKE);
+}
+
+#[test]
+fn join_validates_the_joiner() {
+    let mut env = Env::new();
+    let creator = env.player(100 * SOL);
+    let joiner = env.player(100 * SOL);
+    let pool_id = env.create_pool(&creator, 0, STAKE).unwrap();
+
+    assert_eq!(env.join_pool(pool_id, &creator, force(1), 1, STAKE), Err(spinx_error(SpinXError::InvalidJoiner)));
+    assert_eq!(env.join_pool(pool_id, &joiner, force(1), 0, STAKE), Err(spinx_error(SpinXError::InvalidNumber)));
+    assert_eq!(env.join_pool(pool_id, &joiner, force(1), 2, STAKE), Err(spinx_error(SpinXError::InvalidNumber)));
+    assert_eq!(env.join_pool(pool_id, &joiner, force(1), 1, STAKE - 1), Err(spinx_error(SpinXError::InvalidAmount)));
+
+    // The randomness account has to be the ORAO request for force
+    let ix = with_account(
+        env.join_ix(pool_id, &joiner, force(1), 1, STAKE),
+        &orao_randomness(&force(1)),
+        &orao_randomness(&force(2)),
+    );
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::InvalidRandomnessAccount)));
+
+    env.join_pool(pool_id, &joiner, force(1), 1, STAKE).unwrap();
+
+    let late = env.player(100 * SOL);
+    assert_eq!(env.join_pool(pool_id, &late, force(2), 1, STAKE), Err(spinx_error(SpinXError::InvalidPoolStatus)));
+}
+
+#[test]
+fn creator_wins_on_even_randomness() {
+    let Game { mut env, creator, joiner, pool_id } = joined_game(0);
+    let mint = env.mint;
+    let escrow = ata(&pool_address(pool_id), &mint, &anchor_spl::token::ID);
+    let rent = env.svm.lamports(&pool_address(pool_id)) + env.svm.lamports(&escrow);
+    let lamports = env.svm.lamports(&creator);
+
+    env.fulfill(force(1), 4);
+    env.svm.process(env.result_ix(pool_id, force(1))).unwrap();
+
+    let event = &env.events::<PoolSettled>()[0];
+    assert_eq!((event.randomness, event.result, event.winner, event.loser), (4, 0, creator, joiner));
+    assert_eq!((event.pool_amount, event.payout, event.rake), (2 * STAKE, 2 * STAKE, 0));
+
+    assert_eq!(env.token_balance(&creator, &mint), 100 * SOL + STAKE);
+    assert_eq!(env.token_balance(&joiner, &mint), 100 * SOL - STAKE);
+
+    // Both accounts are closed and their rent goes back to the creator
+    assert!(env.pool(pool_id).is_none());
+    assert!(env.svm.account(&escrow).is_none());
+    assert_eq!(env.svm.lamports(&creator), lamports + rent);
+}
+
+#[test]
+fn tokens_sent_to_the_escrow_do_not_block_the_pool() {
+    let Game { mut env, creator, pool_id, .. } = joined_game(0);
+    let mint = env.mint;
+    let treasury = env.treasury;
+    let stranger = env.player(100 * SOL);
+
+    let escrow = ata(&pool_address(pool_id), &mint, &anchor_spl::token::ID);
+    env.send_tokens(&stranger, &mint, &escrow, 1_000);
+    env.fulfill(force(1), 4);
+    env.svm.process(env.result_ix(pool_id, force(1))).unwrap();
+
+    assert_eq!(env.token_balance(&creator, &mint), 100 * SOL + STAKE);
+    assert_eq!(env.token_balance(&treasury, &mint), 1_000);
+    assert!(env.svm.account(&escrow).is_none());
+
+    // A closed pool gives the extra tokens to the creator
+    let pool_id = env.create_pool(&creator, 0, STAKE).unwrap();
+    let escrow = ata(&pool_address(pool_id), &mint, &anchor_spl::token::ID);
+    env.send_tokens(&stranger, &mint, &escrow, 1_000);
+    env.svm.process(env.close_ix(pool_id, &creator)).unwrap();
+
+    assert_eq!(env.token_balance(&creator, &mint), 100 * SOL + STAKE + 1_000);
+    assert!(env.svm.account(&escrow).is_none());
+}
+
+#[test]
+fn joiner_wins_on_odd_randomness_and_rake_goes_to_the_treasury() {
+    let Game { mut env, creator, joiner, pool_id } = joined_game(500);
+    let mint = env.mint;
+
+    env.fulfill(force(1), 7);
+    env.svm.process(env.result_ix(pool_id, force(1))).unwrap();
+
+    let rake = 2 * STAKE / 20;
+    let event = &env.events::<PoolSettled>()[0];
+    assert_eq!((event.result, event.winner, event.loser), (1, joiner, creator));
+    assert_eq!((event.payout, event.rake), (2 * STAKE - rake, rake));
+
+    assert_eq!(env.token_balance(&joiner, &mint), 100 * SOL + STAKE - rake);
+    assert_eq!(env.token_balance(&creator, &mint), 100 * SOL - STAKE);
+    assert_eq!(env.token_balance(&env.treasury, &mint), rake);
+}
+
+#[test]
+fn pools_keep_the_rake_they_were_created_with() {
+    let Game { mut env, joiner, pool_id, .. } = joined_game(100);
+    let admin = env.admin;
+    let mint = env.mint;
+    env.svm.process(env.admin_ix(&admin, instruction::SetRake { rake_bps: MAX_RAKE_BPS })).unwrap();
+
+    env.fulfill(force(1), 1);
+    env.svm.process(env.result_ix(pool_id, force(1))).unwrap();
+
+    let rake = 2 * STAKE / 100;
+    assert_eq!(env.events::<PoolSettled>()[0].rake, rake);
+    assert_eq!(env.token_balance(&joiner, &mint), 100 * SOL + STAKE - rake);
+}
+
+#[test]
+fn result_waits_for_randomness() {
+    let Game { mut env, pool_id, .. } = joined_game(0);
+
+    let ix = env.result_ix(pool_id, force(1));
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::StillProcessing)));
+
+    // Zero counts as unfulfilled
+    env.fulfill(force(1), 0);
+    let ix = env.result_ix(pool_id, force(1));
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::StillProcessing)));
+
+    env.fulfill(force(1), 2);
+    let ix = env.result_ix(pool_id, force(2));
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::InvalidForce)));
+
+    env.svm.process(env.result_ix(pool_id, force(1))).unwrap();
+}
+
+#[test]
+fn close_returns_the_stake_and_rent() {
+    let mut env = Env::new();
+    let creator = env.player(100 * SOL);
+    let mint = env.mint;
+    let pool_id = env.create_pool(&creator, 0, STAKE).unwrap();
+
+    let stranger = env.player(100 * SOL);
+    let ix = with_account(env.close_ix(pool_id, &creator), &creator, &stranger);
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::InvalidCreator)));
+
+    let escrow = ata(&pool_address(pool_id), &mint, &anchor_spl::token::ID);
+    let rent = env.svm.lamports(&pool_address(pool_id)) + env.svm.lamports(&escrow);
+    let lamports = env.svm.lamports(&creator);
+
+    env.svm.process(env.close_ix(pool_id, &creator)).unwrap();
+
+    let event = &env.events::<PoolClosed>()[0];
+    assert_eq!((event.pool_id, event.status), (pool_id, PoolStatus::Closed));
+    assert_eq!((event.creator_refund, event.joiner_refund), (STAKE, 0));
+
+    assert_eq!(env.token_balance(&creator, &mint), 100 * SOL);
+    assert!(env.pool(pool_id).is_none());
+    assert!(env.svm.account(&escrow).is_none());
+    assert_eq!(env.svm.lamports(&creator), lamports + rent);
+}
+
+#[test]
+fn close_fails_once_joined() {
+    let Game { mut env, creator, pool_id, .. } = joined_game(0);
+
+    let ix = env.close_ix(pool_id, &creator);
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::InvalidClaimStatus)));
+}
+
+#[test]
+fn expire_returns_the_stake_after_the_ttl() {
+    let mut env = Env::new();
+    let creator = env.player(100 * SOL);
+    let joiner = env.player(100 * SOL);
+    let mint = env.mint;
+
+    let pool_id = env.next_pool_id();
+    env.svm.process(env.create_ix(&creator, &mint, 0, STAKE, 60)).unwrap();
+    let expiry_ts = env.svm.clock().unix_timestamp as u64 + 60;
+    assert_eq!(env.events::<PoolCreated>()[0].expiry_ts, expiry_ts);
+
+    let ix = env.expire_ix(pool_id);
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::PoolNotExpired)));
+
+    env.svm.warp(60);
+    assert_eq!(env.join_pool(pool_id, &joiner, force(1), 1, STAKE), Err(spinx_error(SpinXError::PoolExpired)));
+
+    // Anyone can crank it, the stake goes to the creator
+    env.svm.process(env.expire_ix(pool_id)).unwrap();
+    let event = &env.events::<PoolClosed>()[0];
+    assert_eq!((event.status, event.creator_refund), (PoolStatus::Expired, STAKE));
+    assert_eq!(env.token_balance(&creator, &mint), 100 * SOL);
+    assert!(env.pool(pool_id).is_none());
+}
+
+#[test]
+fn expire_fails_once_joined() {
+    let Game { mut env, pool_id, .. } = joined_game(0);
+
+    let ix = env.expire_ix(pool_id);
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::InvalidPoolStatus)));
+}
+
+#[test]
+fn max_pool_ttl_bounds_the_lifetime() {
+    let mut env = Env::new();
+    let admin = env.admin;
+    let creator = env.player(100 * SOL);
+    let mint = env.mint;
+
+    let ix = env.create_ix(&creator, &mint, 0, STAKE, DEFAULT_MAX_POOL_TTL + 1);
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::InvalidExpiry)));
+
+    env.svm.process(env.admin_ix(&admin, instruction::SetMaxPoolTtl { max_pool_ttl: 600 })).unwrap();
+
+    let ix = env.create_ix(&creator, &mint, 0, STAKE, 601);
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::InvalidExpiry)));
+
+    let now = env.svm.clock().unix_timestamp as u64;
+    let pool_id = env.create_pool(&creator, 0, STAKE).unwrap();
+    assert_eq!(env.pool(pool_id).unwrap().expiry_ts, now + 600);
+
+    let pool_id = env.next_pool_id();
+    env.svm.process(env.create_ix(&creator, &mint, 0, STAKE, 30)).unwrap();
+    assert_eq!(env.pool(pool_id).unwrap().expiry_ts, now + 30);
+
+    // Without a limit the expiry still can't overflow
+    env.svm.process(env.admin_ix(&admin, instruction::SetMaxPoolTtl { max_pool_ttl: 0 })).unwrap();
+    let ix = env.create_ix(&creator, &mint, 0, STAKE, u64::MAX);
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::InvalidExpiry)));
+}
+
+#[test]
+fn refund_returns_both_stakes_after_the_timeout() {
+    let Game { mut env, creator, joiner, pool_id } = joined_game(0);
+    let mint = env.mint;
+
+    let ix = env.refund_ix(pool_id);
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::RefundNotAvailable)));
+
+    env.svm.warp(DEFAULT_REFUND_TIMEOUT as i64);
+    env.svm.process(env.refund_ix(pool_id)).unwrap();
+
+    let event = &env.events::<PoolClosed>()[0];
+    assert_eq!(event.status, PoolStatus::Refunded);
+    assert_eq!((event.creator_refund, event.joiner_refund), (STAKE, STAKE));
+
+    assert_eq!(env.token_balance(&creator, &mint), 100 * SOL);
+    assert_eq!(env.token_balance(&joiner, &mint), 100 * SOL);
+    assert!(env.pool(pool_id).is_none());
+}
+
+#[test]
+fn refund_timeout_is_configurable() {
+    let Game { mut env, pool_id, .. } = joined_game(0);
+    let admin = env.admin;
+    env.svm.process(env.admin_ix(&admin, instruction::SetRefundTimeout { refund_timeout: 10 })).unwrap();
+
+    env.svm.warp(9);
+    let ix = env.refund_ix(pool_id);
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::RefundNotAvailable)));
+
+    env.svm.warp(1);
+    env.svm.process(env.refund_ix(pool_id)).unwrap();
+}
+
+#[test]
+fn fulfilled_pools_cannot_be_refunded() {
+    let Game { mut env, pool_id, .. } = joined_game(0);
+
+    env.fulfill(force(1), 3);
+    env.svm.warp(DEFAULT_REFUND_TIMEOUT as i64);
+
+    let ix = env.refund_ix(pool_id);
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::RandomnessFulfilled)));
+
+    env.svm.process(env.result_ix(pool_id, force(1))).unwrap();
+}
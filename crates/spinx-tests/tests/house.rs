@@ -0,0 +1,230 @@
+#![cfg(sbf_build)]
+
+use anchor_lang::prelude::Pubkey;
+use spinx_contract::error::SpinXError;
+use spinx_contract::event::*;
+use spinx_contract::utils::*;
+use spinx_tests::env::*;
+
+const SEED_SLOT: u64 = 100;
+
+#[test]
+fn admin_creates_a_vault_per_mint() {
+    let mut env = Env::new();
+    let mint = env.mint;
+
+    let ix = env.create_house_vault_ix(&mint, MAX_HOUSE_EDGE_BPS + 1, HOUSE_MAX_BET_BPS, WITHDRAWAL_DELAY);
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::InvalidHouseConfig)));
+    let ix = env.create_house_vault_ix(&mint, HOUSE_EDGE_BPS, 0, WITHDRAWAL_DELAY);
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::InvalidHouseConfig)));
+    let ix = env.create_house_vault_ix(&mint, HOUSE_EDGE_BPS, MAX_HOUSE_BET_BPS + 1, WITHDRAWAL_DELAY);
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::InvalidHouseConfig)));
+    let ix = env.create_house_vault_ix(&mint, HOUSE_EDGE_BPS, HOUSE_MAX_BET_BPS, MAX_HOUSE_WITHDRAWAL_DELAY + 1);
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::InvalidHouseConfig)));
+
+    let stranger = wallet();
+    env.svm.airdrop(&stranger, SOL);
+    let ix = with_account(env.create_house_vault_ix(&mint, HOUSE_EDGE_BPS, HOUSE_MAX_BET_BPS, WITHDRAWAL_DELAY), &env.admin, &stranger);
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::InvalidAdmin)));
+
+    env.svm.process(env.create_house_vault_ix(&mint, HOUSE_EDGE_BPS, HOUSE_MAX_BET_BPS, WITHDRAWAL_DELAY)).unwrap();
+
+    let house_vault = house_vault_address(&mint);
+    let event = &env.events::<HouseVaultCreated>()[0];
+    assert_eq!((event.mint, event.house_vault, event.share_mint), (mint, house_vault, share_mint_address(&house_vault)));
+    assert_eq!((event.edge_bps, event.max_bet_bps, event.withdrawal_delay), (HOUSE_EDGE_BPS, HOUSE_MAX_BET_BPS, WITHDRAWAL_DELAY));
+
+    let vault = env.house_vault(&mint).unwrap();
+    assert_eq!((vault.edge_bps, vault.max_bet_bps, vault.locked_amount), (HOUSE_EDGE_BPS, HOUSE_MAX_BET_BPS, 0));
+    assert_eq!((vault.share_mint, vault.withdrawal_delay), (share_mint_address(&house_vault), WITHDRAWAL_DELAY));
+}
+
+#[test]
+fn admin_updates_the_edge_and_max_bet() {
+    let (mut env, _) = house_env();
+    let (admin, mint) = (env.admin, env.mint);
+
+    let ix = env.update_house_vault_ix(&admin, &mint, MAX_HOUSE_EDGE_BPS + 1, HOUSE_MAX_BET_BPS, 0);
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::InvalidHouseConfig)));
+    let ix = env.update_house_vault_ix(&wallet(), &mint, 100, 100, 0);
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::InvalidAdmin)));
+
+    env.svm.process(env.update_house_vault_ix(&admin, &mint, 100, 100, 0)).unwrap();
+
+    let event = &env.events::<HouseVaultUpdated>()[0];
+    assert_eq!((event.old_edge_bps, event.new_edge_bps), (HOUSE_EDGE_BPS, 100));
+    assert_eq!((event.old_max_bet_bps, event.new_max_bet_bps), (HOUSE_MAX_BET_BPS, 100));
+    assert_eq!((event.old_withdrawal_delay, event.new_withdrawal_delay), (WITHDRAWAL_DELAY, 0));
+    assert_eq!(env.house_vault(&mint).unwrap().max_bet_bps, 100);
+}
+
+#[test]
+fn flip_locks_the_payout() {
+    let (mut env, player) = house_env();
+    let mint = env.mint;
+
+    let flip_id = env.flip(&player, force(1), 1, STAKE).unwrap();
+
+    let payout = 2 * STAKE * (10_000 - HOUSE_EDGE_BPS as u64) / 10_000;
+    let event = &env.events::<HouseFlipCreated>()[0];
+    assert_eq!((event.flip_id, event.player, event.amount), (flip_id, player, STAKE));
+    assert_eq!((event.set_number, event.payout, event.fee), (1, payout, COINFLIP_FEE));
+
+    let flip = env.house_flip(&mint, flip_id).unwrap();
+    assert_eq!((flip.amount, flip.payout), (STAKE, payout));
+    assert_eq!(env.house_vault(&mint).unwrap().locked_amount, payout);
+    assert_eq!(vault_balance(&env), BANKROLL + STAKE);
+    assert_eq!(env.token_balance(&player, &mint), 100 * SOL - STAKE);
+}
+
+#[test]
+fn max_bet_follows_the_free_balance() {
+    let (mut env, player) = house_env();
+    let max_bet = BANKROLL * HOUSE_MAX_BET_BPS as u64 / 10_000;
+
+    assert_eq!(env.flip(&player, force(1), 1, max_bet + 1), Err(spinx_error(SpinXError::AmountTooLarge)));
+    env.flip(&player, force(1), 1, max_bet).unwrap();
+
+    // The pending payout is locked, so a second max bet no longer fits
+    assert_eq!(env.flip(&player, force(2), 1, max_bet), Err(spinx_error(SpinXError::AmountTooLarge)));
+}
+
+#[test]
+fn winning_player_is_paid_less_the_edge() {
+    let (mut env, player) = house_env();
+    let mint = env.mint;
+    let flip_id = env.flip(&player, force(1), 1, STAKE).unwrap();
+
+    let ix = env.settle_flip_ix(flip_id);
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::StillProcessing)));
+
+    env.fulfill(force(1), 7);
+    env.svm.process(env.settle_flip_ix(flip_id)).unwrap();
+
+    let payout = 2 * STAKE * (10_000 - HOUSE_EDGE_BPS as u64) / 10_000;
+    let event = &env.events::<HouseFlipSettled>()[0];
+    assert_eq!((event.randomness, event.result, event.player_won), (7, 1, true));
+    assert_eq!(event.payout, payout);
+
+    assert_eq!(env.token_balance(&player, &mint), 100 * SOL - STAKE + payout);
+    assert_eq!(vault_balance(&env), BANKROLL + STAKE - payout);
+    assert_eq!(env.house_vault(&mint).unwrap().locked_amount, 0);
+    assert!(env.house_flip(&mint, flip_id).is_none());
+}
+
+#[test]
+fn losing_stake_stays_in_the_vault() {
+    let (mut env, player) = house_env();
+    let mint = env.mint;
+    let flip_id = env.flip(&player, force(1), 1, STAKE).unwrap();
+
+    let lamports = env.svm.lamports(&player);
+    let rent = env.svm.lamports(&house_flip_address(&house_vault_address(&mint), flip_id));
+    env.fulfill(force(1), 8);
+    env.svm.process(env.settle_flip_ix(flip_id)).unwrap();
+
+    let event = &env.events::<HouseFlipSettled>()[0];
+    assert_eq!((event.result, event.player_won, event.payout), (0, false, 0));
+
+    assert_eq!(env.token_balance(&player, &mint), 100 * SOL - STAKE);
+    assert_eq!(vault_balance(&env), BANKROLL + STAKE);
+    assert_eq!(env.house_vault(&mint).unwrap().locked_amount, 0);
+    assert_eq!(env.svm.lamports(&player), lamports + rent);
+}
+
+#[test]
+fn refund_returns_the_stake_after_the_timeout() {
+    let (mut env, player) = house_env();
+    let mint = env.mint;
+    let flip_id = env.flip(&player, force(1), 1, STAKE).unwrap();
+
+    let ix = env.refund_flip_ix(flip_id);
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::RefundNotAvailable)));
+
+    env.svm.warp(DEFAULT_REFUND_TIMEOUT as i64);
+    env.svm.process(env.refund_flip_ix(flip_id)).unwrap();
+
+    assert_eq!(env.events::<HouseFlipRefunded>()[0].amount, STAKE);
+    assert_eq!(env.token_balance(&player, &mint), 100 * SOL);
+    assert_eq!(vault_balance(&env), BANKROLL);
+    assert_eq!(env.house_vault(&mint).unwrap().locked_amount, 0);
+    assert!(env.house_flip(&mint, flip_id).is_none());
+}
+
+#[test]
+fn fulfilled_flips_cannot_be_refunded() {
+    let (mut env, player) = house_env();
+    let flip_id = env.flip(&player, force(1), 1, STAKE).unwrap();
+
+    env.fulfill(force(1), 8);
+    env.svm.warp(DEFAULT_REFUND_TIMEOUT as i64);
+
+    let ix = env.refund_flip_ix(flip_id);
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::RandomnessFulfilled)));
+}
+
+/// A Switchboard flip on heads committed at `SEED_SLOT`.
+fn switchboard_flip(env: &mut Env, player: &Pubkey) -> (u64, Pubkey, Pubkey) {
+    let queue = env.use_switchboard();
+    let random = wallet();
+    env.svm.warp_to_slot(SEED_SLOT + 1);
+    env.switchboard_randomness(&random, &queue, SEED_SLOT, 0, 0);
+
+    let flip_id = env.house_vault(&env.mint).unwrap().next_flip_id;
+    env.svm.process(env.flip_switchboard_ix(player, &random, 1, STAKE)).unwrap();
+    (flip_id, random, queue)
+}
+
+#[test]
+fn recommitted_flips_are_lost_by_the_player() {
+    let (mut env, player) = house_env();
+    let mint = env.mint;
+    let (flip_id, random, queue) = switchboard_flip(&mut env, &player);
+
+    // Committed again and revealed for a later slot, the player gives the flip up
+    env.switchboard_randomness(&random, &queue, SEED_SLOT + 5, SEED_SLOT + 6, 1);
+
+    env.svm.warp(DEFAULT_REFUND_TIMEOUT as i64);
+    let ix = env.refund_flip_ix(flip_id);
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::RandomnessForfeited)));
+
+    env.svm.process(env.settle_flip_ix(flip_id)).unwrap();
+
+    let event = &env.events::<HouseFlipSettled>()[0];
+    assert_eq!((event.randomness, event.result, event.player_won), (0, 0, false));
+    assert_eq!(env.token_balance(&player, &mint), 100 * SOL - STAKE);
+    assert_eq!(vault_balance(&env), BANKROLL + STAKE);
+    assert_eq!(env.house_vault(&mint).unwrap().locked_amount, 0);
+}
+
+#[test]
+fn unrevealed_flips_are_refunded_after_the_refund_timeout() {
+    let (mut env, player) = house_env();
+    let mint = env.mint;
+    let (flip_id, _, _) = switchboard_flip(&mut env, &player);
+
+    let ix = env.refund_flip_ix(flip_id);
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::RefundNotAvailable)));
+
+    // The oracle never revealed, the player keeps their stake
+    env.svm.warp(DEFAULT_REFUND_TIMEOUT as i64);
+    let ix = env.settle_flip_ix(flip_id);
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::StillProcessing)));
+
+    env.svm.process(env.refund_flip_ix(flip_id)).unwrap();
+    assert_eq!(env.token_balance(&player, &mint), 100 * SOL);
+    assert_eq!(vault_balance(&env), BANKROLL);
+    assert_eq!(env.house_vault(&mint).unwrap().locked_amount, 0);
+}
+
+#[test]
+fn flips_respect_the_mint_and_the_pause_flag() {
+    let (mut env, player) = house_env();
+    let admin = env.admin;
+
+    assert_eq!(env.flip(&player, force(1), 2, STAKE), Err(spinx_error(SpinXError::InvalidNumber)));
+    assert_eq!(env.flip(&player, force(1), 1, 10 * SOL - 1), Err(spinx_error(SpinXError::AmountTooSmall)));
+
+    env.svm.process(env.pause_ix(&admin, PAUSE_HOUSE)).unwrap();
+    assert_eq!(env.flip(&player, force(1), 1, STAKE), Err(spinx_error(SpinXError::InstructionPaused)));
+}
@@ -0,0 +1,192 @@
+#![cfg(sbf_build)]
+
+use anchor_lang::prelude::Pubkey;
+use spinx_contract::error::SpinXError;
+use spinx_contract::event::*;
+use spinx_contract::utils::*;
+use spinx_tests::env::*;
+
+/// Requests a withdrawal of `shares` and waits out the delay.
+fn queue_withdrawal(env: &mut Env, provider: &Pubkey, shares: u64) {
+    env.svm.process(env.request_withdrawal_ix(provider, shares)).unwrap();
+    env.svm.warp(WITHDRAWAL_DELAY as i64);
+}
+
+#[test]
+fn deposit_mints_shares_at_vault_equity() {
+    let (mut env, provider) = house_env();
+    let admin = env.admin;
+
+    assert_eq!(env.shares(&admin), BANKROLL);
+
+    assert_eq!(env.deposit(&provider, 0), Err(spinx_error(SpinXError::InvalidAmount)));
+    env.deposit(&provider, 10 * SOL).unwrap();
+
+    let event = &env.events::<HouseLiquidityDeposited>()[0];
+    assert_eq!((event.provider, event.amount, event.shares), (provider, 10 * SOL, 10 * SOL));
+    assert_eq!((event.balance, event.share_supply), (BANKROLL + 10 * SOL, BANKROLL + 10 * SOL));
+
+    assert_eq!(env.shares(&provider), 10 * SOL);
+    assert_eq!(env.token_balance(&provider, &env.mint), 90 * SOL);
+    assert_eq!(vault_balance(&env), BANKROLL + 10 * SOL);
+}
+
+#[test]
+fn house_profit_raises_the_share_price() {
+    let (mut env, provider) = house_env();
+    let player = env.player(100 * SOL);
+
+    let flip_id = env.flip(&player, force(1), 1, STAKE).unwrap();
+    env.fulfill(force(1), 8);
+    env.svm.process(env.settle_flip_ix(flip_id)).unwrap();
+
+    env.deposit(&provider, 10 * SOL).unwrap();
+
+    // 10 tokens at (1000 shares + virtual) / (1020 tokens + virtual)
+    assert_eq!(env.shares(&provider), 9_803_921_760);
+}
+
+#[test]
+fn withdrawal_waits_for_the_delay() {
+    let (mut env, provider) = house_env();
+    env.deposit(&provider, 10 * SOL).unwrap();
+
+    let ix = env.request_withdrawal_ix(&provider, 0);
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::InvalidAmount)));
+    let ix = env.request_withdrawal_ix(&provider, 10 * SOL + 1);
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::InsufficientFunds)));
+
+    env.svm.process(env.request_withdrawal_ix(&provider, 10 * SOL)).unwrap();
+    let event = &env.events::<HouseWithdrawalRequested>()[0];
+    assert_eq!((event.provider, event.shares), (provider, 10 * SOL));
+    assert_eq!(event.expiry_ts, event.ready_ts + HOUSE_WITHDRAWAL_WINDOW);
+    let withdrawal = env.house_withdrawal(&provider).unwrap();
+    assert_eq!((withdrawal.ready_ts, withdrawal.expiry_ts), (event.ready_ts, event.expiry_ts));
+
+    // The vault holds the shares until the withdrawal completes
+    assert_eq!(env.shares(&provider), 0);
+    assert_eq!(env.shares(&house_vault_address(&env.mint)), 10 * SOL);
+
+    let ix = env.withdraw_ix(&provider);
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::WithdrawalNotReady)));
+
+    env.svm.warp(WITHDRAWAL_DELAY as i64);
+    env.svm.process(env.withdraw_ix(&provider)).unwrap();
+
+    let event = &env.events::<HouseLiquidityWithdrawn>()[0];
+    assert_eq!((event.amount, event.shares), (10 * SOL, 10 * SOL));
+    assert_eq!((event.balance, event.share_supply), (BANKROLL, BANKROLL));
+
+    assert_eq!(env.shares(&provider), 0);
+    assert_eq!(env.shares(&house_vault_address(&env.mint)), 0);
+    assert_eq!(env.token_balance(&provider, &env.mint), 100 * SOL);
+    assert!(env.house_withdrawal(&provider).is_none());
+}
+
+#[test]
+fn unused_requests_expire() {
+    let (mut env, provider) = house_env();
+    env.deposit(&provider, 10 * SOL).unwrap();
+    queue_withdrawal(&mut env, &provider, 10 * SOL);
+
+    let ix = env.expire_withdrawal_ix(&provider);
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::WithdrawalNotExpired)));
+
+    env.svm.warp(HOUSE_WITHDRAWAL_WINDOW as i64);
+    let ix = env.withdraw_ix(&provider);
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::WithdrawalExpired)));
+
+    // Anyone can drop it, the shares and rent go back to the provider
+    let lamports = env.svm.lamports(&provider);
+    let rent = env.svm.lamports(&house_withdrawal_address(&house_vault_address(&env.mint), &provider));
+    env.svm.process(env.expire_withdrawal_ix(&provider)).unwrap();
+
+    let event = &env.events::<HouseWithdrawalCancelled>()[0];
+    assert_eq!((event.provider, event.shares), (provider, 10 * SOL));
+    assert!(env.house_withdrawal(&provider).is_none());
+    assert_eq!(env.shares(&provider), 10 * SOL);
+    assert_eq!(env.svm.lamports(&provider), lamports + rent);
+}
+
+#[test]
+fn cancelled_request_keeps_the_shares() {
+    let (mut env, provider) = house_env();
+    env.deposit(&provider, 10 * SOL).unwrap();
+    env.svm.process(env.request_withdrawal_ix(&provider, 10 * SOL)).unwrap();
+
+    let stranger = wallet();
+    let ix = env.cancel_withdrawal_ix(&stranger);
+    assert!(env.svm.process(ix).is_err());
+
+    env.svm.process(env.cancel_withdrawal_ix(&provider)).unwrap();
+
+    let event = &env.events::<HouseWithdrawalCancelled>()[0];
+    assert_eq!((event.provider, event.shares), (provider, 10 * SOL));
+    assert!(env.house_withdrawal(&provider).is_none());
+    assert_eq!(env.shares(&provider), 10 * SOL);
+    assert_eq!(env.shares(&house_vault_address(&env.mint)), 0);
+}
+
+#[test]
+fn pending_flips_are_priced_as_losses_for_withdrawals() {
+    let (mut env, _) = house_env();
+    let admin = env.admin;
+    let mint = env.mint;
+    let player = env.player(100 * SOL);
+
+    env.flip(&player, force(1), 1, STAKE).unwrap();
+    let locked = env.house_vault(&mint).unwrap().locked_amount;
+
+    // Every share is worth slightly more than the free balance while the payout is locked
+    queue_withdrawal(&mut env, &admin, BANKROLL);
+    let ix = env.withdraw_ix(&admin);
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::InsufficientFunds)));
+
+    env.svm.process(env.cancel_withdrawal_ix(&admin)).unwrap();
+    queue_withdrawal(&mut env, &admin, BANKROLL / 2);
+    env.svm.process(env.withdraw_ix(&admin)).unwrap();
+
+    // Half the shares at (980.8 free tokens + virtual) / (1000 shares + virtual)
+    let amount = 490_400_009_599;
+    let event = &env.events::<HouseLiquidityWithdrawn>()[0];
+    assert_eq!((event.amount, event.locked_amount), (amount, locked));
+    assert_eq!(vault_balance(&env), BANKROLL + STAKE - amount);
+}
+
+#[test]
+fn providers_share_the_edge() {
+    let (mut env, provider) = house_env();
+    let admin = env.admin;
+    env.deposit(&provider, BANKROLL / 10).unwrap();
+    let player = env.player(100 * SOL);
+
+    let flip_id = env.flip(&player, force(1), 1, STAKE).unwrap();
+    env.fulfill(force(1), 8);
+    env.svm.process(env.settle_flip_ix(flip_id)).unwrap();
+
+    // 100 of 1100 shares in a vault holding 1120 tokens, everything short of the virtual liquidity's cut
+    queue_withdrawal(&mut env, &provider, BANKROLL / 10);
+    env.svm.process(env.withdraw_ix(&provider)).unwrap();
+    let provider_amount = 101_818_180_165;
+    assert_eq!(env.token_balance(&provider, &env.mint), 100 * SOL - BANKROLL / 10 + provider_amount);
+
+    queue_withdrawal(&mut env, &admin, BANKROLL);
+    env.svm.process(env.withdraw_ix(&admin)).unwrap();
+    assert_eq!(env.events::<HouseLiquidityWithdrawn>()[0].amount, 1_018_181_801_653);
+    assert_eq!(env.shares(&admin), 0);
+    assert_eq!(vault_balance(&env), 18_182);
+}
+
+#[test]
+fn deposits_respect_the_pause_flag() {
+    let (mut env, provider) = house_env();
+    let admin = env.admin;
+
+    env.deposit(&provider, 10 * SOL).unwrap();
+    env.svm.process(env.pause_ix(&admin, PAUSE_HOUSE)).unwrap();
+    assert_eq!(env.deposit(&provider, 5 * SOL), Err(spinx_error(SpinXError::InstructionPaused)));
+
+    // Withdrawals are an exit path and stay open
+    queue_withdrawal(&mut env, &provider, 10 * SOL);
+    env.svm.process(env.withdraw_ix(&provider)).unwrap();
+}
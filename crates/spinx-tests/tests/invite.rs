@@ -0,0 +1,164 @@
+#![cfg(sbf_build)]
+
+use anchor_lang::prelude::Pubkey;
+use spinx_contract::error::SpinXError;
+use spinx_contract::event::*;
+use spinx_contract::instruction;
+use spinx_contract::utils::*;
+use spinx_tests::env::*;
+
+fn invite(friend: &Pubkey) -> PoolOptions {
+    PoolOptions {
+        allowed_joiner: Some(*friend),
+        ..Default::default()
+    }
+}
+
+/// An invite-only pool of the default mint reserved for `friend`.
+fn invite_pool(env: &mut Env, creator: &Pubkey, friend: &Pubkey) -> u64 {
+    let pool_id = env.next_pool_id();
+    let ix = env.create_ix_with(creator, &env.mint.clone(), 0, STAKE, invite(friend));
+    env.svm.process(ix).unwrap();
+    pool_id
+}
+
+#[test]
+fn only_the_invited_player_can_join() {
+    let mut env = Env::new();
+    let creator = env.player(100 * SOL);
+    let friend = env.player(100 * SOL);
+    let stranger = env.player(100 * SOL);
+    let now = env.svm.clock().unix_timestamp as u64;
+
+    let pool_id = invite_pool(&mut env, &creator, &friend);
+    assert_eq!(env.events::<PoolCreated>()[0].allowed_joiner, friend);
+
+    let pool = env.pool(pool_id).unwrap();
+    assert_eq!(pool.allowed_joiner, friend);
+    assert_eq!(pool.invite_expiry_ts, now + DEFAULT_INVITE_TIMEOUT);
+
+    assert_eq!(env.join_pool(pool_id, &stranger, force(1), 1, STAKE), Err(spinx_error(SpinXError::NotInvited)));
+    env.join_pool(pool_id, &friend, force(1), 1, STAKE).unwrap();
+}
+
+#[test]
+fn public_pools_record_no_invite() {
+    let mut env = Env::new();
+    let creator = env.player(100 * SOL);
+    let pool_id = env.create_pool(&creator, 0, STAKE).unwrap();
+
+    assert_eq!(env.events::<PoolCreated>()[0].allowed_joiner, Pubkey::default());
+    assert_eq!(env.pool(pool_id).unwrap().invite_expiry_ts, 0);
+
+    let ix = env.open_ix(pool_id, &creator);
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::InvalidPoolStatus)));
+}
+
+#[test]
+fn invite_must_name_another_player() {
+    let mut env = Env::new();
+    let creator = env.player(100 * SOL);
+    let mint = env.mint;
+
+    let ix = env.create_ix_with(&creator, &mint, 0, STAKE, invite(&creator));
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::InvalidJoiner)));
+
+    let ix = env.create_ix_with(&creator, &mint, 0, STAKE, invite(&Pubkey::default()));
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::InvalidJoiner)));
+}
+
+#[test]
+fn creator_opens_the_pool_after_the_invite_timeout() {
+    let mut env = Env::new();
+    let creator = env.player(100 * SOL);
+    let friend = env.player(100 * SOL);
+    let stranger = env.player(100 * SOL);
+    let pool_id = invite_pool(&mut env, &creator, &friend);
+
+    let ix = env.open_ix(pool_id, &creator);
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::InviteNotExpired)));
+
+    env.svm.warp(DEFAULT_INVITE_TIMEOUT as i64);
+    let ix = env.open_ix(pool_id, &stranger);
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::InvalidCreator)));
+
+    env.svm.process(env.open_ix(pool_id, &creator)).unwrap();
+    let event = &env.events::<PoolOpened>()[0];
+    assert_eq!((event.pool_id, event.creator, event.allowed_joiner), (pool_id, creator, friend));
+
+    let pool = env.pool(pool_id).unwrap();
+    assert_eq!(pool.allowed_joiner, Pubkey::default());
+    assert_eq!(pool.invite_expiry_ts, 0);
+
+    env.join_pool(pool_id, &stranger, force(1), 1, STAKE).unwrap();
+}
+
+#[test]
+fn creator_can_close_an_invite_pool() {
+    let mut env = Env::new();
+    let creator = env.player(100 * SOL);
+    let friend = env.player(100 * SOL);
+    let pool_id = invite_pool(&mut env, &creator, &friend);
+
+    env.svm.process(env.close_ix(pool_id, &creator)).unwrap();
+    assert_eq!(env.token_balance(&creator, &env.mint), 100 * SOL);
+}
+
+#[test]
+fn joined_invite_pools_cannot_be_opened() {
+    let mut env = Env::new();
+    let creator = env.player(100 * SOL);
+    let friend = env.player(100 * SOL);
+    let pool_id = invite_pool(&mut env, &creator, &friend);
+    env.join_pool(pool_id, &friend, force(1), 1, STAKE).unwrap();
+
+    env.svm.warp(DEFAULT_INVITE_TIMEOUT as i64);
+    let ix = env.open_ix(pool_id, &creator);
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::InvalidPoolStatus)));
+}
+
+#[test]
+fn invite_timeout_is_configurable() {
+    let mut env = Env::new();
+    let admin = env.admin;
+    let creator = env.player(100 * SOL);
+    let friend = env.player(100 * SOL);
+
+    let ix = env.admin_ix(&admin, instruction::SetInviteTimeout { invite_timeout: 0 });
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::InvalidAmount)));
+    let ix = env.admin_ix(&admin, instruction::SetInviteTimeout { invite_timeout: MAX_INVITE_TIMEOUT + 1 });
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::InvalidAmount)));
+
+    env.svm.process(env.admin_ix(&admin, instruction::SetInviteTimeout { invite_timeout: 60 })).unwrap();
+    let event = &env.events::<InviteTimeoutUpdated>()[0];
+    assert_eq!((event.old_invite_timeout, event.new_invite_timeout), (0, 60));
+    assert_eq!(env.global_data().invite_timeout(), 60);
+
+    let pool_id = invite_pool(&mut env, &creator, &friend);
+    env.svm.warp(59);
+    let ix = env.open_ix(pool_id, &creator);
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::InviteNotExpired)));
+
+    env.svm.warp(1);
+    env.svm.process(env.open_ix(pool_id, &creator)).unwrap();
+}
+
+#[test]
+fn sol_pools_take_invites_too() {
+    let mut env = Env::new();
+    env.enable_sol();
+    let creator = wallet();
+    let friend = wallet();
+    let stranger = wallet();
+    for player in [creator, friend, stranger] {
+        env.svm.airdrop(&player, 100 * SOL);
+    }
+
+    let pool_id = env.next_pool_id();
+    env.svm.process(env.create_sol_ix_with(&creator, 0, STAKE, invite(&friend))).unwrap();
+    assert_eq!(env.join_sol_pool(pool_id, &stranger, force(1), 1, STAKE), Err(spinx_error(SpinXError::NotInvited)));
+
+    env.svm.warp(DEFAULT_INVITE_TIMEOUT as i64);
+    env.svm.process(env.open_ix(pool_id, &creator)).unwrap();
+    env.join_sol_pool(pool_id, &stranger, force(1), 1, STAKE).unwrap();
+}
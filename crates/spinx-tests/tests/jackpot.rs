@@ -0,0 +1,346 @@
+#![cfg(sbf_build)]
+
+use anchor_lang::prelude::Pubkey;
+use spinx_contract::account::{JackpotEntry, JackpotRound, PoolStatus, RandomnessProvider};
+use spinx_contract::error::SpinXError;
+use spinx_contract::event::*;
+use spinx_contract::instruction;
+use spinx_contract::utils::*;
+use spinx_tests::env::*;
+use spinx_tests::programs::orao_randomness;
+
+const DURATION: u64 = 10 * 60;
+const SEED_SLOT: u64 = 100;
+
+/// A round of the default mint where the first player deposited STAKE and the
+/// second three times as much, so the first holds a quarter of the pot.
+fn drawn_round() -> (Env, Pubkey, Pubkey, u64) {
+    let mut env = Env::new();
+    let creator = env.player(0);
+    let small = env.player(100 * SOL);
+    let large = env.player(100 * SOL);
+
+    let round_id = env.create_jackpot(&creator, DURATION).unwrap();
+    env.enter_jackpot(round_id, &small, STAKE).unwrap();
+    env.enter_jackpot(round_id, &large, 3 * STAKE).unwrap();
+
+    env.svm.warp(DURATION as i64);
+    env.svm.process(env.draw_jackpot_ix(round_id, &creator, force(1))).unwrap();
+
+    (env, small, large, round_id)
+}
+
+#[test]
+fn create_opens_a_round() {
+    let mut env = Env::new();
+    let creator = env.player(0);
+    let now = env.svm.clock().unix_timestamp as u64;
+
+    let round_id = env.create_jackpot(&creator, DURATION).unwrap();
+    assert_eq!(env.next_jackpot_id(), round_id + 1);
+
+    let event = &env.events::<JackpotCreated>()[0];
+    assert_eq!((event.round_id, event.creator, event.mint), (round_id, creator, env.mint));
+    assert_eq!(event.end_ts, now + DURATION);
+
+    let round = env.jackpot(round_id).unwrap();
+    assert_eq!(round.status, PoolStatus::Waiting);
+    assert_eq!((round.start_ts, round.end_ts), (now, now + DURATION));
+    assert!(round.entries.is_empty());
+
+    assert_eq!(env.create_jackpot(&creator, 0), Err(spinx_error(SpinXError::InvalidExpiry)));
+}
+
+#[test]
+fn duration_is_bounded_by_the_max_pool_ttl() {
+    let mut env = Env::new();
+    let admin = env.admin;
+    let creator = env.player(0);
+
+    env.svm.process(env.admin_ix(&admin, instruction::SetMaxPoolTtl { max_pool_ttl: DURATION })).unwrap();
+
+    assert_eq!(env.create_jackpot(&creator, DURATION + 1), Err(spinx_error(SpinXError::InvalidExpiry)));
+    env.create_jackpot(&creator, DURATION).unwrap();
+}
+
+#[test]
+fn deposits_add_up_per_player() {
+    let mut env = Env::new();
+    let creator = env.player(0);
+    let first = env.player(100 * SOL);
+    let second = env.player(100 * SOL);
+    let treasury = env.svm.lamports(&env.treasury);
+    let round_id = env.create_jackpot(&creator, DURATION).unwrap();
+
+    env.enter_jackpot(round_id, &first, STAKE).unwrap();
+    env.enter_jackpot(round_id, &second, 30 * SOL).unwrap();
+    env.enter_jackpot(round_id, &first, 10 * SOL).unwrap();
+
+    let event = &env.events::<JackpotEntered>()[0];
+    assert_eq!((event.player, event.amount, event.player_amount), (first, 10 * SOL, 30 * SOL));
+    assert_eq!((event.total_amount, event.players, event.fee), (60 * SOL, 2, COINFLIP_FEE));
+
+    let round = env.jackpot(round_id).unwrap();
+    assert_eq!(
+        round.entries,
+        vec![
+            JackpotEntry { player: first, amount: 30 * SOL },
+            JackpotEntry { player: second, amount: 30 * SOL },
+        ]
+    );
+    assert_eq!(round.total_amount, 60 * SOL);
+
+    // Repeat deposits do not grow the account
+    let round_address = jackpot_address(round_id);
+    assert_eq!(env.svm.account(&round_address).unwrap().data.len(), JackpotRound::space(2));
+    assert_eq!(env.token_balance(&round_address, &env.mint), 60 * SOL);
+    assert_eq!(env.svm.lamports(&env.treasury), treasury + 3 * COINFLIP_FEE);
+}
+
+#[test]
+fn deposits_close_at_the_end_of_the_round() {
+    let mut env = Env::new();
+    let creator = env.player(0);
+    let player = env.player(100 * SOL);
+    let round_id = env.create_jackpot(&creator, DURATION).unwrap();
+
+    assert_eq!(env.enter_jackpot(round_id, &player, 10 * SOL - 1), Err(spinx_error(SpinXError::AmountTooSmall)));
+
+    env.svm.warp(DURATION as i64);
+    assert_eq!(env.enter_jackpot(round_id, &player, STAKE), Err(spinx_error(SpinXError::JackpotClosed)));
+}
+
+#[test]
+fn rounds_hold_a_bounded_number_of_players() {
+    let mut env = Env::new();
+    let creator = env.player(0);
+    let round_id = env.create_jackpot(&creator, DURATION).unwrap();
+
+    let mut players = Vec::new();
+    for _ in 0..MAX_JACKPOT_ENTRIES {
+        let player = env.player(100 * SOL);
+        env.enter_jackpot(round_id, &player, 10 * SOL).unwrap();
+        players.push(player);
+    }
+
+    let latecomer = env.player(100 * SOL);
+    assert_eq!(env.enter_jackpot(round_id, &latecomer, 10 * SOL), Err(spinx_error(SpinXError::JackpotFull)));
+
+    // Players already in can still add to their entry
+    env.enter_jackpot(round_id, &players[0], 10 * SOL).unwrap();
+}
+
+#[test]
+fn draw_waits_for_the_end_and_two_players() {
+    let mut env = Env::new();
+    let creator = env.player(0);
+    let player = env.player(100 * SOL);
+    let round_id = env.create_jackpot(&creator, DURATION).unwrap();
+    env.enter_jackpot(round_id, &player, STAKE).unwrap();
+
+    let ix = env.draw_jackpot_ix(round_id, &creator, force(1));
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::JackpotNotEnded)));
+
+    env.svm.warp(DURATION as i64);
+    let ix = env.draw_jackpot_ix(round_id, &creator, force(1));
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::NotEnoughPlayers)));
+}
+
+#[test]
+fn draw_requests_randomness() {
+    let (mut env, small, _, round_id) = drawn_round();
+
+    let event = &env.events::<JackpotDrawn>()[0];
+    assert_eq!((event.round_id, event.force, event.total_amount, event.players), (round_id, force(1), 4 * STAKE, 2));
+
+    let round = env.jackpot(round_id).unwrap();
+    assert_eq!(round.status, PoolStatus::Processing);
+    assert_eq!(round.random_account(), orao_randomness(&force(1)));
+
+    let ix = env.settle_jackpot_ix(round_id, &small);
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::StillProcessing)));
+}
+
+#[test]
+fn smaller_deposit_wins_below_its_share() {
+    let (mut env, small, large, round_id) = drawn_round();
+    let mint = env.mint;
+
+    env.fulfill(force(1), (1 << 62) - 1);
+
+    let ix = env.settle_jackpot_ix(round_id, &large);
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::NotWinner)));
+
+    env.svm.process(env.settle_jackpot_ix(round_id, &small)).unwrap();
+
+    let event = &env.events::<JackpotSettled>()[0];
+    assert_eq!((event.winner, event.winner_amount), (small, STAKE));
+    assert_eq!((event.total_amount, event.payout, event.rake), (4 * STAKE, 4 * STAKE, 0));
+    assert_eq!(env.token_balance(&small, &mint), 100 * SOL + 3 * STAKE);
+    assert_eq!(env.token_balance(&large, &mint), 100 * SOL - 3 * STAKE);
+    assert!(env.jackpot(round_id).is_none());
+}
+
+#[test]
+fn larger_deposit_wins_from_its_share_up() {
+    let (mut env, small, large, round_id) = drawn_round();
+    let mint = env.mint;
+
+    env.fulfill(force(1), 1 << 62);
+    env.svm.process(env.settle_jackpot_ix(round_id, &large)).unwrap();
+
+    assert_eq!(env.events::<JackpotSettled>()[0].winner, large);
+    assert_eq!(env.token_balance(&large, &mint), 100 * SOL + STAKE);
+    assert_eq!(env.token_balance(&small, &mint), 100 * SOL - STAKE);
+}
+
+#[test]
+fn settlement_takes_the_rake_of_round_creation() {
+    let mut env = Env::new();
+    let admin = env.admin;
+    let creator = env.player(0);
+    let first = env.player(100 * SOL);
+    let second = env.player(100 * SOL);
+    let treasury = env.treasury;
+    let mint = env.mint;
+
+    env.svm.process(env.admin_ix(&admin, instruction::SetRake { rake_bps: 500 })).unwrap();
+    let round_id = env.create_jackpot(&creator, DURATION).unwrap();
+    env.svm.process(env.admin_ix(&admin, instruction::SetRake { rake_bps: 0 })).unwrap();
+
+    env.enter_jackpot(round_id, &first, STAKE).unwrap();
+    env.enter_jackpot(round_id, &second, STAKE).unwrap();
+    env.svm.warp(DURATION as i64);
+    env.svm.process(env.draw_jackpot_ix(round_id, &creator, force(1))).unwrap();
+
+    let creator_lamports = env.svm.lamports(&creator);
+    let round = jackpot_address(round_id);
+    let rent = env.svm.lamports(&round) + env.svm.lamports(&ata(&round, &mint, &anchor_spl::token::ID));
+    env.fulfill(force(1), u64::MAX);
+    env.svm.process(env.settle_jackpot_ix(round_id, &second)).unwrap();
+
+    let rake = 2 * STAKE * 500 / 10_000;
+    let event = &env.events::<JackpotSettled>()[0];
+    assert_eq!((event.payout, event.rake), (2 * STAKE - rake, rake));
+    assert_eq!(env.token_balance(&second, &mint), 100 * SOL + STAKE - rake);
+    assert_eq!(env.token_balance(&treasury, &mint), rake);
+    assert_eq!(env.svm.lamports(&creator), creator_lamports + rent);
+}
+
+#[test]
+fn rounds_without_two_players_are_refunded() {
+    let mut env = Env::new();
+    let creator = env.player(0);
+    let player = env.player(100 * SOL);
+    let mint = env.mint;
+    let round_id = env.create_jackpot(&creator, DURATION).unwrap();
+    env.enter_jackpot(round_id, &player, STAKE).unwrap();
+
+    let ix = env.refund_jackpot_ix(round_id, &[player]);
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::RefundNotAvailable)));
+
+    env.svm.warp(DURATION as i64);
+    env.svm.process(env.refund_jackpot_ix(round_id, &[player])).unwrap();
+
+    let event = &env.events::<JackpotRefunded>()[0];
+    assert_eq!((event.player, event.amount, event.remaining_amount), (player, STAKE, 0));
+    assert_eq!(env.token_balance(&player, &mint), 100 * SOL);
+    assert!(env.jackpot(round_id).is_none());
+    assert!(env.svm.account(&ata(&jackpot_address(round_id), &mint, &anchor_spl::token::ID)).is_none());
+}
+
+#[test]
+fn drawn_rounds_refund_in_batches_after_the_timeout() {
+    let (mut env, small, large, round_id) = drawn_round();
+    let mint = env.mint;
+
+    let ix = env.refund_jackpot_ix(round_id, &[small, large]);
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::RefundNotAvailable)));
+
+    env.svm.warp(DEFAULT_REFUND_TIMEOUT as i64);
+    env.svm.process(env.refund_jackpot_ix(round_id, &[small])).unwrap();
+
+    let round = env.jackpot(round_id).unwrap();
+    assert_eq!((round.status, round.total_amount), (PoolStatus::Refunded, 3 * STAKE));
+    assert_eq!(env.token_balance(&small, &mint), 100 * SOL);
+
+    let ix = env.refund_jackpot_ix(round_id, &[small]);
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::NotInJackpot)));
+    let ix = env.draw_jackpot_ix(round_id, &small, force(2));
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::InvalidPoolStatus)));
+
+    env.svm.process(env.refund_jackpot_ix(round_id, &[large])).unwrap();
+    assert_eq!(env.token_balance(&large, &mint), 100 * SOL);
+    assert!(env.jackpot(round_id).is_none());
+}
+
+#[test]
+fn fulfilled_rounds_cannot_be_refunded() {
+    let (mut env, small, large, round_id) = drawn_round();
+
+    env.fulfill(force(1), 1);
+    env.svm.warp(DEFAULT_REFUND_TIMEOUT as i64);
+
+    let ix = env.refund_jackpot_ix(round_id, &[small, large]);
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::RandomnessFulfilled)));
+}
+
+#[test]
+fn tokens_sent_to_the_escrow_go_to_the_treasury() {
+    let (mut env, small, large, round_id) = drawn_round();
+    let mint = env.mint;
+    let treasury = env.treasury;
+    let escrow = ata(&jackpot_address(round_id), &mint, &anchor_spl::token::ID);
+
+    env.send_tokens(&small, &mint, &escrow, 1_000);
+    env.fulfill(force(1), u64::MAX);
+    env.svm.process(env.settle_jackpot_ix(round_id, &large)).unwrap();
+
+    assert_eq!(env.events::<JackpotSettled>()[0].payout, 4 * STAKE);
+    assert_eq!(env.token_balance(&treasury, &mint), 1_000);
+    assert!(env.svm.account(&escrow).is_none());
+}
+
+#[test]
+fn draws_use_orao_while_switchboard_is_selected() {
+    let mut env = Env::new();
+    let queue = env.use_switchboard();
+    let creator = env.player(0);
+    let small = env.player(100 * SOL);
+    let large = env.player(100 * SOL);
+    let round_id = env.create_jackpot(&creator, DURATION).unwrap();
+    env.enter_jackpot(round_id, &small, STAKE).unwrap();
+    env.enter_jackpot(round_id, &large, 3 * STAKE).unwrap();
+    env.svm.warp(DURATION as i64);
+
+    // The drawer could hold back a Switchboard reveal, so it isn't accepted
+    let random = wallet();
+    env.svm.warp_to_slot(SEED_SLOT + 1);
+    env.switchboard_randomness(&random, &queue, SEED_SLOT, 0, 0);
+    let ix = with_account(env.draw_jackpot_ix(round_id, &creator, force(1)), &orao_randomness(&force(1)), &random);
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::InvalidRandomnessAccount)));
+
+    env.svm.process(env.draw_jackpot_ix(round_id, &creator, force(1))).unwrap();
+    let round = env.jackpot(round_id).unwrap();
+    assert_eq!((round.randomness_provider, round.random, round.drawer), (RandomnessProvider::Orao, orao_randomness(&force(1)), creator));
+
+    env.fulfill(force(1), u64::MAX);
+    env.svm.process(env.settle_jackpot_ix(round_id, &large)).unwrap();
+    assert_eq!(env.events::<JackpotSettled>()[0].winner, large);
+}
+
+#[test]
+fn jackpots_pause_separately() {
+    let mut env = Env::new();
+    let admin = env.admin;
+    let creator = env.player(0);
+    let player = env.player(100 * SOL);
+    let round_id = env.create_jackpot(&creator, DURATION).unwrap();
+
+    env.svm.process(env.pause_ix(&admin, PAUSE_JACKPOT)).unwrap();
+    assert_eq!(env.create_jackpot(&creator, DURATION), Err(spinx_error(SpinXError::InstructionPaused)));
+    assert_eq!(env.enter_jackpot(round_id, &player, STAKE), Err(spinx_error(SpinXError::InstructionPaused)));
+
+    // Coinflips are not affected
+    env.create_pool(&player, 0, STAKE).unwrap();
+}
@@ -0,0 +1,283 @@
+#![cfg(sbf_build)]
+
+use anchor_lang::error::ErrorCode;
+use anchor_lang::prelude::Pubkey;
+use spinx_contract::account::{CoinflipPool, PoolStatus};
+use spinx_contract::error::SpinXError;
+use spinx_contract::event::*;
+use spinx_contract::instruction;
+use spinx_contract::utils::*;
+use spinx_tests::env::*;
+use spinx_tests::svm::Account;
+use spl_token_2022::extension::default_account_state::instruction::initialize_default_account_state;
+use spl_token_2022::extension::transfer_fee::instruction::initialize_transfer_fee_config;
+use spl_token_2022::extension::ExtensionType;
+use spl_token_2022::instruction::initialize_permanent_delegate;
+use spl_token_2022::state::AccountState;
+
+#[test]
+fn add_mint_copies_global_defaults() {
+    let mut env = Env::new();
+    let event = &env.events::<MintAdded>()[0];
+    assert_eq!((event.mint, event.min_amount, event.max_amount, event.coinflip_fee), (env.mint, 10 * SOL, 0, COINFLIP_FEE));
+
+    let config = env.mint_config(&env.mint).unwrap();
+    assert!(config.enabled);
+    assert_eq!(config.mint, env.mint);
+    assert_eq!(config.min_amount, 10 * SOL);
+    assert_eq!(config.max_amount, 0);
+    assert_eq!(config.coinflip_fee, COINFLIP_FEE);
+
+    // Already allowlisted
+    let ix = env.add_mint_ix(&env.mint.clone());
+    assert!(env.svm.process(ix).is_err());
+}
+
+#[test]
+fn add_mint_requires_super_admin() {
+    let mut env = Env::new();
+    let mint = wallet();
+    env.create_mint(&mint, &anchor_spl::token::ID, None);
+
+    let stranger = env.player(0);
+    let ix = with_account(env.add_mint_ix(&mint), &env.admin, &stranger);
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::InvalidAdmin)));
+    assert!(env.mint_config(&mint).is_none());
+}
+
+#[test]
+fn update_mint_limits_and_fee() {
+    let mut env = Env::new();
+    let admin = env.admin;
+    let mint = env.mint;
+    let creator = env.player(100 * SOL);
+
+    let ix = env.mint_config_ix(&admin, &mint, instruction::UpdateMint { min_amount: 0, max_amount: 0, coinflip_fee: 0 });
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::InvalidAmount)));
+
+    let ix = env.mint_config_ix(&admin, &mint, instruction::UpdateMint { min_amount: 2 * SOL, max_amount: SOL, coinflip_fee: 0 });
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::InvalidAmount)));
+
+    let stranger = env.player(0);
+    let ix = env.mint_config_ix(&stranger, &mint, instruction::UpdateMint { min_amount: SOL, max_amount: 0, coinflip_fee: 0 });
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::InvalidAdmin)));
+
+    let ix = env.mint_config_ix(&admin, &mint, instruction::UpdateMint { min_amount: SOL, max_amount: 5 * SOL, coinflip_fee: 1_000 });
+    env.svm.process(ix).unwrap();
+
+    let event = &env.events::<MintConfigUpdated>()[0];
+    assert_eq!((event.old_min_amount, event.new_min_amount), (10 * SOL, SOL));
+    assert_eq!((event.old_max_amount, event.new_max_amount), (0, 5 * SOL));
+    assert_eq!((event.old_coinflip_fee, event.new_coinflip_fee), (COINFLIP_FEE, 1_000));
+
+    assert_eq!(env.create_pool(&creator, 0, SOL - 1), Err(spinx_error(SpinXError::AmountTooSmall)));
+    assert_eq!(env.create_pool(&creator, 0, 5 * SOL + 1), Err(spinx_error(SpinXError::AmountTooLarge)));
+
+    let treasury = env.svm.lamports(&env.treasury);
+    let pool_id = env.create_pool(&creator, 0, 5 * SOL).unwrap();
+    assert_eq!(env.svm.lamports(&env.treasury), treasury + 1_000);
+    assert_eq!(env.events::<PoolCreated>()[0].fee, 1_000);
+    assert_eq!(env.pool(pool_id).unwrap().creator_amount, 5 * SOL);
+}
+
+#[test]
+fn disabled_mint_only_allows_closing() {
+    let mut env = Env::new();
+    let admin = env.admin;
+    let mint = env.mint;
+    let creator = env.player(100 * SOL);
+    let joiner = env.player(100 * SOL);
+    let pool_id = env.create_pool(&creator, 0, STAKE).unwrap();
+
+    env.svm.process(env.mint_config_ix(&admin, &mint, instruction::DisableMint {})).unwrap();
+    assert!(!env.events::<MintEnabledUpdated>()[0].enabled);
+    assert!(!env.mint_config(&mint).unwrap().enabled);
+
+    assert_eq!(env.create_pool(&creator, 0, STAKE), Err(spinx_error(SpinXError::TokenNotAllowed)));
+    assert_eq!(env.join_pool(pool_id, &joiner, force(1), 1, STAKE), Err(spinx_error(SpinXError::TokenNotAllowed)));
+
+    env.svm.process(env.mint_config_ix(&admin, &mint, instruction::EnableMint {})).unwrap();
+    assert!(env.events::<MintEnabledUpdated>()[0].enabled);
+    env.create_pool(&creator, 0, STAKE).unwrap();
+
+    env.svm.process(env.mint_config_ix(&admin, &mint, instruction::DisableMint {})).unwrap();
+    env.svm.process(env.close_ix(pool_id, &creator)).unwrap();
+    assert_eq!(env.token_balance(&creator, &mint), 100 * SOL - STAKE);
+}
+
+#[test]
+fn mints_outside_the_allowlist_are_rejected() {
+    let mut env = Env::new();
+    let mint = wallet();
+    env.create_mint(&mint, &anchor_spl::token::ID, None);
+    let creator = env.player(0);
+    env.create_token_account(&creator, &mint, 100 * SOL);
+
+    let ix = env.create_ix(&creator, &mint, 0, STAKE, 0);
+    assert_eq!(env.svm.process(ix), Err(anchor_error(ErrorCode::AccountNotInitialized)));
+}
+
+#[test]
+fn treasury_wallet_must_match_global_data() {
+    let mut env = Env::new();
+    let creator = env.player(100 * SOL);
+    let mint = env.mint;
+
+    let ix = with_account(env.create_ix(&creator, &mint, 0, STAKE, 0), &env.treasury, &wallet());
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::OwnerMismatch)));
+}
+
+#[test]
+fn unsafe_token_2022_extensions_are_rejected() {
+    let mut env = Env::new();
+    let admin = env.admin;
+
+    let mint = wallet();
+    let delegate = wallet();
+    env.create_token_2022_mint(
+        &mint,
+        None,
+        &[ExtensionType::PermanentDelegate],
+        vec![initialize_permanent_delegate(&spl_token_2022::ID, &mint, &delegate).unwrap()],
+    );
+    let ix = env.add_mint_ix(&mint);
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::UnsafeMintExtension)));
+
+    let mint = wallet();
+    // Frozen by default needs a freeze authority
+    env.create_token_2022_mint(
+        &mint,
+        Some(&admin),
+        &[ExtensionType::DefaultAccountState],
+        vec![initialize_default_account_state(&spl_token_2022::ID, &mint, &AccountState::Frozen).unwrap()],
+    );
+    let ix = env.add_mint_ix(&mint);
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::UnsafeMintExtension)));
+}
+
+#[test]
+fn freeze_authority_has_to_be_approved() {
+    let mut env = Env::new();
+    let authority = wallet();
+    let mint = wallet();
+    env.create_mint(&mint, &anchor_spl::token::ID, Some(authority));
+
+    let ix = env.add_mint_ix(&mint);
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::FreezeAuthorityNotApproved)));
+    let ix = env.add_mint_approving_ix(&mint, &wallet());
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::FreezeAuthorityNotApproved)));
+
+    env.svm.process(env.add_mint_approving_ix(&mint, &authority)).unwrap();
+    assert_eq!(env.mint_config(&mint).unwrap().approved_freeze_authority, authority);
+
+    // Games re-check the mint, a new freeze authority blocks them
+    let creator = env.player(0);
+    env.create_token_account(&creator, &mint, 100 * SOL);
+    env.create_token_account(&env.treasury.clone(), &mint, 0);
+    env.create_mint(&mint, &anchor_spl::token::ID, Some(wallet()));
+    let ix = env.create_ix(&creator, &mint, 0, STAKE, 0);
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::FreezeAuthorityNotApproved)));
+
+    env.create_mint(&mint, &anchor_spl::token::ID, Some(authority));
+    env.svm.process(env.create_ix(&creator, &mint, 0, STAKE, 0)).unwrap();
+}
+
+/// An allowlisted Token-2022 mint charging a 1% transfer fee, capped far above any stake.
+fn transfer_fee_mint(env: &mut Env) -> Pubkey {
+    let mint = wallet();
+    let admin = env.admin;
+    env.create_token_2022_mint(
+        &mint,
+        None,
+        &[ExtensionType::TransferFeeConfig],
+        vec![initialize_transfer_fee_config(&spl_token_2022::ID, &mint, Some(&admin), Some(&admin), 100, u64::MAX).unwrap()],
+    );
+    env.svm.process(env.add_mint_ix(&mint)).unwrap();
+    env.mint_to(&env.treasury.clone(), &mint, 0);
+    mint
+}
+
+#[test]
+fn token_2022_transfer_fee_is_taken_from_the_stake() {
+    let mut env = Env::new();
+    let mint = transfer_fee_mint(&mut env);
+
+    let creator = env.player(0);
+    let joiner = env.player(0);
+    env.mint_to(&creator, &mint, 100 * SOL);
+    env.mint_to(&joiner, &mint, 100 * SOL);
+
+    let net = STAKE - STAKE / 100;
+    let pool_id = env.next_pool_id();
+    env.svm.process(env.create_ix(&creator, &mint, 0, STAKE, 0)).unwrap();
+    assert_eq!(env.pool(pool_id).unwrap().creator_amount, net);
+
+    env.join_pool(pool_id, &joiner, force(1), 1, STAKE).unwrap();
+    assert_eq!(env.pool(pool_id).unwrap().pool_amount, 2 * net);
+
+    // Odd randomness, the joiner picked 1
+    env.fulfill(force(1), 1);
+    env.svm.process(env.result_ix(pool_id, force(1))).unwrap();
+
+    let payout = 2 * net;
+    assert_eq!(env.events::<PoolSettled>()[0].payout, payout);
+    assert_eq!(env.token_balance(&joiner, &mint), 100 * SOL - STAKE + payout - payout / 100);
+    assert_eq!(env.token_balance(&creator, &mint), 100 * SOL - STAKE);
+    // Withheld fees were harvested so the escrow could close
+    assert!(env.pool(pool_id).is_none());
+    assert!(env.svm.account(&ata(&pool_address(pool_id), &mint, &spl_token_2022::ID)).is_none());
+}
+
+#[test]
+fn transfer_fee_stakes_are_listed_by_what_the_escrow_receives() {
+    let mut env = Env::new();
+    let mint = transfer_fee_mint(&mut env);
+    let creator = env.player(0);
+    env.mint_to(&creator, &mint, 1_000 * SOL);
+
+    // 100 tokens sent, 99 received, the pool belongs with the stakes below 100
+    let pool_id = env.next_pool_id();
+    env.svm.process(env.create_ix(&creator, &mint, 0, 100 * SOL, 0)).unwrap();
+    let pool = env.pool(pool_id).unwrap();
+    assert_eq!((pool.creator_amount, pool.stake_tier), (99 * SOL, stake_tier(99 * SOL)));
+    assert_eq!(env.lobby(&mint, stake_tier(99 * SOL)), vec![pool_id]);
+    assert!(env.pool_registry(&mint, stake_tier(100 * SOL)).is_none());
+}
+
+#[test]
+fn migrate_coinflip_pool_grows_older_layouts() {
+    let mut env = Env::new();
+    let creator = env.player(100 * SOL);
+    let payer = env.player(0);
+    let pool_id = env.create_pool(&creator, 1, STAKE).unwrap();
+    let new_len = 8 + std::mem::size_of::<CoinflipPool>();
+
+    // Cut the pool back to its layout before the mint allowlist, which ended at status
+    let old_len = 8 + 8 + 8 + 1 + 32 + 8 + 32 + 32 + 8 + 1 + 32 + 32 + 8 + 1 + 32 + 1;
+    let address = pool_address(pool_id);
+    let mut account = env.svm.account(&address).unwrap().clone();
+    account.data.truncate(old_len);
+    env.svm.set_account(address, account);
+
+    env.svm.process(env.migrate_pool_ix(&payer, pool_id)).unwrap();
+
+    let pool = env.pool(pool_id).unwrap();
+    assert_eq!(env.svm.account(&address).unwrap().data.len(), new_len);
+    assert_eq!(pool.mint, env.global_data().spinx_token);
+    assert_eq!(pool.creator_player, creator);
+    assert_eq!(pool.creator_amount, STAKE);
+    assert_eq!(pool.creator_set_number, 1);
+    assert_eq!(pool.status, PoolStatus::Waiting);
+    assert_eq!(pool.expiry_ts, 0);
+}
+
+#[test]
+fn migrate_coinflip_pool_checks_the_discriminator() {
+    let mut env = Env::new();
+    let payer = env.player(0);
+    let address = pool_address(7);
+    env.svm.set_account(address, Account::new(SOL, vec![0; 64], spinx_contract::ID));
+
+    let ix = env.migrate_pool_ix(&payer, 7);
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::InvalidAccountData)));
+}
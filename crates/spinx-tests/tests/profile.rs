@@ -0,0 +1,157 @@
+#![cfg(sbf_build)]
+
+use anchor_lang::system_program;
+use anchor_spl::token::spl_token::native_mint;
+use spinx_contract::instruction;
+use spinx_tests::env::*;
+use spinx_tests::svm::Account;
+
+#[test]
+fn create_and_join_open_the_profiles() {
+    let mut env = Env::new();
+    let mint = env.mint;
+    let creator = env.player(100 * SOL);
+    let joiner = env.player(100 * SOL);
+
+    let pool_id = env.create_pool(&creator, 0, STAKE).unwrap();
+    let profile = env.profile(&creator, &mint).unwrap();
+    assert_eq!((profile.player, profile.mint, profile.games_played), (creator, mint, 0));
+    assert!(env.profile(&joiner, &mint).is_none());
+
+    env.join_pool(pool_id, &joiner, force(1), 1, STAKE).unwrap();
+    assert_eq!(env.profile(&joiner, &mint).unwrap().player, joiner);
+
+    // A second game reuses the profile
+    env.create_pool(&creator, 0, STAKE).unwrap();
+    assert_eq!(env.profile(&creator, &mint).unwrap().player, creator);
+}
+
+#[test]
+fn settlement_records_the_result() {
+    let mut env = Env::new();
+    let mint = env.mint;
+    let creator = env.player(100 * SOL);
+    let joiner = env.player(100 * SOL);
+
+    env.play(&creator, &joiner, 1, STAKE, 4);
+
+    let winner = env.profile(&creator, &mint).unwrap();
+    assert_eq!((winner.games_played, winner.wins, winner.losses), (1, 1, 0));
+    assert_eq!((winner.total_wagered, winner.net_pnl), (STAKE, STAKE as i64));
+    assert_eq!((winner.current_streak, winner.best_streak), (1, 1));
+    assert_eq!(winner.last_game_ts, env.svm.clock().unix_timestamp as u64);
+
+    let loser = env.profile(&joiner, &mint).unwrap();
+    assert_eq!((loser.games_played, loser.wins, loser.losses), (1, 0, 1));
+    assert_eq!((loser.total_wagered, loser.net_pnl), (STAKE, -(STAKE as i64)));
+    assert_eq!((loser.current_streak, loser.best_streak), (0, 0));
+}
+
+#[test]
+fn streaks_and_pnl_follow_the_games() {
+    let mut env = Env::new();
+    let mint = env.mint;
+    let admin = env.admin;
+    env.svm.process(env.admin_ix(&admin, instruction::SetRake { rake_bps: 500 })).unwrap();
+
+    let creator = env.player(200 * SOL);
+    let joiner = env.player(200 * SOL);
+    let rake = 2 * STAKE * 500 / 10_000;
+
+    env.play(&creator, &joiner, 1, STAKE, 2);
+    env.play(&creator, &joiner, 2, STAKE, 4);
+    env.play(&creator, &joiner, 3, STAKE, 7);
+
+    let creator_profile = env.profile(&creator, &mint).unwrap();
+    assert_eq!((creator_profile.games_played, creator_profile.wins, creator_profile.losses), (3, 2, 1));
+    assert_eq!((creator_profile.current_streak, creator_profile.best_streak), (0, 2));
+    assert_eq!(creator_profile.total_wagered, 3 * STAKE);
+    // Two wins of the joiner's stake less rake, one lost stake
+    assert_eq!(creator_profile.net_pnl, 2 * (STAKE - rake) as i64 - STAKE as i64);
+
+    let joiner_profile = env.profile(&joiner, &mint).unwrap();
+    assert_eq!((joiner_profile.current_streak, joiner_profile.best_streak), (1, 1));
+    assert_eq!(joiner_profile.net_pnl, (STAKE - rake) as i64 - 2 * STAKE as i64);
+}
+
+#[test]
+fn full_counters_saturate_instead_of_failing_settlement() {
+    let mut env = Env::new();
+    let mint = env.mint;
+    let creator = env.player(100 * SOL);
+    let joiner = env.player(100 * SOL);
+
+    let pool_id = env.create_pool(&creator, 0, STAKE).unwrap();
+    env.join_pool(pool_id, &joiner, force(1), 1, STAKE).unwrap();
+
+    let mut profile = env.profile(&creator, &mint).unwrap();
+    (profile.games_played, profile.wins, profile.total_wagered) = (u64::MAX, u64::MAX, u64::MAX);
+    (profile.net_pnl, profile.current_streak) = (i64::MAX, u32::MAX);
+    env.set_profile(&profile);
+    let mut profile = env.profile(&joiner, &mint).unwrap();
+    (profile.losses, profile.net_pnl) = (u64::MAX, i64::MIN);
+    env.set_profile(&profile);
+
+    env.fulfill(force(1), 4);
+    env.svm.process(env.result_ix(pool_id, force(1))).unwrap();
+
+    let winner = env.profile(&creator, &mint).unwrap();
+    assert_eq!((winner.games_played, winner.wins, winner.total_wagered), (u64::MAX, u64::MAX, u64::MAX));
+    assert_eq!((winner.net_pnl, winner.current_streak, winner.best_streak), (i64::MAX, u32::MAX, u32::MAX));
+    let loser = env.profile(&joiner, &mint).unwrap();
+    assert_eq!((loser.games_played, loser.losses, loser.net_pnl), (1, u64::MAX, i64::MIN));
+    assert_eq!(env.token_balance(&creator, &mint), 100 * SOL + STAKE);
+}
+
+#[test]
+fn sol_pools_keep_a_native_mint_profile() {
+    let mut env = Env::new();
+    env.enable_sol();
+    let creator = wallet();
+    let joiner = wallet();
+    env.svm.airdrop(&creator, 100 * SOL);
+    env.svm.airdrop(&joiner, 100 * SOL);
+
+    let pool_id = env.create_sol_pool(&creator, 0, STAKE, 0).unwrap();
+    env.join_sol_pool(pool_id, &joiner, force(1), 1, STAKE).unwrap();
+    env.fulfill(force(1), 7);
+    env.svm.process(env.result_sol_ix(pool_id, force(1))).unwrap();
+
+    let winner = env.profile(&joiner, &native_mint::ID).unwrap();
+    assert_eq!((winner.wins, winner.net_pnl), (1, STAKE as i64));
+    assert_eq!(env.profile(&creator, &native_mint::ID).unwrap().losses, 1);
+    assert!(env.profile(&creator, &env.mint.clone()).is_none());
+}
+
+#[test]
+fn games_without_a_winner_are_not_recorded() {
+    let mut env = Env::new();
+    let mint = env.mint;
+    let creator = env.player(100 * SOL);
+
+    let pool_id = env.create_pool(&creator, 0, STAKE).unwrap();
+    env.svm.process(env.close_ix(pool_id, &creator)).unwrap();
+
+    let profile = env.profile(&creator, &mint).unwrap();
+    assert_eq!((profile.games_played, profile.total_wagered), (0, 0));
+}
+
+#[test]
+fn pools_without_profiles_still_settle() {
+    let mut env = Env::new();
+    let mint = env.mint;
+    let creator = env.player(100 * SOL);
+    let joiner = env.player(100 * SOL);
+
+    let pool_id = env.create_pool(&creator, 0, STAKE).unwrap();
+    env.join_pool(pool_id, &joiner, force(1), 1, STAKE).unwrap();
+
+    // As for a pool created before profiles existed
+    env.svm.set_account(player_profile_address(&creator, &mint), Account::new(0, vec![], system_program::ID));
+
+    env.fulfill(force(1), 4);
+    env.svm.process(env.result_ix(pool_id, force(1))).unwrap();
+
+    assert!(env.profile(&creator, &mint).is_none());
+    assert_eq!(env.profile(&joiner, &mint).unwrap().losses, 1);
+}
@@ -0,0 +1,183 @@
+#![cfg(sbf_build)]
+
+use anchor_lang::prelude::Pubkey;
+use spinx_contract::error::SpinXError;
+use spinx_contract::event::*;
+use spinx_contract::instruction;
+use spinx_contract::utils::*;
+use spinx_tests::env::*;
+
+const SHARE_BPS: u16 = 2000;
+
+/// A registered referrer and a player who bound them, with the referral share set.
+fn referred_env() -> (Env, Pubkey, Pubkey) {
+    let mut env = Env::new();
+    let admin = env.admin;
+    env.svm
+        .process(env.admin_ix(&admin, instruction::SetReferralShare { referral_share_bps: SHARE_BPS }))
+        .unwrap();
+
+    let referrer = env.player(100 * SOL);
+    let player = env.player(100 * SOL);
+    env.svm.process(env.register_referral_ix(&referrer)).unwrap();
+    env.svm.process(env.bind_referrer_ix(&player, &referrer)).unwrap();
+    (env, referrer, player)
+}
+
+#[test]
+fn admin_sets_the_referral_share() {
+    let mut env = Env::new();
+    let admin = env.admin;
+
+    let ix = env.admin_ix(&admin, instruction::SetReferralShare { referral_share_bps: MAX_REFERRAL_SHARE_BPS + 1 });
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::InvalidReferralShare)));
+    let ix = env.admin_ix(&wallet(), instruction::SetReferralShare { referral_share_bps: SHARE_BPS });
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::InvalidAdmin)));
+
+    env.svm
+        .process(env.admin_ix(&admin, instruction::SetReferralShare { referral_share_bps: SHARE_BPS }))
+        .unwrap();
+
+    let event = &env.events::<ReferralShareUpdated>()[0];
+    assert_eq!((event.old_referral_share_bps, event.new_referral_share_bps), (0, SHARE_BPS));
+    assert_eq!(env.global_data().referral_share_bps, SHARE_BPS);
+}
+
+#[test]
+fn player_binds_a_registered_referrer_once() {
+    let mut env = Env::new();
+    let referrer = env.player(0);
+    let player = env.player(0);
+
+    // Unregistered players can't be referrers
+    let ix = env.bind_referrer_ix(&player, &referrer);
+    assert!(env.svm.process(ix).is_err());
+
+    env.svm.process(env.register_referral_ix(&referrer)).unwrap();
+    let event = &env.events::<ReferralRegistered>()[0];
+    assert_eq!((event.player, event.referral_id), (referrer, 0));
+
+    env.svm.process(env.bind_referrer_ix(&player, &referrer)).unwrap();
+    let event = &env.events::<ReferrerBound>()[0];
+    assert_eq!((event.player, event.referrer), (player, referrer));
+
+    let referral = env.player_referral(&player).unwrap();
+    assert_eq!((referral.player, referral.referral_id, referral.referrer), (player, 1, referrer));
+    assert_eq!(env.player_referral(&referrer).unwrap().referred_players, 1);
+    assert_eq!(env.global_data().next_referral_id, 2);
+
+    let other = env.player(0);
+    env.svm.process(env.register_referral_ix(&other)).unwrap();
+    let ix = env.bind_referrer_ix(&player, &other);
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::ReferrerAlreadyBound)));
+}
+
+#[test]
+fn self_referrals_and_cycles_are_rejected() {
+    let mut env = Env::new();
+    let first = env.player(0);
+    let second = env.player(0);
+    let third = env.player(0);
+
+    env.svm.process(env.register_referral_ix(&first)).unwrap();
+    let ix = env.bind_referrer_ix(&first, &first);
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::SelfReferral)));
+
+    env.svm.process(env.bind_referrer_ix(&second, &first)).unwrap();
+    env.svm.process(env.bind_referrer_ix(&third, &second)).unwrap();
+
+    // Pointing back at a later registration would close the loop
+    let ix = env.bind_referrer_ix(&first, &third);
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::ReferralCycle)));
+    let ix = env.bind_referrer_ix(&first, &second);
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::ReferralCycle)));
+}
+
+#[test]
+fn referrer_earns_a_share_of_the_fees() {
+    let (mut env, referrer, player) = referred_env();
+    let creator = env.player(100 * SOL);
+    let treasury = env.svm.lamports(&env.treasury);
+    let referral_lamports = env.svm.lamports(&player_referral_address(&referrer));
+    let share = COINFLIP_FEE * SHARE_BPS as u64 / 10_000;
+
+    let pool_id = env.create_pool(&creator, 0, STAKE).unwrap();
+    assert!(env.events::<ReferralFeePaid>().is_empty());
+    assert_eq!(env.svm.lamports(&env.treasury), treasury + COINFLIP_FEE);
+
+    env.join_pool(pool_id, &player, force(1), 1, STAKE).unwrap();
+
+    let event = &env.events::<ReferralFeePaid>()[0];
+    assert_eq!((event.player, event.referrer), (player, referrer));
+    assert_eq!((event.fee, event.amount), (COINFLIP_FEE, share));
+
+    assert_eq!(env.svm.lamports(&env.treasury), treasury + 2 * COINFLIP_FEE - share);
+    assert_eq!(env.svm.lamports(&player_referral_address(&referrer)), referral_lamports + share);
+
+    let referral = env.player_referral(&referrer).unwrap();
+    assert_eq!((referral.pending_rewards, referral.total_rewards), (share, share));
+
+    env.create_pool(&player, 0, STAKE).unwrap();
+    assert_eq!(env.player_referral(&referrer).unwrap().total_rewards, 2 * share);
+}
+
+#[test]
+fn house_flips_and_jackpots_share_the_fee_too() {
+    let (mut env, referrer, player) = referred_env();
+    let share = COINFLIP_FEE * SHARE_BPS as u64 / 10_000;
+
+    env.house(200, 500, 1_000 * SOL);
+    env.flip(&player, force(1), 1, STAKE).unwrap();
+    assert_eq!(env.events::<ReferralFeePaid>()[0].amount, share);
+
+    let round_id = env.create_jackpot(&referrer, 60).unwrap();
+    env.enter_jackpot(round_id, &player, STAKE).unwrap();
+    assert_eq!(env.events::<ReferralFeePaid>()[0].amount, share);
+
+    assert_eq!(env.player_referral(&referrer).unwrap().pending_rewards, 2 * share);
+}
+
+#[test]
+fn referred_players_must_pass_their_referrer() {
+    let (mut env, referrer, player) = referred_env();
+    let other = env.player(0);
+    env.svm.process(env.register_referral_ix(&other)).unwrap();
+
+    let ix = env.create_ix(&player, &env.mint.clone(), 0, STAKE, 0);
+    let wrong_referrer = with_account(ix.clone(), &player_referral_address(&referrer), &player_referral_address(&other));
+    assert_eq!(env.svm.process(wrong_referrer), Err(spinx_error(SpinXError::InvalidReferrer)));
+    let no_referrer = with_account(ix.clone(), &player_referral_address(&referrer), &spinx_contract::ID);
+    assert_eq!(env.svm.process(no_referrer), Err(spinx_error(SpinXError::InvalidReferrer)));
+
+    // The player's own record can't be left out either
+    let ix = with_account(ix, &player_referral_address(&player), &spinx_contract::ID);
+    let ix = with_account(ix, &player_referral_address(&referrer), &spinx_contract::ID);
+    assert_eq!(env.svm.process(ix), Err(anchor_error(anchor_lang::error::ErrorCode::ConstraintSeeds)));
+}
+
+#[test]
+fn referrer_claims_the_accrued_rewards() {
+    let (mut env, referrer, player) = referred_env();
+    let share = COINFLIP_FEE * SHARE_BPS as u64 / 10_000;
+
+    let ix = env.claim_referral_ix(&referrer);
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::NoReferralRewards)));
+
+    env.create_pool(&player, 0, STAKE).unwrap();
+    let lamports = env.svm.lamports(&referrer);
+    let referral_lamports = env.svm.lamports(&player_referral_address(&referrer));
+
+    // Nobody but the referrer can claim from their account
+    let ix = with_account(env.claim_referral_ix(&referrer), &referrer, &player);
+    assert!(env.svm.process(ix).is_err());
+
+    env.svm.process(env.claim_referral_ix(&referrer)).unwrap();
+
+    let event = &env.events::<ReferralRewardsClaimed>()[0];
+    assert_eq!((event.referrer, event.amount), (referrer, share));
+    assert_eq!(env.svm.lamports(&referrer), lamports + share);
+    assert_eq!(env.svm.lamports(&player_referral_address(&referrer)), referral_lamports - share);
+
+    let referral = env.player_referral(&referrer).unwrap();
+    assert_eq!((referral.pending_rewards, referral.total_rewards), (0, share));
+}
@@ -0,0 +1,183 @@
+#![cfg(sbf_build)]
+
+use anchor_lang::system_program;
+use anchor_spl::token::spl_token::native_mint;
+use spinx_contract::account::{RegistryEntry, StakeRange};
+use spinx_contract::instruction;
+use spinx_contract::utils::*;
+use spinx_tests::env::*;
+use spinx_tests::svm::Account;
+
+/// Tier of STAKE, 20 tokens of a 9 decimal mint.
+const TIER: u8 = 10;
+
+#[test]
+fn stakes_are_sharded_by_order_of_magnitude() {
+    assert_eq!(stake_tier(0), 0);
+    assert_eq!(stake_tier(9), 0);
+    assert_eq!(stake_tier(10), 1);
+    assert_eq!(stake_tier(STAKE), TIER);
+    assert_eq!(stake_tier(100 * SOL - 1), TIER);
+    assert_eq!(stake_tier(100 * SOL), TIER + 1);
+    assert_eq!(stake_tier(u64::MAX), 19);
+}
+
+#[test]
+fn created_pools_are_listed() {
+    let mut env = Env::new();
+    let mint = env.mint;
+    let creator = env.player(1_000 * SOL);
+    let now = env.svm.clock().unix_timestamp as u64;
+
+    let first = env.create_pool(&creator, 1, STAKE).unwrap();
+    let options = PoolOptions {
+        ttl: 60,
+        joiner_stake: Some(StakeRange { min_amount: 10 * SOL, max_amount: 50 * SOL }),
+        ..Default::default()
+    };
+    let second = env.next_pool_id();
+    env.svm.process(env.create_ix_with(&creator, &mint, 0, 30 * SOL, options)).unwrap();
+    let large = env.create_pool(&creator, 0, 200 * SOL).unwrap();
+
+    let registry = env.pool_registry(&mint, TIER).unwrap();
+    assert_eq!((registry.mint, registry.stake_tier), (mint, TIER));
+    assert_eq!(registry.pools, vec![
+        RegistryEntry { pool_id: first, amount: STAKE, expiry_ts: now + DEFAULT_MAX_POOL_TTL, set_number: 1, weighted: false },
+        RegistryEntry { pool_id: second, amount: 30 * SOL, expiry_ts: now + 60, set_number: 0, weighted: true },
+    ]);
+
+    assert_eq!(env.pool(large).unwrap().stake_tier, TIER + 1);
+    assert_eq!(env.lobby(&mint, TIER + 1), vec![large]);
+}
+
+#[test]
+fn pools_leave_the_lobby_when_joined_closed_or_expired() {
+    let mut env = Env::new();
+    let mint = env.mint;
+    let creator = env.player(1_000 * SOL);
+    let joiner = env.player(100 * SOL);
+
+    let joined = env.create_pool(&creator, 0, STAKE).unwrap();
+    let closed = env.create_pool(&creator, 0, STAKE).unwrap();
+    let expired = env.next_pool_id();
+    env.svm.process(env.create_ix(&creator, &mint, 0, STAKE, 60)).unwrap();
+    let open = env.create_pool(&creator, 0, STAKE).unwrap();
+    assert_eq!(env.lobby(&mint, TIER), vec![joined, closed, expired, open]);
+
+    env.join_pool(joined, &joiner, force(1), 1, STAKE).unwrap();
+    assert_eq!(env.lobby(&mint, TIER), vec![closed, expired, open]);
+
+    env.svm.process(env.close_ix(closed, &creator)).unwrap();
+    assert_eq!(env.lobby(&mint, TIER), vec![expired, open]);
+
+    env.svm.warp(60);
+    env.svm.process(env.expire_ix(expired)).unwrap();
+    assert_eq!(env.lobby(&mint, TIER), vec![open]);
+}
+
+#[test]
+fn invite_only_pools_are_listed_once_opened() {
+    let mut env = Env::new();
+    let mint = env.mint;
+    let creator = env.player(100 * SOL);
+    let friend = env.player(0);
+
+    let pool_id = env.next_pool_id();
+    let options = PoolOptions { allowed_joiner: Some(friend), ..Default::default() };
+    env.svm.process(env.create_ix_with(&creator, &mint, 0, STAKE, options)).unwrap();
+    assert!(env.lobby(&mint, TIER).is_empty());
+
+    env.svm.warp(DEFAULT_INVITE_TIMEOUT as i64);
+    env.svm.process(env.open_ix(pool_id, &creator)).unwrap();
+    assert_eq!(env.lobby(&mint, TIER), vec![pool_id]);
+}
+
+#[test]
+fn sol_pools_are_listed_under_the_native_mint() {
+    let mut env = Env::new();
+    env.enable_sol();
+    let creator = wallet();
+    let joiner = wallet();
+    env.svm.airdrop(&creator, 100 * SOL);
+    env.svm.airdrop(&joiner, 100 * SOL);
+
+    let joined = env.create_sol_pool(&creator, 0, STAKE, 0).unwrap();
+    let closed = env.create_sol_pool(&creator, 0, STAKE, 0).unwrap();
+    let expired = env.create_sol_pool(&creator, 0, STAKE, 60).unwrap();
+    assert_eq!(env.lobby(&native_mint::ID, TIER), vec![joined, closed, expired]);
+    assert!(env.lobby(&env.mint.clone(), TIER).is_empty());
+
+    env.join_sol_pool(joined, &joiner, force(1), 1, STAKE).unwrap();
+    env.svm.process(env.close_sol_ix(closed, &creator)).unwrap();
+    env.svm.warp(60);
+    env.svm.process(env.expire_sol_ix(expired)).unwrap();
+    assert!(env.lobby(&native_mint::ID, TIER).is_empty());
+}
+
+#[test]
+fn full_shards_leave_new_pools_unlisted() {
+    let mut env = Env::new();
+    let mint = env.mint;
+    let creator = env.player(MAX_REGISTRY_POOLS as u64 * 20 * SOL + 100 * SOL);
+
+    let listed: Vec<u64> = (0..MAX_REGISTRY_POOLS).map(|_| env.create_pool(&creator, 0, STAKE).unwrap()).collect();
+    let unlisted = env.create_pool(&creator, 0, STAKE).unwrap();
+    assert_eq!(env.lobby(&mint, TIER), listed);
+
+    // Unlisted pools still play and close normally
+    env.svm.process(env.close_ix(unlisted, &creator)).unwrap();
+    assert_eq!(env.lobby(&mint, TIER).len(), MAX_REGISTRY_POOLS);
+
+    env.svm.process(env.close_ix(listed[0], &creator)).unwrap();
+    let next = env.create_pool(&creator, 0, STAKE).unwrap();
+    assert_eq!(env.lobby(&mint, TIER).last(), Some(&next));
+}
+
+#[test]
+fn expired_pools_make_room_in_full_shards() {
+    let mut env = Env::new();
+    let mint = env.mint;
+    let creator = env.player(MAX_REGISTRY_POOLS as u64 * 20 * SOL + 100 * SOL);
+
+    let expiring = env.next_pool_id();
+    env.svm.process(env.create_ix(&creator, &mint, 0, STAKE, 60)).unwrap();
+    let listed: Vec<u64> = (1..MAX_REGISTRY_POOLS).map(|_| env.create_pool(&creator, 0, STAKE).unwrap()).collect();
+    assert_eq!(env.lobby(&mint, TIER).len(), MAX_REGISTRY_POOLS);
+
+    // Nobody cranked the expired pool, the next listing drops it anyway
+    env.svm.warp(60);
+    let next = env.create_pool(&creator, 0, STAKE).unwrap();
+    assert_eq!(env.lobby(&mint, TIER), [listed, vec![next]].concat());
+
+    env.svm.process(env.expire_ix(expiring)).unwrap();
+    assert!(env.pool(expiring).is_none());
+}
+
+#[test]
+fn pools_that_never_expire_are_not_listed() {
+    let mut env = Env::new();
+    let (admin, mint) = (env.admin, env.mint);
+    let creator = env.player(100 * SOL);
+    env.svm.process(env.admin_ix(&admin, instruction::SetMaxPoolTtl { max_pool_ttl: 0 })).unwrap();
+
+    let forever = env.create_pool(&creator, 0, STAKE).unwrap();
+    let expiring = env.next_pool_id();
+    env.svm.process(env.create_ix(&creator, &mint, 0, STAKE, 60)).unwrap();
+
+    assert_eq!(env.pool(forever).unwrap().expiry_ts, 0);
+    assert_eq!(env.lobby(&mint, TIER), vec![expiring]);
+}
+
+#[test]
+fn pools_without_a_registry_still_close() {
+    let mut env = Env::new();
+    let mint = env.mint;
+    let creator = env.player(100 * SOL);
+    let pool_id = env.create_pool(&creator, 0, STAKE).unwrap();
+
+    // As for a pool created before the registry existed
+    env.svm.set_account(pool_registry_address(&mint, TIER), Account::new(0, vec![], system_program::ID));
+
+    env.svm.process(env.close_ix(pool_id, &creator)).unwrap();
+    assert!(env.pool(pool_id).is_none());
+}
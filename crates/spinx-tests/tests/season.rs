@@ -0,0 +1,299 @@
+#![cfg(sbf_build)]
+
+use anchor_lang::prelude::Pubkey;
+use anchor_spl::token::spl_token::native_mint;
+use spinx_contract::account::SeasonMetric;
+use spinx_contract::error::SpinXError;
+use spinx_contract::event::*;
+use spinx_contract::instruction;
+use spinx_contract::utils::*;
+use spinx_tests::env::*;
+
+const RAKE_BPS: u16 = 500;
+const PRIZE_SHARE_BPS: u16 = 4000;
+const WEEK: u64 = 7 * 24 * 60 * 60;
+
+fn now(env: &Env) -> u64 {
+    env.svm.clock().unix_timestamp as u64
+}
+
+/// A week long season of the default mint, starting now, with rake enabled.
+fn season_env(metric: SeasonMetric, payout_bps: Vec<u16>) -> Env {
+    let mut env = Env::new();
+    let (admin, mint) = (env.admin, env.mint);
+    env.svm.process(env.admin_ix(&admin, instruction::SetRake { rake_bps: RAKE_BPS })).unwrap();
+
+    let start_ts = now(&env);
+    env.svm
+        .process(env.create_season_ix(&mint, start_ts, start_ts + WEEK, metric, PRIZE_SHARE_BPS, payout_bps))
+        .unwrap();
+    env
+}
+
+fn prize_balance(env: &Env) -> u64 {
+    env.token_balance(&season_address(&env.mint), &env.mint)
+}
+
+fn rankings(env: &Env) -> Vec<(Pubkey, i64)> {
+    env.season(&env.mint).unwrap().rankings.iter().map(|entry| (entry.player, entry.score)).collect()
+}
+
+#[test]
+fn admin_creates_a_season() {
+    let mut env = Env::new();
+    let mint = env.mint;
+    let start_ts = now(&env);
+    let end_ts = start_ts + WEEK;
+
+    let invalid = [
+        (start_ts, start_ts, PRIZE_SHARE_BPS, vec![10_000]),
+        (start_ts, end_ts, MAX_SEASON_PRIZE_SHARE_BPS + 1, vec![10_000]),
+        (start_ts, end_ts, PRIZE_SHARE_BPS, vec![]),
+        (start_ts, end_ts, PRIZE_SHARE_BPS, vec![6000, 3000]),
+        (start_ts, end_ts, PRIZE_SHARE_BPS, vec![1000; MAX_SEASON_RANKS + 1]),
+        (start_ts - 2 * WEEK, start_ts - WEEK, PRIZE_SHARE_BPS, vec![10_000]),
+    ];
+    for (start_ts, end_ts, prize_share_bps, payout_bps) in invalid {
+        let ix = env.create_season_ix(&mint, start_ts, end_ts, SeasonMetric::Volume, prize_share_bps, payout_bps);
+        assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::InvalidSeasonConfig)));
+    }
+
+    let ix = env.create_season_ix(&mint, start_ts, end_ts, SeasonMetric::Volume, PRIZE_SHARE_BPS, vec![10_000]);
+    let ix = with_account(ix, &env.admin.clone(), &wallet());
+    assert!(env.svm.process(ix).is_err());
+
+    env.svm
+        .process(env.create_season_ix(&mint, start_ts, end_ts, SeasonMetric::NetWins, PRIZE_SHARE_BPS, vec![6000, 4000]))
+        .unwrap();
+
+    let event = &env.events::<SeasonCreated>()[0];
+    assert_eq!((event.season_id, event.mint, event.metric), (1, mint, SeasonMetric::NetWins));
+    assert_eq!((event.start_ts, event.end_ts, event.prize_share_bps), (start_ts, end_ts, PRIZE_SHARE_BPS));
+    assert_eq!(event.payout_bps, vec![6000, 4000]);
+    assert_eq!(env.global_data().last_season_id, 1);
+
+    // One season per mint at a time
+    let ix = env.create_season_ix(&mint, start_ts, end_ts + 1, SeasonMetric::Volume, PRIZE_SHARE_BPS, vec![10_000]);
+    assert!(env.svm.process(ix).is_err());
+}
+
+#[test]
+fn sol_pools_have_no_seasons() {
+    let mut env = Env::new();
+    env.enable_sol();
+    let start_ts = now(&env);
+
+    let ix = env.create_season_ix(&native_mint::ID, start_ts, start_ts + WEEK, SeasonMetric::Volume, PRIZE_SHARE_BPS, vec![10_000]);
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::TokenNotAllowed)));
+}
+
+#[test]
+fn live_season_takes_a_share_of_the_rake() {
+    let mut env = season_env(SeasonMetric::Volume, vec![10_000]);
+    let mint = env.mint;
+    let creator = env.player(100 * SOL);
+    let joiner = env.player(100 * SOL);
+    let treasury = env.token_balance(&env.treasury.clone(), &mint);
+
+    let rake = 2 * STAKE * RAKE_BPS as u64 / 10_000;
+    let prize = rake * PRIZE_SHARE_BPS as u64 / 10_000;
+
+    let pool_id = env.create_pool(&creator, 0, STAKE).unwrap();
+    env.join_pool(pool_id, &joiner, force(1), 1, STAKE).unwrap();
+    env.fulfill(force(1), 4);
+    env.svm.process(env.result_ix(pool_id, force(1))).unwrap();
+
+    let event = &env.events::<SeasonPrizeFunded>()[0];
+    assert_eq!((event.season_id, event.pool_id, event.amount), (1, pool_id, prize));
+    assert_eq!(env.events::<PoolSettled>()[0].rake, rake);
+
+    assert_eq!(prize_balance(&env), prize);
+    assert_eq!(env.token_balance(&env.treasury.clone(), &mint), treasury + rake - prize);
+    assert_eq!(env.token_balance(&creator, &mint), 100 * SOL + STAKE - rake);
+
+    // Volume scores both sides of the game
+    assert_eq!(rankings(&env), vec![(creator, STAKE as i64), (joiner, STAKE as i64)]);
+    let profile = env.profile(&creator, &mint).unwrap();
+    assert_eq!((profile.season_id, profile.season_score), (1, STAKE as i64));
+}
+
+#[test]
+fn net_wins_rank_only_winning_records() {
+    let mut env = season_env(SeasonMetric::NetWins, vec![10_000]);
+    let mint = env.mint;
+    let creator = env.player(200 * SOL);
+    let joiner = env.player(200 * SOL);
+
+    env.play(&creator, &joiner, 1, STAKE, 2);
+    env.play(&creator, &joiner, 2, STAKE, 4);
+    assert_eq!(rankings(&env), vec![(creator, 2)]);
+
+    env.play(&creator, &joiner, 3, STAKE, 7);
+    assert_eq!(rankings(&env), vec![(creator, 1)]);
+    assert_eq!(env.profile(&joiner, &mint).unwrap().season_score, -1);
+}
+
+#[test]
+fn full_scores_saturate_instead_of_failing_settlement() {
+    let mut env = season_env(SeasonMetric::Volume, vec![10_000]);
+    let mint = env.mint;
+    let creator = env.player(200 * SOL);
+    let joiner = env.player(200 * SOL);
+
+    env.play(&creator, &joiner, 1, STAKE, 2);
+    let mut profile = env.profile(&creator, &mint).unwrap();
+    profile.season_score = i64::MAX - 1;
+    env.set_profile(&profile);
+
+    env.play(&creator, &joiner, 2, STAKE, 2);
+    assert_eq!(rankings(&env), vec![(creator, i64::MAX), (joiner, 2 * STAKE as i64)]);
+}
+
+#[test]
+fn games_outside_the_season_are_not_scored() {
+    let mut env = Env::new();
+    let (admin, mint) = (env.admin, env.mint);
+    env.svm.process(env.admin_ix(&admin, instruction::SetRake { rake_bps: RAKE_BPS })).unwrap();
+    let start_ts = now(&env) + 60;
+    env.svm
+        .process(env.create_season_ix(&mint, start_ts, start_ts + WEEK, SeasonMetric::Volume, PRIZE_SHARE_BPS, vec![10_000]))
+        .unwrap();
+
+    let creator = env.player(200 * SOL);
+    let joiner = env.player(200 * SOL);
+
+    env.play(&creator, &joiner, 1, STAKE, 2);
+    assert!(env.events::<SeasonPrizeFunded>().is_empty());
+    assert!(rankings(&env).is_empty());
+
+    env.svm.warp(60 + WEEK as i64);
+    env.play(&creator, &joiner, 2, STAKE, 2);
+    assert_eq!(prize_balance(&env), 0);
+    assert!(rankings(&env).is_empty());
+    assert_eq!(env.profile(&creator, &mint).unwrap().season_score, 0);
+}
+
+#[test]
+fn rankings_keep_the_best_players() {
+    let mut env = season_env(SeasonMetric::Volume, vec![10_000]);
+    let whale = env.player(1_000 * SOL);
+    let creators: Vec<Pubkey> = (0..MAX_SEASON_RANKS as u64 + 1).map(|_| env.player(100 * SOL)).collect();
+
+    for (i, creator) in creators.iter().enumerate() {
+        env.play(creator, &whale, i as u8 + 1, STAKE + i as u64 * SOL, 2);
+    }
+
+    let ranked = rankings(&env);
+    assert_eq!(ranked.len(), MAX_SEASON_RANKS);
+    assert_eq!(ranked[0].0, whale);
+    assert!(ranked.windows(2).all(|pair| pair[0].1 >= pair[1].1));
+
+    // The two smallest stakes lost their ranks to bigger ones
+    assert!(!ranked.iter().any(|(player, _)| *player == creators[0] || *player == creators[1]));
+    assert_eq!(ranked[1], (creators[MAX_SEASON_RANKS], (STAKE + MAX_SEASON_RANKS as u64 * SOL) as i64));
+}
+
+#[test]
+fn winners_claim_their_share_once_finalized() {
+    let mut env = season_env(SeasonMetric::NetWins, vec![7000, 3000]);
+    let mint = env.mint;
+    let first = env.player(200 * SOL);
+    let second = env.player(200 * SOL);
+    let loser = env.player(200 * SOL);
+
+    env.play(&first, &loser, 1, STAKE, 2);
+    env.play(&first, &loser, 2, STAKE, 2);
+    env.play(&second, &loser, 3, STAKE, 2);
+    let prize = prize_balance(&env);
+
+    let ix = env.finalize_season_ix(&mint);
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::SeasonNotEnded)));
+    let ix = env.claim_season_prize_ix(&first, &mint);
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::SeasonNotFinalized)));
+
+    env.svm.warp(WEEK as i64);
+    env.svm.process(env.finalize_season_ix(&mint)).unwrap();
+
+    let event = &env.events::<SeasonFinalized>()[0];
+    assert_eq!((event.prize_amount, event.winners.clone()), (prize, vec![first, second]));
+    let ix = env.finalize_season_ix(&mint);
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::SeasonFinalized)));
+
+    // Later games no longer move the locked rankings
+    env.play(&loser, &second, 4, STAKE, 2);
+    assert_eq!(rankings(&env), vec![(first, 2), (second, 1)]);
+
+    let balance = env.token_balance(&first, &mint);
+    env.svm.process(env.claim_season_prize_ix(&first, &mint)).unwrap();
+    let event = &env.events::<SeasonPrizeClaimed>()[0];
+    assert_eq!((event.player, event.rank, event.amount), (first, 0, prize * 7000 / 10_000));
+    assert_eq!(env.token_balance(&first, &mint), balance + prize * 7000 / 10_000);
+
+    let ix = env.claim_season_prize_ix(&first, &mint);
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::NoSeasonPrize)));
+    let ix = env.claim_season_prize_ix(&loser, &mint);
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::NoSeasonPrize)));
+
+    env.svm.process(env.claim_season_prize_ix(&second, &mint)).unwrap();
+    assert_eq!(env.events::<SeasonPrizeClaimed>()[0].amount, prize * 3000 / 10_000);
+    assert!(env.season(&mint).unwrap().rankings.iter().all(|entry| entry.claimed));
+}
+
+#[test]
+fn closing_sweeps_unclaimed_prizes_to_the_treasury() {
+    let mut env = season_env(SeasonMetric::Volume, vec![10_000]);
+    let (admin, mint) = (env.admin, env.mint);
+    let creator = env.player(100 * SOL);
+    let joiner = env.player(100 * SOL);
+
+    env.play(&creator, &joiner, 1, STAKE, 2);
+    let prize = prize_balance(&env);
+
+    let ix = env.close_season_ix(&admin, &mint);
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::SeasonNotFinalized)));
+
+    env.svm.warp(WEEK as i64);
+    env.svm.process(env.finalize_season_ix(&mint)).unwrap();
+
+    let ix = env.close_season_ix(&admin, &mint);
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::SeasonClaimsOpen)));
+    let ix = env.close_season_ix(&creator, &mint);
+    assert!(env.svm.process(ix).is_err());
+
+    env.svm.warp(SEASON_CLAIM_WINDOW as i64);
+    let treasury = env.token_balance(&env.treasury.clone(), &mint);
+    env.svm.process(env.close_season_ix(&admin, &mint)).unwrap();
+
+    let event = &env.events::<SeasonClosed>()[0];
+    assert_eq!((event.season_id, event.swept_amount), (1, prize));
+    assert_eq!(env.token_balance(&env.treasury.clone(), &mint), treasury + prize);
+    assert!(env.season(&mint).is_none());
+    assert!(env.svm.account(&ata(&season_address(&mint), &mint, &spl_token::ID)).is_none());
+
+    // The mint is free for the next season, which restarts the scores
+    let start_ts = now(&env);
+    env.svm
+        .process(env.create_season_ix(&mint, start_ts, start_ts + WEEK, SeasonMetric::Volume, PRIZE_SHARE_BPS, vec![10_000]))
+        .unwrap();
+    assert_eq!(env.season(&mint).unwrap().season_id, 2);
+
+    env.play(&creator, &joiner, 2, STAKE, 2);
+    let profile = env.profile(&creator, &mint).unwrap();
+    assert_eq!((profile.season_id, profile.season_score), (2, STAKE as i64));
+}
+
+#[test]
+fn season_closes_early_once_every_prize_is_claimed() {
+    let mut env = season_env(SeasonMetric::NetWins, vec![10_000]);
+    let (admin, mint) = (env.admin, env.mint);
+    let creator = env.player(100 * SOL);
+    let joiner = env.player(100 * SOL);
+
+    env.play(&creator, &joiner, 1, STAKE, 2);
+    env.svm.warp(WEEK as i64);
+    env.svm.process(env.finalize_season_ix(&mint)).unwrap();
+    env.svm.process(env.claim_season_prize_ix(&creator, &mint)).unwrap();
+
+    env.svm.process(env.close_season_ix(&admin, &mint)).unwrap();
+    assert_eq!(env.events::<SeasonClosed>()[0].swept_amount, 0);
+}
@@ -0,0 +1,242 @@
+#![cfg(sbf_build)]
+
+use anchor_lang::prelude::Pubkey;
+use anchor_spl::token::spl_token::native_mint;
+use spinx_contract::account::PoolStatus;
+use spinx_contract::error::SpinXError;
+use spinx_contract::event::*;
+use spinx_contract::instruction;
+use spinx_contract::utils::*;
+use spinx_tests::env::*;
+
+/// An environment with SOL pools enabled and two players holding 100 SOL.
+fn sol_env() -> (Env, Pubkey, Pubkey) {
+    let mut env = Env::new();
+    env.enable_sol();
+
+    let creator = wallet();
+    let joiner = wallet();
+    env.svm.airdrop(&creator, 100 * SOL);
+    env.svm.airdrop(&joiner, 100 * SOL);
+
+    (env, creator, joiner)
+}
+
+#[test]
+fn sol_pools_need_the_native_mint_allowlisted() {
+    let mut env = Env::new();
+    let creator = env.player(0);
+
+    let ix = env.create_sol_ix(&creator, 0, STAKE, 0);
+    assert!(env.svm.process(ix).is_err());
+}
+
+#[test]
+fn create_moves_lamports_into_the_escrow() {
+    let (mut env, creator, _) = sol_env();
+    let treasury = env.svm.lamports(&env.treasury);
+
+    let pool_id = env.create_sol_pool(&creator, 0, STAKE, 0).unwrap();
+
+    let event = &env.events::<PoolCreated>()[0];
+    assert!(event.is_native);
+    assert_eq!((event.mint, event.amount, event.fee), (native_mint::ID, STAKE, COINFLIP_FEE));
+
+    let pool = env.pool(pool_id).unwrap();
+    assert!(pool.is_native);
+    assert_eq!(pool.mint, native_mint::ID);
+    assert_eq!(pool.creator_amount, STAKE);
+
+    let escrow = sol_escrow_address(&pool_address(pool_id));
+    assert_eq!(env.svm.lamports(&escrow), STAKE);
+    assert_eq!(env.svm.account(&escrow).unwrap().owner, anchor_lang::system_program::ID);
+    assert_eq!(env.svm.lamports(&env.treasury), treasury + COINFLIP_FEE);
+}
+
+#[test]
+fn create_enforces_the_native_mint_limits() {
+    let (mut env, creator, _) = sol_env();
+    let admin = env.admin;
+
+    assert_eq!(env.create_sol_pool(&creator, 2, STAKE, 0), Err(spinx_error(SpinXError::InvalidNumber)));
+    assert_eq!(env.create_sol_pool(&creator, 0, 10 * SOL - 1, 0), Err(spinx_error(SpinXError::AmountTooSmall)));
+
+    let ix = env.mint_config_ix(&admin, &native_mint::ID, instruction::UpdateMint { min_amount: 1, max_amount: 30 * SOL, coinflip_fee: 0 });
+    env.svm.process(ix).unwrap();
+
+    assert_eq!(env.create_sol_pool(&creator, 0, 30 * SOL + 1, 0), Err(spinx_error(SpinXError::AmountTooLarge)));
+    // The escrow has to be rent exempt on its own
+    assert_eq!(env.create_sol_pool(&creator, 0, 1, 0), Err(spinx_error(SpinXError::AmountTooSmall)));
+}
+
+#[test]
+fn sol_and_spl_instructions_do_not_mix() {
+    let (mut env, creator, joiner) = sol_env();
+    let pool_id = env.create_sol_pool(&creator, 0, STAKE, 0).unwrap();
+    let spl_pool_id = {
+        let spl_creator = env.player(100 * SOL);
+        env.create_pool(&spl_creator, 0, STAKE).unwrap()
+    };
+
+    let ix = env.close_ix(pool_id, &creator);
+    assert!(env.svm.process(ix).is_err());
+
+    assert_eq!(
+        env.join_sol_pool(spl_pool_id, &joiner, force(1), 1, STAKE),
+        Err(spinx_error(SpinXError::InvalidPoolAsset))
+    );
+}
+
+#[test]
+fn full_game_pays_the_winner_in_lamports() {
+    let (mut env, creator, joiner) = sol_env();
+    let admin = env.admin;
+    env.svm.process(env.admin_ix(&admin, instruction::SetRake { rake_bps: 250 })).unwrap();
+
+    let pool_id = env.create_sol_pool(&creator, 1, STAKE, 0).unwrap();
+    assert_eq!(env.join_sol_pool(pool_id, &joiner, force(1), 0, STAKE - 1), Err(spinx_error(SpinXError::InvalidAmount)));
+    env.join_sol_pool(pool_id, &joiner, force(1), 0, STAKE).unwrap();
+
+    let event = &env.events::<PoolJoined>()[0];
+    assert_eq!((event.joiner, event.pool_amount), (joiner, 2 * STAKE));
+    assert_eq!(env.pool(pool_id).unwrap().status, PoolStatus::Processing);
+
+    let ix = env.result_sol_ix(pool_id, force(1));
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::StillProcessing)));
+
+    let creator_lamports = env.svm.lamports(&creator);
+    let joiner_lamports = env.svm.lamports(&joiner);
+    let treasury = env.svm.lamports(&env.treasury);
+    let pool_rent = env.svm.lamports(&pool_address(pool_id));
+
+    // Odd randomness, the creator picked 1
+    env.fulfill(force(1), 9);
+    env.svm.process(env.result_sol_ix(pool_id, force(1))).unwrap();
+
+    let rake = 2 * STAKE * 250 / 10_000;
+    let event = &env.events::<PoolSettled>()[0];
+    assert_eq!((event.winner, event.loser), (creator, joiner));
+    assert_eq!((event.payout, event.rake), (2 * STAKE - rake, rake));
+
+    assert_eq!(env.svm.lamports(&creator), creator_lamports + 2 * STAKE - rake + pool_rent);
+    assert_eq!(env.svm.lamports(&joiner), joiner_lamports);
+    assert_eq!(env.svm.lamports(&env.treasury), treasury + rake);
+    assert!(env.pool(pool_id).is_none());
+    assert!(env.svm.account(&sol_escrow_address(&pool_address(pool_id))).is_none());
+}
+
+#[test]
+fn lamports_sent_to_the_escrow_go_to_the_treasury() {
+    let (mut env, creator, joiner) = sol_env();
+    let treasury = env.treasury;
+
+    // Without the sweep the escrow would be left below rent exemption
+    let pool_id = env.create_sol_pool(&creator, 1, STAKE, 0).unwrap();
+    let escrow = sol_escrow_address(&pool_address(pool_id));
+    env.join_sol_pool(pool_id, &joiner, force(1), 0, STAKE).unwrap();
+    env.svm.airdrop(&escrow, 1_000);
+    env.fulfill(force(1), 9);
+
+    let lamports = env.svm.lamports(&treasury);
+    env.svm.process(env.result_sol_ix(pool_id, force(1))).unwrap();
+    assert_eq!(env.svm.lamports(&treasury), lamports + 1_000);
+    assert!(env.svm.account(&escrow).is_none());
+
+    let pool_id = env.create_sol_pool(&creator, 1, STAKE, 0).unwrap();
+    let escrow = sol_escrow_address(&pool_address(pool_id));
+    env.svm.airdrop(&escrow, 1_000);
+
+    let lamports = env.svm.lamports(&treasury);
+    env.svm.process(env.close_sol_ix(pool_id, &creator)).unwrap();
+    assert_eq!(env.svm.lamports(&treasury), lamports + 1_000);
+    assert!(env.svm.account(&escrow).is_none());
+}
+
+#[test]
+fn close_returns_the_stake() {
+    let (mut env, creator, joiner) = sol_env();
+    let pool_id = env.create_sol_pool(&creator, 0, STAKE, 0).unwrap();
+
+    let ix = env.close_sol_ix(pool_id, &joiner);
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::InvalidCreator)));
+
+    let lamports = env.svm.lamports(&creator);
+    let pool_rent = env.svm.lamports(&pool_address(pool_id));
+    env.svm.process(env.close_sol_ix(pool_id, &creator)).unwrap();
+
+    let event = &env.events::<PoolClosed>()[0];
+    assert_eq!((event.status, event.creator_refund), (PoolStatus::Closed, STAKE));
+    assert_eq!(env.svm.lamports(&creator), lamports + STAKE + pool_rent);
+    assert!(env.pool(pool_id).is_none());
+}
+
+#[test]
+fn close_fails_once_joined() {
+    let (mut env, creator, joiner) = sol_env();
+    let pool_id = env.create_sol_pool(&creator, 0, STAKE, 0).unwrap();
+    env.join_sol_pool(pool_id, &joiner, force(1), 1, STAKE).unwrap();
+
+    let ix = env.close_sol_ix(pool_id, &creator);
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::InvalidClaimStatus)));
+    let latecomer = wallet();
+    env.svm.airdrop(&latecomer, 100 * SOL);
+    assert_eq!(env.join_sol_pool(pool_id, &latecomer, force(2), 1, STAKE), Err(spinx_error(SpinXError::InvalidPoolStatus)));
+}
+
+#[test]
+fn expire_returns_the_stake() {
+    let (mut env, creator, joiner) = sol_env();
+    let pool_id = env.create_sol_pool(&creator, 0, STAKE, 120).unwrap();
+
+    let ix = env.expire_sol_ix(pool_id);
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::PoolNotExpired)));
+
+    env.svm.warp(120);
+    assert_eq!(env.join_sol_pool(pool_id, &joiner, force(1), 1, STAKE), Err(spinx_error(SpinXError::PoolExpired)));
+
+    let lamports = env.svm.lamports(&creator);
+    let pool_rent = env.svm.lamports(&pool_address(pool_id));
+    env.svm.process(env.expire_sol_ix(pool_id)).unwrap();
+
+    assert_eq!(env.events::<PoolClosed>()[0].status, PoolStatus::Expired);
+    assert_eq!(env.svm.lamports(&creator), lamports + STAKE + pool_rent);
+}
+
+#[test]
+fn refund_returns_both_stakes() {
+    let (mut env, creator, joiner) = sol_env();
+    let pool_id = env.create_sol_pool(&creator, 0, STAKE, 0).unwrap();
+    env.join_sol_pool(pool_id, &joiner, force(1), 1, STAKE).unwrap();
+
+    let ix = env.expire_sol_ix(pool_id);
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::InvalidPoolStatus)));
+
+    let ix = env.refund_sol_ix(pool_id);
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::RefundNotAvailable)));
+
+    env.svm.warp(DEFAULT_REFUND_TIMEOUT as i64);
+    let creator_lamports = env.svm.lamports(&creator);
+    let joiner_lamports = env.svm.lamports(&joiner);
+    let pool_rent = env.svm.lamports(&pool_address(pool_id));
+
+    env.svm.process(env.refund_sol_ix(pool_id)).unwrap();
+
+    let event = &env.events::<PoolClosed>()[0];
+    assert_eq!(event.status, PoolStatus::Refunded);
+    assert_eq!((event.creator_refund, event.joiner_refund), (STAKE, STAKE));
+    assert_eq!(env.svm.lamports(&creator), creator_lamports + STAKE + pool_rent);
+    assert_eq!(env.svm.lamports(&joiner), joiner_lamports + STAKE);
+}
+
+#[test]
+fn fulfilled_pools_cannot_be_refunded() {
+    let (mut env, creator, joiner) = sol_env();
+    let pool_id = env.create_sol_pool(&creator, 0, STAKE, 0).unwrap();
+    env.join_sol_pool(pool_id, &joiner, force(1), 1, STAKE).unwrap();
+
+    env.fulfill(force(1), 1);
+    env.svm.warp(DEFAULT_REFUND_TIMEOUT as i64);
+
+    let ix = env.refund_sol_ix(pool_id);
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::RandomnessFulfilled)));
+}
@@ -0,0 +1,211 @@
+#![cfg(sbf_build)]
+
+use anchor_lang::prelude::{pubkey, Pubkey};
+use spinx_contract::account::RandomnessProvider;
+use spinx_contract::error::SpinXError;
+use spinx_contract::event::*;
+use spinx_contract::instruction;
+use spinx_contract::utils::*;
+use spinx_tests::env::*;
+use spinx_tests::programs::orao_randomness;
+use spinx_tests::svm::Account;
+
+const SEED_SLOT: u64 = 100;
+
+struct Game {
+    env: Env,
+    creator: Pubkey,
+    joiner: Pubkey,
+    pool_id: u64,
+    queue: Pubkey,
+    random: Pubkey,
+}
+
+/// A waiting pool, Switchboard selected and a randomness account committed in
+/// the previous slot.
+fn committed_game() -> Game {
+    let mut env = Env::new();
+    let admin = env.admin;
+    let queue = wallet();
+    let ix = env.admin_ix(
+        &admin,
+        instruction::SetRandomnessProvider {
+            provider: RandomnessProvider::Switchboard,
+            switchboard_queue: queue,
+        },
+    );
+    env.svm.process(ix).unwrap();
+
+    let creator = env.player(100 * SOL);
+    let joiner = env.player(100 * SOL);
+    let pool_id = env.create_pool(&creator, 0, STAKE).unwrap();
+
+    let random = wallet();
+    env.svm.warp_to_slot(SEED_SLOT + 1);
+    env.switchboard_randomness(&random, &queue, SEED_SLOT, 0, 0);
+
+    Game { env, creator, joiner, pool_id, queue, random }
+}
+
+fn join(game: &mut Game) -> Result<(), anchor_lang::prelude::ProgramError> {
+    let ix = with_account(
+        game.env.join_ix(game.pool_id, &game.joiner, force(1), 1, STAKE),
+        &orao_randomness(&force(1)),
+        &game.random,
+    );
+    game.env.svm.process(ix)
+}
+
+#[test]
+fn join_records_the_commit() {
+    let mut game = committed_game();
+    let orao_treasury = game.env.svm.lamports(&game.env.orao_treasury);
+
+    join(&mut game).unwrap();
+
+    let event = &game.env.events::<RandomnessRequested>()[0];
+    assert_eq!((event.random, event.provider), (game.random, RandomnessProvider::Switchboard));
+
+    let pool = game.env.pool(game.pool_id).unwrap();
+    assert_eq!(pool.randomness_provider, RandomnessProvider::Switchboard);
+    assert_eq!(pool.random, game.random);
+    assert_eq!(pool.commit_slot, SEED_SLOT);
+    // Nothing is paid to ORAO
+    assert_eq!(game.env.svm.lamports(&game.env.orao_treasury), orao_treasury);
+}
+
+#[test]
+fn settles_once_the_commit_is_revealed() {
+    let mut game = committed_game();
+    join(&mut game).unwrap();
+
+    let ix = game.env.result_ix(game.pool_id, force(1));
+    assert_eq!(game.env.svm.process(ix), Err(spinx_error(SpinXError::StillProcessing)));
+
+    let (random, queue) = (game.random, game.queue);
+    game.env.switchboard_randomness(&random, &queue, SEED_SLOT, SEED_SLOT + 2, 5);
+    game.env.svm.process(game.env.result_ix(game.pool_id, force(1))).unwrap();
+
+    let event = &game.env.events::<PoolSettled>()[0];
+    assert_eq!((event.randomness, event.winner), (5, game.joiner));
+    assert_eq!(game.env.token_balance(&game.joiner, &game.env.mint), 100 * SOL + STAKE);
+}
+
+#[test]
+fn join_rejects_unusable_randomness_accounts() {
+    let mut game = committed_game();
+    let (random, queue) = (game.random, game.queue);
+
+    // Another queue
+    game.env.switchboard_randomness(&random, &wallet(), SEED_SLOT, 0, 0);
+    assert_eq!(join(&mut game), Err(spinx_error(SpinXError::InvalidRandomnessAccount)));
+
+    // Committed too early
+    game.env.switchboard_randomness(&random, &queue, SEED_SLOT - 1, 0, 0);
+    assert_eq!(join(&mut game), Err(spinx_error(SpinXError::InvalidRandomnessAccount)));
+
+    // Already revealed, the joiner would know the outcome
+    game.env.switchboard_randomness(&random, &queue, SEED_SLOT, SEED_SLOT + 1, 5);
+    assert_eq!(join(&mut game), Err(spinx_error(SpinXError::RandomnessFulfilled)));
+
+    // Not owned by Switchboard
+    let mut account = game.env.svm.account(&random).unwrap().clone();
+    account.owner = wallet();
+    game.env.svm.set_account(random, account.clone());
+    assert_eq!(join(&mut game), Err(spinx_error(SpinXError::InvalidRandomnessAccount)));
+
+    // Owned by the devnet deployment, only accepted by devnet builds
+    account.owner = pubkey!("Aio4gaXjXzJNVLtzwtNVmSqGKpANtXhybbkhtAC94ji2");
+    game.env.svm.set_account(random, account);
+    assert_eq!(join(&mut game), Err(spinx_error(SpinXError::InvalidRandomnessAccount)));
+
+    // Not a randomness account
+    game.env.svm.set_account(random, Account::new(SOL, vec![0; 256], SWITCHBOARD_PROGRAM_ID));
+    assert_eq!(join(&mut game), Err(spinx_error(SpinXError::InvalidRandomnessAccount)));
+
+    game.env.switchboard_randomness(&random, &queue, SEED_SLOT, 0, 0);
+    join(&mut game).unwrap();
+}
+
+#[test]
+fn recommitted_randomness_forfeits_the_game() {
+    let mut game = committed_game();
+    join(&mut game).unwrap();
+
+    // Committed again and revealed for a later slot, the joiner gives the game up
+    let (random, queue) = (game.random, game.queue);
+    game.env.switchboard_randomness(&random, &queue, SEED_SLOT + 5, SEED_SLOT + 6, 5);
+
+    game.env.svm.warp(DEFAULT_REFUND_TIMEOUT as i64);
+    let ix = game.env.refund_ix(game.pool_id);
+    assert_eq!(game.env.svm.process(ix), Err(spinx_error(SpinXError::RandomnessForfeited)));
+
+    game.env.svm.process(game.env.result_ix(game.pool_id, force(1))).unwrap();
+
+    let event = &game.env.events::<PoolSettled>()[0];
+    assert_eq!((event.randomness, event.winner, event.loser), (0, game.creator, game.joiner));
+    assert_eq!(game.env.token_balance(&game.creator, &game.env.mint), 100 * SOL + STAKE);
+    assert_eq!(game.env.token_balance(&game.joiner, &game.env.mint), 100 * SOL - STAKE);
+}
+
+#[test]
+fn unrevealed_randomness_is_refunded_after_the_refund_timeout() {
+    let mut game = committed_game();
+    join(&mut game).unwrap();
+
+    let ix = game.env.refund_ix(game.pool_id);
+    assert_eq!(game.env.svm.process(ix), Err(spinx_error(SpinXError::RefundNotAvailable)));
+
+    // The oracle never revealed, neither player is to blame
+    game.env.svm.warp(DEFAULT_REFUND_TIMEOUT as i64);
+    let ix = game.env.result_ix(game.pool_id, force(1));
+    assert_eq!(game.env.svm.process(ix), Err(spinx_error(SpinXError::StillProcessing)));
+
+    game.env.svm.process(game.env.refund_ix(game.pool_id)).unwrap();
+    assert_eq!(game.env.token_balance(&game.creator, &game.env.mint), 100 * SOL);
+    assert_eq!(game.env.token_balance(&game.joiner, &game.env.mint), 100 * SOL);
+}
+
+#[test]
+fn closed_randomness_accounts_are_forfeited_and_unreadable_ones_never_refund() {
+    let mut game = committed_game();
+    join(&mut game).unwrap();
+    let random = game.random;
+    game.env.svm.warp(DEFAULT_REFUND_TIMEOUT as i64);
+
+    // Handed to another program, nothing can be read from it
+    let mut account = game.env.svm.account(&random).unwrap().clone();
+    account.owner = wallet();
+    game.env.svm.set_account(random, account);
+    let ix = game.env.refund_ix(game.pool_id);
+    assert_eq!(game.env.svm.process(ix), Err(spinx_error(SpinXError::InvalidRandomnessAccount)));
+
+    // Closed by the joiner
+    game.env.svm.set_account(random, Account::new(0, Vec::new(), anchor_lang::system_program::ID));
+    let ix = game.env.refund_ix(game.pool_id);
+    assert_eq!(game.env.svm.process(ix), Err(spinx_error(SpinXError::RandomnessForfeited)));
+
+    game.env.svm.process(game.env.result_ix(game.pool_id, force(1))).unwrap();
+    assert_eq!(game.env.events::<PoolSettled>()[0].winner, game.creator);
+}
+
+#[test]
+fn switching_back_to_orao_keeps_running_pools_on_switchboard() {
+    let mut game = committed_game();
+    join(&mut game).unwrap();
+
+    let admin = game.env.admin;
+    let ix = game.env.admin_ix(
+        &admin,
+        instruction::SetRandomnessProvider {
+            provider: RandomnessProvider::Orao,
+            switchboard_queue: Pubkey::default(),
+        },
+    );
+    game.env.svm.process(ix).unwrap();
+
+    let (random, queue) = (game.random, game.queue);
+    game.env.switchboard_randomness(&random, &queue, SEED_SLOT, SEED_SLOT + 1, 4);
+    game.env.svm.process(game.env.result_ix(game.pool_id, force(1))).unwrap();
+    assert_eq!(game.env.events::<PoolSettled>()[0].winner, game.creator);
+}
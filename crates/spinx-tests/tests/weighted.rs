@@ -0,0 +1,153 @@
+#![cfg(sbf_build)]
+
+use anchor_lang::prelude::Pubkey;
+use spinx_contract::account::StakeRange;
+use spinx_contract::error::SpinXError;
+use spinx_contract::event::*;
+use spinx_contract::utils::*;
+use spinx_tests::env::*;
+
+const JOINER_STAKE: u64 = 60 * SOL;
+
+fn weighted(min_amount: u64, max_amount: u64) -> PoolOptions {
+    PoolOptions {
+        joiner_stake: Some(StakeRange { min_amount, max_amount }),
+        ..Default::default()
+    }
+}
+
+/// A weighted pool where the creator staked STAKE against a JOINER_STAKE joiner,
+/// so the creator holds a quarter of the pot.
+fn joined_game() -> (Env, Pubkey, Pubkey, u64) {
+    let mut env = Env::new();
+    let creator = env.player(100 * SOL);
+    let joiner = env.player(100 * SOL);
+    let mint = env.mint;
+
+    let pool_id = env.next_pool_id();
+    env.svm.process(env.create_ix_with(&creator, &mint, 0, STAKE, weighted(10 * SOL, JOINER_STAKE))).unwrap();
+    env.join_pool(pool_id, &joiner, force(1), 1, JOINER_STAKE).unwrap();
+
+    (env, creator, joiner, pool_id)
+}
+
+#[test]
+fn create_records_the_joiner_range() {
+    let mut env = Env::new();
+    let creator = env.player(100 * SOL);
+    let mint = env.mint;
+
+    let pool_id = env.next_pool_id();
+    env.svm.process(env.create_ix_with(&creator, &mint, 0, STAKE, weighted(SOL, 50 * SOL))).unwrap();
+
+    let event = &env.events::<PoolCreated>()[0];
+    assert!(event.weighted);
+    assert_eq!((event.joiner_min_amount, event.joiner_max_amount), (SOL, 50 * SOL));
+
+    let pool = env.pool(pool_id).unwrap();
+    assert!(pool.weighted);
+    assert_eq!((pool.joiner_min_amount, pool.joiner_max_amount), (SOL, 50 * SOL));
+
+    let pool_id = env.create_pool(&creator, 0, STAKE).unwrap();
+    assert!(!env.events::<PoolCreated>()[0].weighted);
+    assert!(!env.pool(pool_id).unwrap().weighted);
+}
+
+#[test]
+fn create_rejects_empty_ranges() {
+    let mut env = Env::new();
+    let creator = env.player(100 * SOL);
+    let mint = env.mint;
+
+    let ix = env.create_ix_with(&creator, &mint, 0, STAKE, weighted(0, SOL));
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::InvalidAmount)));
+
+    let ix = env.create_ix_with(&creator, &mint, 0, STAKE, weighted(2 * SOL, SOL));
+    assert_eq!(env.svm.process(ix), Err(spinx_error(SpinXError::InvalidAmount)));
+}
+
+#[test]
+fn joiner_stake_must_fit_the_range_and_the_mint() {
+    let mut env = Env::new();
+    let creator = env.player(100 * SOL);
+    let joiner = env.player(100 * SOL);
+    let mint = env.mint;
+
+    let pool_id = env.next_pool_id();
+    env.svm.process(env.create_ix_with(&creator, &mint, 0, STAKE, weighted(SOL, 40 * SOL))).unwrap();
+
+    assert_eq!(env.join_pool(pool_id, &joiner, force(1), 1, 40 * SOL + 1), Err(spinx_error(SpinXError::InvalidAmount)));
+    assert_eq!(env.join_pool(pool_id, &joiner, force(1), 1, SOL - 1), Err(spinx_error(SpinXError::InvalidAmount)));
+    // In range, but below the 10 token minimum of the mint
+    assert_eq!(env.join_pool(pool_id, &joiner, force(1), 1, 5 * SOL), Err(spinx_error(SpinXError::AmountTooSmall)));
+
+    env.join_pool(pool_id, &joiner, force(1), 1, 15 * SOL).unwrap();
+    let pool = env.pool(pool_id).unwrap();
+    assert_eq!((pool.creator_amount, pool.joiner_amount, pool.pool_amount), (STAKE, 15 * SOL, STAKE + 15 * SOL));
+}
+
+#[test]
+fn creator_wins_below_their_share() {
+    let (mut env, creator, joiner, pool_id) = joined_game();
+    let mint = env.mint;
+
+    // A quarter of the pot, so everything below 2^62 goes to the creator
+    env.fulfill(force(1), (1 << 62) - 1);
+    env.svm.process(env.result_ix(pool_id, force(1))).unwrap();
+
+    let event = &env.events::<PoolSettled>()[0];
+    assert_eq!((event.winner, event.loser, event.result), (creator, joiner, 0));
+    assert_eq!(event.payout, STAKE + JOINER_STAKE);
+    assert_eq!(env.token_balance(&creator, &mint), 100 * SOL + JOINER_STAKE);
+    assert_eq!(env.token_balance(&joiner, &mint), 100 * SOL - JOINER_STAKE);
+}
+
+#[test]
+fn joiner_wins_from_their_share_up() {
+    let (mut env, creator, joiner, pool_id) = joined_game();
+    let mint = env.mint;
+
+    env.fulfill(force(1), 1 << 62);
+    env.svm.process(env.result_ix(pool_id, force(1))).unwrap();
+
+    let event = &env.events::<PoolSettled>()[0];
+    assert_eq!((event.winner, event.loser, event.result), (joiner, creator, 1));
+    assert_eq!(env.token_balance(&joiner, &mint), 100 * SOL + STAKE);
+    assert_eq!(env.token_balance(&creator, &mint), 100 * SOL - STAKE);
+}
+
+#[test]
+fn refund_returns_each_stake() {
+    let (mut env, creator, joiner, pool_id) = joined_game();
+    let mint = env.mint;
+
+    env.svm.warp(DEFAULT_REFUND_TIMEOUT as i64);
+    env.svm.process(env.refund_ix(pool_id)).unwrap();
+
+    let event = &env.events::<PoolClosed>()[0];
+    assert_eq!((event.creator_refund, event.joiner_refund), (STAKE, JOINER_STAKE));
+    assert_eq!(env.token_balance(&creator, &mint), 100 * SOL);
+    assert_eq!(env.token_balance(&joiner, &mint), 100 * SOL);
+}
+
+#[test]
+fn sol_pools_can_be_weighted() {
+    let mut env = Env::new();
+    env.enable_sol();
+    let creator = wallet();
+    let joiner = wallet();
+    env.svm.airdrop(&creator, 100 * SOL);
+    env.svm.airdrop(&joiner, 100 * SOL);
+
+    let pool_id = env.next_pool_id();
+    env.svm.process(env.create_sol_ix_with(&creator, 1, STAKE, weighted(10 * SOL, JOINER_STAKE))).unwrap();
+    assert_eq!(env.join_sol_pool(pool_id, &joiner, force(1), 0, JOINER_STAKE + 1), Err(spinx_error(SpinXError::InvalidAmount)));
+    env.join_sol_pool(pool_id, &joiner, force(1), 0, JOINER_STAKE).unwrap();
+
+    let joiner_lamports = env.svm.lamports(&joiner);
+    env.fulfill(force(1), u64::MAX);
+    env.svm.process(env.result_sol_ix(pool_id, force(1))).unwrap();
+
+    assert_eq!(env.events::<PoolSettled>()[0].winner, joiner);
+    assert_eq!(env.svm.lamports(&joiner), joiner_lamports + STAKE + JOINER_STAKE);
+}
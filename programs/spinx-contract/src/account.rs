@@ -1,17 +1,39 @@
 use anchor_lang::prelude::*;
 
-#[derive(Debug, AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+use crate::error::SpinXError;
+use crate::randomness;
+use crate::utils::*;
+
+#[derive(Debug, Default, AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
 pub enum PoolStatus {
+    #[default]
     Waiting,
     Processing,
     Finished,
-    Closed
+    Closed,
+    Refunded,
+    Expired
+}
+
+#[derive(Debug, Default, AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum RandomnessProvider {
+    #[default]
+    Orao,
+    Switchboard
+}
+
+#[derive(Debug, Default, AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum SeasonMetric {
+    #[default]
+    Volume, // stakes of settled games
+    NetWins // wins less losses
 }
 
-impl<> Default for PoolStatus {
-    fn default() -> Self {
-        return PoolStatus::Waiting
-    } 
+// Stakes a joiner may bring to a weighted pool, after transfer fees
+#[derive(Debug, AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct StakeRange {
+    pub min_amount: u64,
+    pub max_amount: u64,
 }
 
 // Default Account structures here
@@ -20,10 +42,63 @@ impl<> Default for PoolStatus {
 pub struct GlobalData {
     pub super_admin: Pubkey,  // 32
     pub treasury_wallet: Pubkey,  // 32
-    pub coinflip_fee: u64,
-    pub spinx_token: Pubkey,
+    pub coinflip_fee: u64, // default for newly added mints
+    pub spinx_token: Pubkey, // default mint of clients, and of pools created before the mint allowlist
     pub next_pool_id: u64,
-    pub min_amount: u64
+    pub min_amount: u64, // default for newly added mints
+    pub pending_admin: Pubkey, // 32, Pubkey::default() when no handover is in progress
+    pub pauser: Pubkey, // 32, may only add pause bits
+    pub pause_flags: u32, // 4, see PAUSE_* in utils.rs
+    pub rake_bps: u16, // 2, share of the pot kept by the treasury at settlement
+    pub refund_timeout: u64, // 8, seconds after join before an unsettled pool can be refunded, 0 for the default
+    pub max_pool_ttl: u64, // 8, longest lifetime of a waiting pool in seconds, 0 for no limit (set by the admin only)
+    pub randomness_provider: RandomnessProvider, // 1, provider requested by new joins
+    pub switchboard_queue: Pubkey, // 32, only randomness from this queue is accepted
+    pub invite_timeout: u64, // 8, seconds before an invite-only pool can be opened, 0 for the default
+    pub next_jackpot_id: u64, // 8
+    pub referral_share_bps: u16, // 2, share of each flat fee paid to the player's referrer
+    pub next_referral_id: u64, // 8, registration order of PlayerReferral accounts
+    pub last_season_id: u64, // 8, 0 before the first season
+}
+
+impl GlobalData {
+    // Offset of max_pool_ttl, accounts shorter than this predate it
+    pub const MAX_POOL_TTL_OFFSET: usize = 8 + 32 + 32 + 8 + 32 + 8 + 8 + 32 + 32 + 4 + 2 + 8;
+
+    pub fn is_paused(&self, flag: u32) -> bool {
+        self.pause_flags & (flag | PAUSE_ALL) != 0
+    }
+
+    pub fn refund_timeout(&self) -> u64 {
+        if self.refund_timeout == 0 {
+            DEFAULT_REFUND_TIMEOUT
+        } else {
+            self.refund_timeout
+        }
+    }
+
+    pub fn invite_timeout(&self) -> u64 {
+        if self.invite_timeout == 0 {
+            DEFAULT_INVITE_TIMEOUT
+        } else {
+            self.invite_timeout
+        }
+    }
+
+    // Expiry timestamp for a pool created at now with the creator's ttl, 0 never expires
+    pub fn pool_expiry(&self, now: u64, ttl: u64) -> Result<u64> {
+        let ttl = if self.max_pool_ttl == 0 {
+            if ttl == 0 {
+                return Ok(0);
+            }
+            ttl
+        } else {
+            require!(ttl <= self.max_pool_ttl, SpinXError::InvalidExpiry);
+            if ttl == 0 { self.max_pool_ttl } else { ttl }
+        };
+
+        Ok(now.checked_add(ttl).ok_or(SpinXError::InvalidExpiry)?)
+    }
 }
 
 #[account]
@@ -42,5 +117,671 @@ pub struct CoinflipPool {
     pub joiner_amount: u64, // 8
     pub joiner_set_number: u8, // 1
     pub force: [u8; 32],
-    pub status: PoolStatus
-}
\ No newline at end of file
+    pub status: PoolStatus,
+    pub mint: Pubkey, // 32, native mint for SOL pools
+    pub is_native: bool, // 1, stakes are lamports held in the sol escrow PDA
+    pub rake_bps: u16, // 2, GlobalData.rake_bps when the pool was created
+    pub join_ts: u64, // 8
+    pub expiry_ts: u64, // 8, 0 never expires
+    pub randomness_provider: RandomnessProvider, // 1, provider the randomness was requested from
+    pub random: Pubkey, // 32, randomness account, derived from force when unset
+    pub commit_slot: u64, // 8, Switchboard seed slot committed on join
+    pub allowed_joiner: Pubkey, // 32, only player who may join, Pubkey::default() for public pools
+    pub invite_expiry_ts: u64, // 8, when the creator may make an invite-only pool public
+    pub weighted: bool, // 1, odds follow the stakes instead of a matched 50/50 flip
+    pub joiner_min_amount: u64, // 8, weighted pools only
+    pub joiner_max_amount: u64, // 8, weighted pools only
+    pub stake_tier: u8, // 1, PoolRegistry shard the pool is listed in, see stake_tier in utils.rs
+}
+
+impl CoinflipPool {
+    // Checks shared by the SPL and SOL join paths
+    pub fn check_joinable(&self, joiner: Pubkey, set_number: u8, now: u64) -> Result<()> {
+        require!(self.status == PoolStatus::Waiting, SpinXError::InvalidPoolStatus);
+        require!(!self.is_expired(now), SpinXError::PoolExpired);
+        require!(self.winner == Pubkey::default(), SpinXError::AlreadyDrawn);
+        require!(self.joiner_player == Pubkey::default(), SpinXError::AlreadyJoined);
+        require!(self.creator_player != joiner, SpinXError::InvalidJoiner);
+        require!(self.allowed_joiner == Pubkey::default() || self.allowed_joiner == joiner, SpinXError::NotInvited);
+        require!(set_number < 2, SpinXError::InvalidNumber);
+        require!(self.creator_set_number != set_number, SpinXError::InvalidNumber);
+        Ok(())
+    }
+
+    // Reserves a new pool for allowed_joiner until the invite times out, None keeps it public
+    pub fn invite(&mut self, allowed_joiner: Option<Pubkey>, now: u64, invite_timeout: u64) -> Result<()> {
+        if let Some(joiner) = allowed_joiner {
+            require!(joiner != Pubkey::default() && joiner != self.creator_player, SpinXError::InvalidJoiner);
+
+            self.allowed_joiner = joiner;
+            self.invite_expiry_ts = now.saturating_add(invite_timeout);
+        }
+        Ok(())
+    }
+
+    // Weighted pools let the joiner bring any stake in the creator's range, None keeps the pool even
+    pub fn set_joiner_stake(&mut self, joiner_stake: Option<StakeRange>) -> Result<()> {
+        if let Some(range) = joiner_stake {
+            require!(range.min_amount > 0 && range.max_amount >= range.min_amount, SpinXError::InvalidAmount);
+
+            self.weighted = true;
+            self.joiner_min_amount = range.min_amount;
+            self.joiner_max_amount = range.max_amount;
+        }
+        Ok(())
+    }
+
+    // Even pools need a matching stake, weighted pools any stake in range that the mint allows
+    pub fn check_joiner_amount(&self, amount: u64, mint_config: &MintConfig) -> Result<()> {
+        if !self.weighted {
+            require!(self.creator_amount == amount, SpinXError::InvalidAmount);
+            return Ok(());
+        }
+
+        require!(amount >= self.joiner_min_amount && amount <= self.joiner_max_amount, SpinXError::InvalidAmount);
+        require!(amount >= mint_config.min_amount, SpinXError::AmountTooSmall);
+        require!(mint_config.max_amount == 0 || amount <= mint_config.max_amount, SpinXError::AmountTooLarge);
+        Ok(())
+    }
+
+    pub fn is_expired(&self, now: u64) -> bool {
+        self.expiry_ts != 0 && now >= self.expiry_ts
+    }
+
+    // Pools joined before join_ts was recorded fall back to start_ts
+    pub fn refund_deadline(&self, refund_timeout: u64) -> u64 {
+        let joined = if self.join_ts > 0 { self.join_ts } else { self.start_ts };
+        joined.saturating_add(refund_timeout)
+    }
+
+    // Pools joined before the randomness account was recorded used ORAO
+    pub fn random_account(&self) -> Pubkey {
+        if self.random != Pubkey::default() {
+            self.random
+        } else {
+            randomness::randomness_address(&self.force)
+        }
+    }
+
+    pub fn rake(&self) -> u64 {
+        (self.pool_amount as u128 * self.rake_bps as u128 / BPS_DENOMINATOR as u128) as u64
+    }
+
+    // Even pools: the coin side is the VRF value mod 2, whoever picked it wins.
+    // Weighted pools: the creator wins when randomness / 2^64 is below their share of the
+    // stakes. Both sides are compared as exact 128 bit integers, so there is no modulo bias.
+    pub fn winner_for(&self, randomness: u64) -> Pubkey {
+        let joiner_wins = if self.weighted {
+            let total = self.creator_amount as u128 + self.joiner_amount as u128;
+            randomness as u128 * total >= (self.creator_amount as u128) << 64
+        } else {
+            (randomness % 2) as u8 == self.joiner_set_number
+        };
+
+        if joiner_wins {
+            self.joiner_player
+        } else {
+            self.creator_player
+        }
+    }
+
+    // Side that won, the coin result for even pools
+    pub fn winning_number(&self) -> u8 {
+        if self.winner == self.joiner_player {
+            self.joiner_set_number
+        } else {
+            self.creator_set_number
+        }
+    }
+}
+
+// One per allowlisted mint, seeds = [MINT_CONFIG_SEED, mint]
+#[account]
+#[derive(Default)]
+pub struct MintConfig {
+    pub mint: Pubkey, // 32
+    pub enabled: bool, // 1
+    pub min_amount: u64, // 8
+    pub max_amount: u64, // 8, 0 means no limit
+    pub coinflip_fee: u64, // 8, flat SOL fee charged on create and join
+    pub bump: u8, // 1
+    pub approved_freeze_authority: Pubkey, // 32, freeze authority the admin accepted, default for none
+}
+
+// A player's total deposit in a jackpot round
+#[derive(Debug, Default, AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct JackpotEntry {
+    pub player: Pubkey, // 32
+    pub amount: u64, // 8, after transfer fees
+}
+
+// Multi-player round, seeds = [JACKPOT_SEED, round_id]. Deposits are taken until end_ts,
+// then one randomness request picks the winner weighted by deposit.
+#[account]
+#[derive(Default)]
+pub struct JackpotRound {
+    pub round_id: u64, // 8
+    pub creator: Pubkey, // 32, paid the rent and gets it back when the round closes
+    pub mint: Pubkey, // 32
+    pub start_ts: u64, // 8
+    pub end_ts: u64, // 8, last moment deposits are taken
+    pub status: PoolStatus, // 1, Waiting while taking deposits, Processing once drawn
+    pub total_amount: u64, // 8, sum of the entries still held in escrow
+    pub rake_bps: u16, // 2, GlobalData.rake_bps when the round was created
+    pub force: [u8; 32],
+    pub randomness_provider: RandomnessProvider, // 1
+    pub random: Pubkey, // 32
+    pub commit_slot: u64, // 8
+    pub draw_ts: u64, // 8
+    pub drawer: Pubkey, // 32, paid for the draw
+    pub winner: Pubkey, // 32
+    pub bump: u8, // 1
+    pub entries: Vec<JackpotEntry>, // grows by one entry per new player, at most MAX_JACKPOT_ENTRIES
+}
+
+impl JackpotRound {
+    // Account size holding the given number of entries
+    pub fn space(entries: usize) -> usize {
+        8 + std::mem::size_of::<JackpotRound>() + entries * std::mem::size_of::<JackpotEntry>()
+    }
+
+    // Number of entries once player has deposited, repeat deposits add to their entry
+    pub fn entries_with(&self, player: &Pubkey) -> usize {
+        if self.entries.iter().any(|entry| entry.player == *player) {
+            self.entries.len()
+        } else {
+            self.entries.len() + 1
+        }
+    }
+
+    pub fn deposit(&mut self, player: Pubkey, amount: u64) -> Result<u64> {
+        let total = match self.entries.iter_mut().find(|entry| entry.player == player) {
+            Some(entry) => {
+                entry.amount += amount;
+                entry.amount
+            }
+            None => {
+                require!(self.entries.len() < MAX_JACKPOT_ENTRIES, SpinXError::JackpotFull);
+                self.entries.push(JackpotEntry { player, amount });
+                amount
+            }
+        };
+
+        self.total_amount += amount;
+        Ok(total)
+    }
+
+    pub fn random_account(&self) -> Pubkey {
+        if self.random != Pubkey::default() {
+            self.random
+        } else {
+            randomness::randomness_address(&self.force)
+        }
+    }
+
+    pub fn rake(&self) -> u64 {
+        (self.total_amount as u128 * self.rake_bps as u128 / BPS_DENOMINATOR as u128) as u64
+    }
+
+    // Maps randomness / 2^64 onto the deposits laid end to end, exact in 128 bit integers.
+    // Each player wins with their share of the total.
+    pub fn winner_for(&self, randomness: u64) -> JackpotEntry {
+        let target = ((randomness as u128 * self.total_amount as u128) >> 64) as u64;
+        let mut end = 0;
+
+        for entry in &self.entries {
+            end += entry.amount;
+            if target < end {
+                return *entry;
+            }
+        }
+
+        // target is always below total_amount
+        self.entries[self.entries.len() - 1]
+    }
+
+    pub fn refund_deadline(&self, refund_timeout: u64) -> u64 {
+        self.draw_ts.saturating_add(refund_timeout)
+    }
+
+    // Rounds nobody can win are refundable as soon as deposits close, drawn rounds once
+    // randomness has been pending for refund_timeout
+    pub fn is_refundable(&self, now: u64, refund_timeout: u64) -> bool {
+        match self.status {
+            PoolStatus::Waiting => now >= self.end_ts && self.entries.len() < MIN_JACKPOT_PLAYERS,
+            PoolStatus::Processing => now >= self.refund_deadline(refund_timeout),
+            PoolStatus::Refunded => true,
+            _ => false,
+        }
+    }
+}
+
+// Bankroll that takes the other side of flip_vs_house, one per mint,
+// seeds = [HOUSE_VAULT_SEED, mint]. Funds sit in the vault's associated token account
+// and belong to the holders of share_mint.
+#[account]
+#[derive(Default)]
+pub struct HouseVault {
+    pub mint: Pubkey, // 32
+    pub edge_bps: u16, // 2, kept from the doubled stake when the player wins
+    pub max_bet_bps: u16, // 2, largest stake as a share of the free balance
+    pub locked_amount: u64, // 8, what pending flips are paid if they all win
+    pub next_flip_id: u64, // 8
+    pub bump: u8, // 1
+    pub share_mint: Pubkey, // 32, LP shares, seeds = [HOUSE_SHARES_SEED, house_vault]
+    pub withdrawal_delay: u64, // 8, seconds between requesting a withdrawal and completing it
+}
+
+impl HouseVault {
+    pub fn check_config(edge_bps: u16, max_bet_bps: u16, withdrawal_delay: u64) -> Result<()> {
+        require!(edge_bps <= MAX_HOUSE_EDGE_BPS, SpinXError::InvalidHouseConfig);
+        require!(max_bet_bps > 0 && max_bet_bps <= MAX_HOUSE_BET_BPS, SpinXError::InvalidHouseConfig);
+        require!(withdrawal_delay <= MAX_HOUSE_WITHDRAWAL_DELAY, SpinXError::InvalidHouseConfig);
+        Ok(())
+    }
+
+    // Balance not reserved for pending flips
+    pub fn free_amount(&self, balance: u64) -> u64 {
+        balance.saturating_sub(self.locked_amount)
+    }
+
+    // Largest stake the vault takes on with the given balance
+    pub fn max_bet(&self, balance: u64) -> u64 {
+        (self.free_amount(balance) as u128 * self.max_bet_bps as u128 / BPS_DENOMINATOR as u128) as u64
+    }
+
+    // Deposits are priced as if every pending flip loses and withdrawals as if every one wins,
+    // so an LP gains nothing from knowing an outcome before it is settled. The virtual
+    // liquidity starts shares at one per token and makes donations useless for skewing the price.
+    pub fn shares_for_deposit(&self, amount: u64, balance: u64, share_supply: u64) -> u64 {
+        (amount as u128 * (share_supply as u128 + HOUSE_VIRTUAL_LIQUIDITY as u128)
+            / (balance as u128 + HOUSE_VIRTUAL_LIQUIDITY as u128)) as u64
+    }
+
+    pub fn amount_for_shares(&self, shares: u64, balance: u64, share_supply: u64) -> u64 {
+        (shares as u128 * (self.free_amount(balance) as u128 + HOUSE_VIRTUAL_LIQUIDITY as u128)
+            / (share_supply as u128 + HOUSE_VIRTUAL_LIQUIDITY as u128)) as u64
+    }
+
+    // Doubled stake less the edge, what a winning player is paid
+    pub fn payout_for(&self, amount: u64) -> u64 {
+        (2 * amount as u128 * (BPS_DENOMINATOR - self.edge_bps as u64) as u128 / BPS_DENOMINATOR as u128) as u64
+    }
+}
+
+// A pending flip against the house, seeds = [HOUSE_FLIP_SEED, house_vault, flip_id].
+// Closed to the player once settled or refunded.
+#[account]
+#[derive(Default)]
+pub struct HouseFlip {
+    pub flip_id: u64, // 8
+    pub house_vault: Pubkey, // 32
+    pub player: Pubkey, // 32
+    pub mint: Pubkey, // 32
+    pub amount: u64, // 8, the player's stake after transfer fees
+    pub set_number: u8, // 1
+    pub payout: u64, // 8, paid if the player wins, locked in the vault until then
+    pub force: [u8; 32],
+    pub randomness_provider: RandomnessProvider, // 1
+    pub random: Pubkey, // 32
+    pub commit_slot: u64, // 8
+    pub flip_ts: u64, // 8
+    pub bump: u8, // 1
+}
+
+impl HouseFlip {
+    pub fn random_account(&self) -> Pubkey {
+        if self.random != Pubkey::default() {
+            self.random
+        } else {
+            randomness::randomness_address(&self.force)
+        }
+    }
+
+    pub fn refund_deadline(&self, refund_timeout: u64) -> u64 {
+        self.flip_ts.saturating_add(refund_timeout)
+    }
+
+    // Same coin as even pools, the player wins when the VRF value mod 2 is their side
+    pub fn player_wins(&self, randomness: u64) -> bool {
+        (randomness % 2) as u8 == self.set_number
+    }
+}
+
+// An LP's pending withdrawal, seeds = [HOUSE_WITHDRAWAL_SEED, house_vault, owner].
+// The vault holds the shares until then, and prices them when the withdrawal completes,
+// not when it is requested.
+#[account]
+#[derive(Default)]
+pub struct HouseWithdrawal {
+    pub house_vault: Pubkey, // 32
+    pub owner: Pubkey, // 32
+    pub shares: u64, // 8
+    pub ready_ts: u64, // 8, earliest completion
+    pub bump: u8, // 1
+    pub expiry_ts: u64, // 8, anyone can drop the request afterwards
+}
+
+// A player's referral record, seeds = [PLAYER_REFERRAL_SEED, player].
+// Holds who referred the player and, as lamports on top of rent, the fee shares the player
+// earned as a referrer. Referrers must have registered earlier, so referrals can never loop.
+#[account]
+#[derive(Default)]
+pub struct PlayerReferral {
+    pub player: Pubkey, // 32
+    pub referral_id: u64, // 8, registration order
+    pub referrer: Pubkey, // 32, Pubkey::default() until bound
+    pub referred_players: u64, // 8
+    pub pending_rewards: u64, // 8, lamports claimable by the player
+    pub total_rewards: u64, // 8, lifetime lamports earned
+    pub bump: u8, // 1
+}
+
+impl PlayerReferral {
+    pub fn register(&mut self, global_data: &mut GlobalData, player: Pubkey, bump: u8) {
+        self.player = player;
+        self.referral_id = global_data.next_referral_id;
+        self.bump = bump;
+        global_data.next_referral_id += 1;
+    }
+
+    pub fn is_registered(&self) -> bool {
+        self.player != Pubkey::default()
+    }
+
+    // Fee instructions take the record as an unchecked PDA, so a referred player can't skip
+    // their referrer by leaving it out. Players who never registered have no referrer.
+    pub fn referrer_of(player_referral: &AccountInfo) -> Result<Pubkey> {
+        if player_referral.owner != &crate::ID {
+            return Ok(Pubkey::default());
+        }
+
+        let data = player_referral.try_borrow_data()?;
+        Ok(PlayerReferral::try_deserialize(&mut &data[..])?.referrer)
+    }
+}
+
+// A player's lifetime coinflip results in one mint, seeds = [PLAYER_PROFILE_SEED, player, mint].
+// Amounts in different mints don't add up, so every mint gets its own profile. The player's
+// first create or join in the mint pays for it, settlement updates it.
+#[account]
+#[derive(Default)]
+pub struct PlayerProfile {
+    pub player: Pubkey, // 32
+    pub mint: Pubkey, // 32, native mint for SOL pools
+    pub games_played: u64, // 8, settled games only
+    pub wins: u64, // 8
+    pub losses: u64, // 8
+    pub total_wagered: u64, // 8, stakes of settled games after transfer fees
+    pub net_pnl: i64, // 8, payouts less stakes, flat SOL fees not included
+    pub current_streak: u32, // 4, wins in a row
+    pub best_streak: u32, // 4
+    pub last_game_ts: u64, // 8
+    pub bump: u8, // 1
+    pub season_id: u64, // 8, season the score belongs to
+    pub season_score: i64, // 8, restarts at 0 when a new season scores the player
+}
+
+impl PlayerProfile {
+    // Fills in a profile just created by init_if_needed, existing ones are left alone
+    pub fn open(&mut self, player: Pubkey, mint: Pubkey, bump: u8) {
+        if self.player == Pubkey::default() {
+            self.player = player;
+            self.mint = mint;
+            self.bump = bump;
+        }
+    }
+
+    // Records a settled game, payout is 0 for a loss. Saturates rather than overflows,
+    // bookkeeping must never block a payout.
+    pub fn record(&mut self, stake: u64, payout: u64, now: u64) {
+        self.games_played = self.games_played.saturating_add(1);
+        self.total_wagered = self.total_wagered.saturating_add(stake);
+        let net_pnl = self.net_pnl as i128 + payout as i128 - stake as i128;
+        self.net_pnl = net_pnl.clamp(i64::MIN as i128, i64::MAX as i128) as i64;
+        self.last_game_ts = now;
+
+        if payout > 0 {
+            self.wins = self.wins.saturating_add(1);
+            self.current_streak = self.current_streak.saturating_add(1);
+            self.best_streak = self.best_streak.max(self.current_streak);
+        } else {
+            self.losses = self.losses.saturating_add(1);
+            self.current_streak = 0;
+        }
+    }
+
+    // Settlement takes profiles as unchecked PDAs, since players of pools created before
+    // profiles existed may not have one. Those games are simply not recorded, not even
+    // for a running season.
+    pub fn record_on(profile: &AccountInfo, stake: u64, payout: u64, now: u64, season: Option<&mut Season>) -> Result<()> {
+        if profile.owner != &crate::ID {
+            return Ok(());
+        }
+
+        let mut data = profile.try_borrow_mut_data()?;
+        let mut player_profile = PlayerProfile::try_deserialize(&mut &data[..])?;
+        player_profile.record(stake, payout, now);
+
+        if let Some(season) = season {
+            season.record(&mut player_profile, stake, payout > 0);
+        }
+
+        let mut writer: &mut [u8] = &mut data[..];
+        player_profile.try_serialize(&mut writer)
+    }
+}
+
+// A ranked player of a season
+#[derive(Debug, Default, AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct SeasonEntry {
+    pub player: Pubkey, // 32
+    pub score: i64, // 8
+    pub claimed: bool, // 1
+}
+
+// A competition over the coinflip pools of one mint, seeds = [SEASON_SEED, mint], one at a time.
+// While live, settlement pays prize_share_bps of the rake into the season's associated token
+// account and scores both players on their profiles. Only the best MAX_SEASON_RANKS are ranked,
+// a player who dropped out gets back in by playing again with a score above the last rank.
+#[account]
+#[derive(Default)]
+pub struct Season {
+    pub season_id: u64, // 8
+    pub mint: Pubkey, // 32
+    pub start_ts: u64, // 8
+    pub end_ts: u64, // 8, games settled from here on no longer count
+    pub metric: SeasonMetric, // 1
+    pub prize_share_bps: u16, // 2, share of the rake paid into the prize vault
+    pub finalized: bool, // 1, rankings are locked
+    pub prize_amount: u64, // 8, vault balance when finalized, split by payout_bps
+    pub bump: u8, // 1
+    pub payout_bps: Vec<u16>, // share of the prize per rank, best first, adds up to BPS_DENOMINATOR
+    pub rankings: Vec<SeasonEntry>, // best first, at most MAX_SEASON_RANKS
+}
+
+impl Season {
+    // Fixed size, the rankings never outgrow MAX_SEASON_RANKS
+    pub fn space() -> usize {
+        8 + std::mem::size_of::<Season>()
+            + MAX_SEASON_RANKS * (std::mem::size_of::<u16>() + std::mem::size_of::<SeasonEntry>())
+    }
+
+    pub fn check_config(start_ts: u64, end_ts: u64, prize_share_bps: u16, payout_bps: &[u16]) -> Result<()> {
+        require!(end_ts > start_ts, SpinXError::InvalidSeasonConfig);
+        require!(prize_share_bps <= MAX_SEASON_PRIZE_SHARE_BPS, SpinXError::InvalidSeasonConfig);
+        require!(!payout_bps.is_empty() && payout_bps.len() <= MAX_SEASON_RANKS, SpinXError::InvalidSeasonConfig);
+        require!(
+            payout_bps.iter().map(|bps| *bps as u64).sum::<u64>() == BPS_DENOMINATOR,
+            SpinXError::InvalidSeasonConfig
+        );
+        Ok(())
+    }
+
+    pub fn is_live(&self, now: u64) -> bool {
+        !self.finalized && self.start_ts <= now && now < self.end_ts
+    }
+
+    // Part of a pool's rake that goes to the prize vault
+    pub fn prize_for(&self, rake: u64) -> u64 {
+        (rake as u128 * self.prize_share_bps as u128 / BPS_DENOMINATOR as u128) as u64
+    }
+
+    // Prize of the player ranked at rank, ranks without a payout share get nothing
+    pub fn prize_of(&self, rank: usize) -> u64 {
+        let bps = self.payout_bps.get(rank).copied().unwrap_or(0);
+        (self.prize_amount as u128 * bps as u128 / BPS_DENOMINATOR as u128) as u64
+    }
+
+    // Scores a settled game on the player's profile and moves them in the rankings
+    pub fn record(&mut self, profile: &mut PlayerProfile, stake: u64, won: bool) {
+        if profile.season_id != self.season_id {
+            profile.season_id = self.season_id;
+            profile.season_score = 0;
+        }
+
+        // Saturates like the profile's own counters
+        profile.season_score = profile.season_score.saturating_add(match self.metric {
+            SeasonMetric::Volume => stake.min(i64::MAX as u64) as i64,
+            SeasonMetric::NetWins => if won { 1 } else { -1 },
+        });
+
+        self.rank(profile.player, profile.season_score);
+    }
+
+    fn rank(&mut self, player: Pubkey, score: i64) {
+        let entry = SeasonEntry { player, score, claimed: false };
+
+        if let Some(ranked) = self.rankings.iter_mut().find(|ranked| ranked.player == player) {
+            ranked.score = score;
+        } else if score <= 0 {
+            return;
+        } else if self.rankings.len() < MAX_SEASON_RANKS {
+            self.rankings.push(entry);
+        } else if score > self.rankings[MAX_SEASON_RANKS - 1].score {
+            self.rankings[MAX_SEASON_RANKS - 1] = entry;
+        } else {
+            return;
+        }
+
+        // Stable, so ties go to whoever reached the score first
+        self.rankings.sort_by_key(|entry| std::cmp::Reverse(entry.score));
+    }
+
+    // Locks the rankings, players without a positive score win nothing
+    pub fn finalize(&mut self, prize_amount: u64) {
+        self.rankings.retain(|entry| entry.score > 0);
+        self.prize_amount = prize_amount;
+        self.finalized = true;
+    }
+
+    // Every prize that pays out has been claimed
+    pub fn is_settled(&self) -> bool {
+        self.rankings
+            .iter()
+            .enumerate()
+            .all(|(rank, entry)| entry.claimed || self.prize_of(rank) == 0)
+    }
+
+    // Settlement takes the season as an unchecked PDA, most of the time none is running
+    pub fn load_live(season: &AccountInfo, now: u64) -> Result<Option<Season>> {
+        if season.owner != &crate::ID {
+            return Ok(None);
+        }
+
+        let season = Season::try_deserialize(&mut &season.try_borrow_data()?[..])?;
+        Ok(if season.is_live(now) { Some(season) } else { None })
+    }
+
+    pub fn store(&self, season: &AccountInfo) -> Result<()> {
+        let mut data = season.try_borrow_mut_data()?;
+        let mut writer: &mut [u8] = &mut data[..];
+        self.try_serialize(&mut writer)
+    }
+}
+
+// A waiting pool as the lobby lists it
+#[derive(Debug, Default, AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct RegistryEntry {
+    pub pool_id: u64, // 8
+    pub amount: u64, // 8, creator's stake after transfer fees
+    pub expiry_ts: u64, // 8, 0 never expires
+    pub set_number: u8, // 1
+    pub weighted: bool, // 1
+}
+
+// The public waiting pools of one mint and stake tier, seeds = [POOL_REGISTRY_SEED, mint, stake_tier],
+// so clients can read the lobby without scanning program accounts. Creating or opening a pool lists
+// it, joining, closing or expiring it takes it off. The first pool of a shard pays its rent.
+// Only pools that expire are listed and expired ones make room for new pools, so nobody can fill
+// a shard for good. Once a shard is full of live pools, new ones are left unlisted rather than failing.
+#[account]
+#[derive(Default)]
+pub struct PoolRegistry {
+    pub mint: Pubkey, // 32, native mint for SOL pools
+    pub stake_tier: u8, // 1
+    pub bump: u8, // 1
+    pub pools: Vec<RegistryEntry>, // oldest first, at most MAX_REGISTRY_POOLS
+}
+
+impl PoolRegistry {
+    // Fixed size, the list never outgrows MAX_REGISTRY_POOLS
+    pub fn space() -> usize {
+        8 + std::mem::size_of::<PoolRegistry>() + MAX_REGISTRY_POOLS * std::mem::size_of::<RegistryEntry>()
+    }
+
+    // Fills in a registry just created by init_if_needed, existing ones are left alone
+    pub fn open(&mut self, mint: Pubkey, stake_tier: u8, bump: u8) {
+        if self.mint == Pubkey::default() {
+            self.mint = mint;
+            self.stake_tier = stake_tier;
+            self.bump = bump;
+        }
+    }
+
+    // Lists a public pool, invite-only pools wait until they are opened
+    pub fn list(&mut self, pool: &CoinflipPool, now: u64) -> bool {
+        if pool.allowed_joiner != Pubkey::default() || pool.expiry_ts == 0 {
+            return false;
+        }
+
+        // Expired pools can't be joined anymore, they stay in their escrow until cranked
+        self.pools.retain(|entry| entry.expiry_ts != 0 && now < entry.expiry_ts);
+        if self.pools.len() >= MAX_REGISTRY_POOLS {
+            return false;
+        }
+
+        self.pools.push(RegistryEntry {
+            pool_id: pool.pool_id,
+            amount: pool.creator_amount,
+            expiry_ts: pool.expiry_ts,
+            set_number: pool.creator_set_number,
+            weighted: pool.weighted,
+        });
+        true
+    }
+
+    pub fn unlist(&mut self, pool_id: u64) -> bool {
+        let len = self.pools.len();
+        self.pools.retain(|entry| entry.pool_id != pool_id);
+        self.pools.len() != len
+    }
+
+    // Pools leave the lobby through an unchecked PDA, since pools created before the registry
+    // existed, while their shard was full or without an expiry were never listed, and their shard
+    // may not exist.
+    pub fn unlist_from(registry: &AccountInfo, pool_id: u64) -> Result<()> {
+        if registry.owner != &crate::ID {
+            return Ok(());
+        }
+
+        let mut data = registry.try_borrow_mut_data()?;
+        let mut pool_registry = PoolRegistry::try_deserialize(&mut &data[..])?;
+        if !pool_registry.unlist(pool_id) {
+            return Ok(());
+        }
+
+        let mut writer: &mut [u8] = &mut data[..];
+        pool_registry.try_serialize(&mut writer)
+    }
+}
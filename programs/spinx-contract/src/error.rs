@@ -83,5 +83,128 @@ pub enum SpinXError {
     InsufficientFunds,
 
     #[msg("Randomness is still being fulfilled")]
-    StillProcessing
-}
\ No newline at end of file
+    StillProcessing,
+
+    #[msg("No admin handover is pending")]
+    NoPendingAdmin,
+
+    #[msg("Invalid account data")]
+    InvalidAccountData,
+
+    #[msg("Instruction is paused")]
+    InstructionPaused,
+
+    #[msg("Not the admin or pauser")]
+    InvalidPauser,
+
+    #[msg("Amount is too large")]
+    AmountTooLarge,
+
+    #[msg("Mint has an unsupported token extension")]
+    UnsafeMintExtension,
+
+    #[msg("Wrong instruction for this pool's asset")]
+    InvalidPoolAsset,
+
+    #[msg("Rake is above the maximum")]
+    InvalidRake,
+
+    #[msg("Refund timeout has not passed yet")]
+    RefundNotAvailable,
+
+    #[msg("Randomness is fulfilled, settle the pool instead")]
+    RandomnessFulfilled,
+
+    #[msg("Pool has expired")]
+    PoolExpired,
+
+    #[msg("Pool has not expired yet")]
+    PoolNotExpired,
+
+    #[msg("Expiry is above the maximum pool lifetime")]
+    InvalidExpiry,
+
+    #[msg("Randomness account does not belong to the randomness provider")]
+    InvalidRandomnessAccount,
+
+    #[msg("Switchboard needs a queue")]
+    InvalidRandomnessProvider,
+
+    #[msg("Pool is reserved for another player")]
+    NotInvited,
+
+    #[msg("Invite has not timed out yet")]
+    InviteNotExpired,
+
+    #[msg("Jackpot round has no room for another player")]
+    JackpotFull,
+
+    #[msg("Jackpot round no longer takes deposits")]
+    JackpotClosed,
+
+    #[msg("Jackpot round is still taking deposits")]
+    JackpotNotEnded,
+
+    #[msg("Jackpot round needs at least two players")]
+    NotEnoughPlayers,
+
+    #[msg("Token account does not belong to a player with a deposit to refund")]
+    NotInJackpot,
+
+    #[msg("House edge, max bet or withdrawal delay is out of range")]
+    InvalidHouseConfig,
+
+    #[msg("Withdrawal delay has not passed yet")]
+    WithdrawalNotReady,
+
+    #[msg("Referral share is out of range")]
+    InvalidReferralShare,
+
+    #[msg("Players cannot refer themselves")]
+    SelfReferral,
+
+    #[msg("Referrer is already bound")]
+    ReferrerAlreadyBound,
+
+    #[msg("Referrer must have registered before the player")]
+    ReferralCycle,
+
+    #[msg("Referrer account does not match the player's referrer")]
+    InvalidReferrer,
+
+    #[msg("No referral rewards to claim")]
+    NoReferralRewards,
+
+    #[msg("Season timestamps, prize share or payouts are out of range")]
+    InvalidSeasonConfig,
+
+    #[msg("Season has not ended yet")]
+    SeasonNotEnded,
+
+    #[msg("Season is already finalized")]
+    SeasonFinalized,
+
+    #[msg("Season is not finalized yet")]
+    SeasonNotFinalized,
+
+    #[msg("No season prize to claim")]
+    NoSeasonPrize,
+
+    #[msg("Season prizes can still be claimed")]
+    SeasonClaimsOpen,
+
+    #[msg("Treasury wallet can't be the default address")]
+    InvalidTreasury,
+
+    #[msg("Mint has a freeze authority the admin didn't approve")]
+    FreezeAuthorityNotApproved,
+
+    #[msg("Randomness was forfeited by its committer, settle instead")]
+    RandomnessForfeited,
+
+    #[msg("Withdrawal was not completed in time")]
+    WithdrawalExpired,
+
+    #[msg("Withdrawal can still be completed")]
+    WithdrawalNotExpired
+}
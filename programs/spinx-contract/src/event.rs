@@ -0,0 +1,450 @@
+use anchor_lang::prelude::*;
+
+use crate::account::{PoolStatus, RandomnessProvider, SeasonMetric};
+
+// All events are emitted with emit_cpi! so indexers can decode them from the IDL
+// without relying on program logs.
+
+// Pool lifecycle events
+
+#[event]
+pub struct PoolCreated {
+    pub pool_id: u64,
+    pub pool: Pubkey,
+    pub creator: Pubkey,
+    pub mint: Pubkey,
+    pub is_native: bool,
+    pub amount: u64,
+    pub set_number: u8,
+    pub fee: u64,
+    pub rake_bps: u16,
+    pub expiry_ts: u64,
+    pub allowed_joiner: Pubkey,
+    pub weighted: bool,
+    pub joiner_min_amount: u64,
+    pub joiner_max_amount: u64,
+}
+
+// An invite-only pool that timed out was made public by its creator
+#[event]
+pub struct PoolOpened {
+    pub pool_id: u64,
+    pub pool: Pubkey,
+    pub creator: Pubkey,
+    pub allowed_joiner: Pubkey,
+}
+
+#[event]
+pub struct PoolJoined {
+    pub pool_id: u64,
+    pub pool: Pubkey,
+    pub joiner: Pubkey,
+    pub amount: u64,
+    pub set_number: u8,
+    pub fee: u64,
+    pub pool_amount: u64,
+}
+
+#[event]
+pub struct RandomnessRequested {
+    pub pool_id: u64,
+    pub pool: Pubkey,
+    pub force: [u8; 32],
+    pub random: Pubkey,
+    pub provider: RandomnessProvider,
+}
+
+#[event]
+pub struct PoolSettled {
+    pub pool_id: u64,
+    pub pool: Pubkey,
+    pub randomness: u64,
+    pub result: u8,
+    pub winner: Pubkey,
+    pub loser: Pubkey,
+    pub pool_amount: u64,
+    pub payout: u64,
+    pub rake: u64,
+}
+
+// Emitted when a pool ends without a winner, status is Closed, Refunded or Expired
+#[event]
+pub struct PoolClosed {
+    pub pool_id: u64,
+    pub pool: Pubkey,
+    pub status: PoolStatus,
+    pub creator_refund: u64,
+    pub joiner_refund: u64,
+}
+
+// Jackpot round events
+
+#[event]
+pub struct JackpotCreated {
+    pub round_id: u64,
+    pub round: Pubkey,
+    pub creator: Pubkey,
+    pub mint: Pubkey,
+    pub rake_bps: u16,
+    pub end_ts: u64,
+}
+
+#[event]
+pub struct JackpotEntered {
+    pub round_id: u64,
+    pub round: Pubkey,
+    pub player: Pubkey,
+    pub amount: u64,
+    pub fee: u64,
+    pub player_amount: u64, // the player's deposits in this round so far
+    pub total_amount: u64,
+    pub players: u16,
+}
+
+#[event]
+pub struct JackpotDrawn {
+    pub round_id: u64,
+    pub round: Pubkey,
+    pub force: [u8; 32],
+    pub random: Pubkey,
+    pub provider: RandomnessProvider,
+    pub total_amount: u64,
+    pub players: u16,
+}
+
+#[event]
+pub struct JackpotSettled {
+    pub round_id: u64,
+    pub round: Pubkey,
+    pub randomness: u64,
+    pub winner: Pubkey,
+    pub winner_amount: u64, // what the winner deposited
+    pub total_amount: u64,
+    pub payout: u64,
+    pub rake: u64,
+}
+
+// One per refunded deposit, the round is closed once remaining_amount is 0
+#[event]
+pub struct JackpotRefunded {
+    pub round_id: u64,
+    pub round: Pubkey,
+    pub player: Pubkey,
+    pub amount: u64,
+    pub remaining_amount: u64,
+}
+
+// House vault events
+
+#[event]
+pub struct HouseFlipCreated {
+    pub flip_id: u64,
+    pub flip: Pubkey,
+    pub mint: Pubkey,
+    pub player: Pubkey,
+    pub amount: u64,
+    pub set_number: u8,
+    pub fee: u64,
+    pub payout: u64,
+    pub force: [u8; 32],
+    pub random: Pubkey,
+    pub provider: RandomnessProvider,
+}
+
+#[event]
+pub struct HouseFlipSettled {
+    pub flip_id: u64,
+    pub flip: Pubkey,
+    pub mint: Pubkey,
+    pub player: Pubkey,
+    pub randomness: u64,
+    pub result: u8,
+    pub player_won: bool,
+    pub amount: u64,
+    pub payout: u64, // 0 when the house won
+}
+
+#[event]
+pub struct HouseFlipRefunded {
+    pub flip_id: u64,
+    pub flip: Pubkey,
+    pub mint: Pubkey,
+    pub player: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct HouseVaultCreated {
+    pub admin: Pubkey,
+    pub mint: Pubkey,
+    pub house_vault: Pubkey,
+    pub share_mint: Pubkey,
+    pub edge_bps: u16,
+    pub max_bet_bps: u16,
+    pub withdrawal_delay: u64,
+}
+
+#[event]
+pub struct HouseVaultUpdated {
+    pub admin: Pubkey,
+    pub mint: Pubkey,
+    pub old_edge_bps: u16,
+    pub new_edge_bps: u16,
+    pub old_max_bet_bps: u16,
+    pub new_max_bet_bps: u16,
+    pub old_withdrawal_delay: u64,
+    pub new_withdrawal_delay: u64,
+}
+
+// balance is what the vault holds afterwards, including stakes of pending flips
+#[event]
+pub struct HouseLiquidityDeposited {
+    pub mint: Pubkey,
+    pub provider: Pubkey,
+    pub amount: u64,
+    pub shares: u64,
+    pub balance: u64,
+    pub locked_amount: u64,
+    pub share_supply: u64,
+}
+
+#[event]
+pub struct HouseWithdrawalRequested {
+    pub mint: Pubkey,
+    pub provider: Pubkey,
+    pub shares: u64,
+    pub ready_ts: u64,
+    pub expiry_ts: u64,
+}
+
+#[event]
+pub struct HouseWithdrawalCancelled {
+    pub mint: Pubkey,
+    pub provider: Pubkey,
+    pub shares: u64,
+}
+
+#[event]
+pub struct HouseLiquidityWithdrawn {
+    pub mint: Pubkey,
+    pub provider: Pubkey,
+    pub amount: u64,
+    pub shares: u64,
+    pub balance: u64,
+    pub locked_amount: u64,
+    pub share_supply: u64,
+}
+
+#[event]
+pub struct ReferralRegistered {
+    pub player: Pubkey,
+    pub referral_id: u64,
+}
+
+#[event]
+pub struct ReferrerBound {
+    pub player: Pubkey,
+    pub referrer: Pubkey,
+}
+
+// Referrer's share of a flat fee, accrued to their PlayerReferral
+#[event]
+pub struct ReferralFeePaid {
+    pub player: Pubkey,
+    pub referrer: Pubkey,
+    pub fee: u64,
+    pub amount: u64,
+}
+
+#[event]
+pub struct ReferralRewardsClaimed {
+    pub referrer: Pubkey,
+    pub amount: u64,
+}
+
+// Season events
+
+#[event]
+pub struct SeasonCreated {
+    pub season_id: u64,
+    pub season: Pubkey,
+    pub mint: Pubkey,
+    pub start_ts: u64,
+    pub end_ts: u64,
+    pub metric: SeasonMetric,
+    pub prize_share_bps: u16,
+    pub payout_bps: Vec<u16>,
+}
+
+// Rake of a settled pool paid into the prize vault
+#[event]
+pub struct SeasonPrizeFunded {
+    pub season_id: u64,
+    pub pool_id: u64,
+    pub amount: u64,
+}
+
+#[event]
+pub struct SeasonFinalized {
+    pub season_id: u64,
+    pub season: Pubkey,
+    pub prize_amount: u64,
+    pub winners: Vec<Pubkey>,
+}
+
+#[event]
+pub struct SeasonPrizeClaimed {
+    pub season_id: u64,
+    pub player: Pubkey,
+    pub rank: u8,
+    pub amount: u64,
+}
+
+// Unclaimed prizes are swept to the treasury when the season closes
+#[event]
+pub struct SeasonClosed {
+    pub season_id: u64,
+    pub season: Pubkey,
+    pub swept_amount: u64,
+}
+
+// Admin config change events, old and new values are kept for auditing
+
+#[event]
+pub struct FeeUpdated {
+    pub admin: Pubkey,
+    pub old_fee: u64,
+    pub new_fee: u64,
+}
+
+#[event]
+pub struct MinAmountUpdated {
+    pub admin: Pubkey,
+    pub old_min_amount: u64,
+    pub new_min_amount: u64,
+}
+
+#[event]
+pub struct TreasuryUpdated {
+    pub admin: Pubkey,
+    pub old_treasury_wallet: Pubkey,
+    pub new_treasury_wallet: Pubkey,
+}
+
+#[event]
+pub struct SpinxTokenUpdated {
+    pub admin: Pubkey,
+    pub old_spinx_token: Pubkey,
+    pub new_spinx_token: Pubkey,
+}
+
+#[event]
+pub struct RakeUpdated {
+    pub admin: Pubkey,
+    pub old_rake_bps: u16,
+    pub new_rake_bps: u16,
+}
+
+#[event]
+pub struct ReferralShareUpdated {
+    pub admin: Pubkey,
+    pub old_referral_share_bps: u16,
+    pub new_referral_share_bps: u16,
+}
+
+#[event]
+pub struct RefundTimeoutUpdated {
+    pub admin: Pubkey,
+    pub old_refund_timeout: u64,
+    pub new_refund_timeout: u64,
+}
+
+#[event]
+pub struct InviteTimeoutUpdated {
+    pub admin: Pubkey,
+    pub old_invite_timeout: u64,
+    pub new_invite_timeout: u64,
+}
+
+#[event]
+pub struct MaxPoolTtlUpdated {
+    pub admin: Pubkey,
+    pub old_max_pool_ttl: u64,
+    pub new_max_pool_ttl: u64,
+}
+
+#[event]
+pub struct RandomnessProviderUpdated {
+    pub admin: Pubkey,
+    pub old_provider: RandomnessProvider,
+    pub new_provider: RandomnessProvider,
+    pub switchboard_queue: Pubkey,
+}
+
+#[event]
+pub struct AdminProposed {
+    pub admin: Pubkey,
+    pub pending_admin: Pubkey,
+}
+
+#[event]
+pub struct AdminProposalCancelled {
+    pub admin: Pubkey,
+    pub pending_admin: Pubkey,
+}
+
+#[event]
+pub struct AdminChanged {
+    pub old_admin: Pubkey,
+    pub new_admin: Pubkey,
+}
+
+#[event]
+pub struct GlobalDataMigrated {
+    pub admin: Pubkey,
+    pub old_len: u64,
+    pub new_len: u64,
+}
+
+#[event]
+pub struct PauserUpdated {
+    pub admin: Pubkey,
+    pub old_pauser: Pubkey,
+    pub new_pauser: Pubkey,
+}
+
+#[event]
+pub struct PauseFlagsUpdated {
+    pub authority: Pubkey,
+    pub old_flags: u32,
+    pub new_flags: u32,
+}
+
+#[event]
+pub struct MintAdded {
+    pub admin: Pubkey,
+    pub mint: Pubkey,
+    pub min_amount: u64,
+    pub max_amount: u64,
+    pub coinflip_fee: u64,
+    pub approved_freeze_authority: Pubkey,
+}
+
+#[event]
+pub struct MintConfigUpdated {
+    pub admin: Pubkey,
+    pub mint: Pubkey,
+    pub old_min_amount: u64,
+    pub new_min_amount: u64,
+    pub old_max_amount: u64,
+    pub new_max_amount: u64,
+    pub old_coinflip_fee: u64,
+    pub new_coinflip_fee: u64,
+}
+
+#[event]
+pub struct MintEnabledUpdated {
+    pub admin: Pubkey,
+    pub mint: Pubkey,
+    pub enabled: bool,
+}
@@ -1,22 +1,23 @@
+
 use anchor_lang::prelude::*;
 use anchor_spl::{
-    associated_token::AssociatedToken,
-    token::{self, Mint, Token, TokenAccount, Transfer},
+    associated_token::{get_associated_token_address_with_program_id, AssociatedToken},
+    token::{spl_token::native_mint, Token},
+    token_interface::{self, Burn, Mint, MintTo, TokenAccount, TokenInterface, TransferChecked},
 };
 
-mod misc;
 pub mod account;
 pub mod utils;
 pub mod error;
+pub mod event;
+pub mod randomness;
 
 use account::*;
 use utils::*;
 use error::*;
+use event::*;
 
-use orao_solana_vrf::program::OraoVrf;
-use orao_solana_vrf::state::NetworkState;
-use orao_solana_vrf::CONFIG_ACCOUNT_SEED;
-use orao_solana_vrf::RANDOMNESS_ACCOUNT_SEED;
+use randomness::RequestAccounts;
 
 #[cfg(not(feature = "no-entrypoint"))]
 use {solana_security_txt::security_txt};
@@ -28,244 +29,4259 @@ security_txt! {
     contacts: "email:support@spinx.fun",
     policy: "https://spinx.fun",
 
-    // Optional Fields
-    preferred_languages: "en,de",
-    source_code: "https://github.com/Spinx-fun/spinx-contract",
-    source_release: "",
-    encryption: ""
+    // Optional Fields
+    preferred_languages: "en,de",
+    source_code: "https://github.com/Spinx-fun/spinx-contract",
+    source_release: "",
+    encryption: ""
+}
+
+// This is your program's public key and it will update
+// automatically when you build the project.
+declare_id!("CK9bscEwv3uJRrtVFCaf55ascDR7ufgdk4udGsAWWbi8");
+
+// Anchor 0.31's #[program] emits its IDL handlers next to the module it is on, and they still
+// call the deprecated AccountInfo::realloc. Wrapping it keeps the allow off the rest of the crate.
+#[allow(deprecated)]
+mod handlers {
+    use super::*;
+
+    #[program]
+    pub mod spinx {
+        use super::*;
+
+        pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
+            let global_data = &mut ctx.accounts.global_data;
+            global_data.super_admin = ctx.accounts.admin.key();
+            global_data.treasury_wallet = TREASURY_WALLET.parse::<Pubkey>().unwrap();
+            global_data.spinx_token = SPINX_TOKEN_ADDRESS.parse::<Pubkey>().unwrap();
+            global_data.coinflip_fee = COINFLIP_FEE;
+            global_data.min_amount = 10000000000;
+            global_data.next_pool_id = 1; // Initialize the pool ID count
+            global_data.max_pool_ttl = DEFAULT_MAX_POOL_TTL;
+
+            Ok(())
+        }
+
+        pub fn set_fee(ctx: Context<UpdateGlobalData>, coinflip_fee: u64) -> Result<()> {
+            let global_data = &mut ctx.accounts.global_data;
+
+            let old_fee = global_data.coinflip_fee;
+            global_data.coinflip_fee = coinflip_fee;
+
+            emit_cpi!(FeeUpdated {
+                admin: ctx.accounts.admin.key(),
+                old_fee,
+                new_fee: coinflip_fee,
+            });
+
+            Ok(())
+        }
+
+        pub fn set_min_amount(ctx: Context<UpdateGlobalData>, min_amount: u64) -> Result<()> {
+            let global_data = &mut ctx.accounts.global_data;
+
+            require!(min_amount > 0, SpinXError::InvalidAmount);
+
+            let old_min_amount = global_data.min_amount;
+            global_data.min_amount = min_amount;
+
+            emit_cpi!(MinAmountUpdated {
+                admin: ctx.accounts.admin.key(),
+                old_min_amount,
+                new_min_amount: min_amount,
+            });
+
+            Ok(())
+        }
+
+        pub fn set_treasury(ctx: Context<UpdateGlobalData>, treasury_wallet: Pubkey) -> Result<()> {
+            let global_data = &mut ctx.accounts.global_data;
+
+            require!(treasury_wallet != Pubkey::default(), SpinXError::InvalidTreasury);
+
+            let old_treasury_wallet = global_data.treasury_wallet;
+            global_data.treasury_wallet = treasury_wallet;
+
+            emit_cpi!(TreasuryUpdated {
+                admin: ctx.accounts.admin.key(),
+                old_treasury_wallet,
+                new_treasury_wallet: treasury_wallet,
+            });
+
+            Ok(())
+        }
+
+        pub fn set_spinx_token(ctx: Context<UpdateGlobalData>, spinx_token: Pubkey) -> Result<()> {
+            let global_data = &mut ctx.accounts.global_data;
+
+            require!(spinx_token != Pubkey::default(), SpinXError::TokenNotAllowed);
+
+            let old_spinx_token = global_data.spinx_token;
+            global_data.spinx_token = spinx_token;
+
+            emit_cpi!(SpinxTokenUpdated {
+                admin: ctx.accounts.admin.key(),
+                old_spinx_token,
+                new_spinx_token: spinx_token,
+            });
+
+            Ok(())
+        }
+
+        pub fn set_rake(ctx: Context<UpdateGlobalData>, rake_bps: u16) -> Result<()> {
+            let global_data = &mut ctx.accounts.global_data;
+
+            require!(rake_bps <= MAX_RAKE_BPS, SpinXError::InvalidRake);
+
+            let old_rake_bps = global_data.rake_bps;
+            global_data.rake_bps = rake_bps;
+
+            emit_cpi!(RakeUpdated {
+                admin: ctx.accounts.admin.key(),
+                old_rake_bps,
+                new_rake_bps: rake_bps,
+            });
+
+            Ok(())
+        }
+
+        pub fn set_referral_share(ctx: Context<UpdateGlobalData>, referral_share_bps: u16) -> Result<()> {
+            let global_data = &mut ctx.accounts.global_data;
+
+            require!(referral_share_bps <= MAX_REFERRAL_SHARE_BPS, SpinXError::InvalidReferralShare);
+
+            let old_referral_share_bps = global_data.referral_share_bps;
+            global_data.referral_share_bps = referral_share_bps;
+
+            emit_cpi!(ReferralShareUpdated {
+                admin: ctx.accounts.admin.key(),
+                old_referral_share_bps,
+                new_referral_share_bps: referral_share_bps,
+            });
+
+            Ok(())
+        }
+
+        pub fn set_refund_timeout(ctx: Context<UpdateGlobalData>, refund_timeout: u64) -> Result<()> {
+            let global_data = &mut ctx.accounts.global_data;
+
+            require!(refund_timeout > 0 && refund_timeout <= MAX_REFUND_TIMEOUT, SpinXError::InvalidAmount);
+
+            let old_refund_timeout = global_data.refund_timeout;
+            global_data.refund_timeout = refund_timeout;
+
+            emit_cpi!(RefundTimeoutUpdated {
+                admin: ctx.accounts.admin.key(),
+                old_refund_timeout,
+                new_refund_timeout: refund_timeout,
+            });
+
+            Ok(())
+        }
+
+        pub fn set_invite_timeout(ctx: Context<UpdateGlobalData>, invite_timeout: u64) -> Result<()> {
+            let global_data = &mut ctx.accounts.global_data;
+
+            require!(invite_timeout > 0 && invite_timeout <= MAX_INVITE_TIMEOUT, SpinXError::InvalidAmount);
+
+            let old_invite_timeout = global_data.invite_timeout;
+            global_data.invite_timeout = invite_timeout;
+
+            emit_cpi!(InviteTimeoutUpdated {
+                admin: ctx.accounts.admin.key(),
+                old_invite_timeout,
+                new_invite_timeout: invite_timeout,
+            });
+
+            Ok(())
+        }
+
+        pub fn set_max_pool_ttl(ctx: Context<UpdateGlobalData>, max_pool_ttl: u64) -> Result<()> {
+            let global_data = &mut ctx.accounts.global_data;
+
+            let old_max_pool_ttl = global_data.max_pool_ttl;
+            global_data.max_pool_ttl = max_pool_ttl;
+
+            emit_cpi!(MaxPoolTtlUpdated {
+                admin: ctx.accounts.admin.key(),
+                old_max_pool_ttl,
+                new_max_pool_ttl: max_pool_ttl,
+            });
+
+            Ok(())
+        }
+
+        // Provider for new joins, pools already joined keep theirs
+        pub fn set_randomness_provider(ctx: Context<UpdateGlobalData>, provider: RandomnessProvider, switchboard_queue: Pubkey) -> Result<()> {
+            let global_data = &mut ctx.accounts.global_data;
+
+            require!(
+                provider != RandomnessProvider::Switchboard || switchboard_queue != Pubkey::default(),
+                SpinXError::InvalidRandomnessProvider
+            );
+
+            let old_provider = global_data.randomness_provider;
+            global_data.randomness_provider = provider;
+            global_data.switchboard_queue = switchboard_queue;
+
+            emit_cpi!(RandomnessProviderUpdated {
+                admin: ctx.accounts.admin.key(),
+                old_provider,
+                new_provider: provider,
+                switchboard_queue,
+            });
+
+            Ok(())
+        }
+
+        pub fn propose_admin(ctx: Context<UpdateGlobalData>, new_admin: Pubkey) -> Result<()> {
+            let global_data = &mut ctx.accounts.global_data;
+
+            require!(new_admin != Pubkey::default(), SpinXError::InvalidAdmin);
+
+            global_data.pending_admin = new_admin;
+
+            emit_cpi!(AdminProposed {
+                admin: ctx.accounts.admin.key(),
+                pending_admin: new_admin,
+            });
+
+            Ok(())
+        }
+
+        pub fn cancel_admin_proposal(ctx: Context<UpdateGlobalData>) -> Result<()> {
+            let global_data = &mut ctx.accounts.global_data;
+
+            require!(global_data.pending_admin != Pubkey::default(), SpinXError::NoPendingAdmin);
+
+            let pending_admin = global_data.pending_admin;
+            global_data.pending_admin = Pubkey::default();
+
+            emit_cpi!(AdminProposalCancelled {
+                admin: ctx.accounts.admin.key(),
+                pending_admin,
+            });
+
+            Ok(())
+        }
+
+        pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+            let global_data = &mut ctx.accounts.global_data;
+
+            let old_admin = global_data.super_admin;
+            global_data.super_admin = ctx.accounts.new_admin.key();
+            global_data.pending_admin = Pubkey::default();
+
+            emit_cpi!(AdminChanged {
+                old_admin,
+                new_admin: global_data.super_admin,
+            });
+
+            Ok(())
+        }
+
+        pub fn set_pauser(ctx: Context<UpdateGlobalData>, pauser: Pubkey) -> Result<()> {
+            let global_data = &mut ctx.accounts.global_data;
+
+            let old_pauser = global_data.pauser;
+            global_data.pauser = pauser;
+
+            emit_cpi!(PauserUpdated {
+                admin: ctx.accounts.admin.key(),
+                old_pauser,
+                new_pauser: pauser,
+            });
+
+            Ok(())
+        }
+
+        // Admin only, can both pause and unpause
+        pub fn set_pause_flags(ctx: Context<UpdateGlobalData>, pause_flags: u32) -> Result<()> {
+            let global_data = &mut ctx.accounts.global_data;
+
+            let old_flags = global_data.pause_flags;
+            global_data.pause_flags = pause_flags;
+
+            emit_cpi!(PauseFlagsUpdated {
+                authority: ctx.accounts.admin.key(),
+                old_flags,
+                new_flags: pause_flags,
+            });
+
+            Ok(())
+        }
+
+        // Admin or pauser, can only add bits so a leaked pauser key can't unpause
+        pub fn pause(ctx: Context<Pause>, pause_flags: u32) -> Result<()> {
+            let global_data = &mut ctx.accounts.global_data;
+
+            let old_flags = global_data.pause_flags;
+            global_data.pause_flags |= pause_flags;
+
+            emit_cpi!(PauseFlagsUpdated {
+                authority: ctx.accounts.authority.key(),
+                old_flags,
+                new_flags: global_data.pause_flags,
+            });
+
+            Ok(())
+        }
+
+        // Grows a GlobalData account created by an older program version to the current layout.
+        // New fields are zero filled, which keeps the previous behaviour for each of them.
+        pub fn migrate_global_data(ctx: Context<MigrateGlobalData>) -> Result<()> {
+            let global_data = &ctx.accounts.global_data;
+            let new_len = 8 + std::mem::size_of::<GlobalData>();
+
+            let old_len = {
+                let data = global_data.try_borrow_data()?;
+                require!(data.len() >= 8 + 32, SpinXError::InvalidAccountData);
+                require!(data[..8] == *GlobalData::DISCRIMINATOR, SpinXError::InvalidAccountData);
+
+                let super_admin = Pubkey::try_from(&data[8..8 + 32]).unwrap();
+                require!(super_admin == ctx.accounts.admin.key(), SpinXError::InvalidAdmin);
+
+                data.len()
+            };
+
+            if old_len < new_len {
+                resize_account(
+                    global_data.to_account_info(),
+                    ctx.accounts.admin.to_account_info(),
+                    ctx.accounts.system_program.to_account_info(),
+                    new_len
+                )?;
+            }
+
+            // Layouts before max_pool_ttl get the default instead of no limit
+            if old_len < GlobalData::MAX_POOL_TTL_OFFSET + 8 {
+                let mut data = global_data.try_borrow_mut_data()?;
+                data[GlobalData::MAX_POOL_TTL_OFFSET..GlobalData::MAX_POOL_TTL_OFFSET + 8]
+                    .copy_from_slice(&DEFAULT_MAX_POOL_TTL.to_le_bytes());
+            }
+
+            emit_cpi!(GlobalDataMigrated {
+                admin: ctx.accounts.admin.key(),
+                old_len: old_len as u64,
+                new_len: old_len.max(new_len) as u64,
+            });
+
+            Ok(())
+        }
+
+        // Allowlists a mint, starting from the global min_amount and coinflip_fee defaults
+        // approved_freeze_authority is the mint's freeze authority the admin accepts,
+        // Pubkey::default() for mints without one
+        pub fn add_mint(ctx: Context<AddMint>, approved_freeze_authority: Pubkey) -> Result<()> {
+            let global_data = &ctx.accounts.global_data;
+            let mint_config = &mut ctx.accounts.mint_config;
+
+            check_mint(&ctx.accounts.mint.to_account_info(), approved_freeze_authority)?;
+
+            mint_config.mint = ctx.accounts.mint.key();
+            mint_config.enabled = true;
+            mint_config.min_amount = global_data.min_amount;
+            mint_config.max_amount = 0;
+            mint_config.coinflip_fee = global_data.coinflip_fee;
+            mint_config.bump = ctx.bumps.mint_config;
+            mint_config.approved_freeze_authority = approved_freeze_authority;
+
+            emit_cpi!(MintAdded {
+                admin: ctx.accounts.admin.key(),
+                mint: mint_config.mint,
+                min_amount: mint_config.min_amount,
+                max_amount: mint_config.max_amount,
+                coinflip_fee: mint_config.coinflip_fee,
+                approved_freeze_authority,
+            });
+
+            Ok(())
+        }
+
+        pub fn update_mint(ctx: Context<UpdateMintConfig>, min_amount: u64, max_amount: u64, coinflip_fee: u64) -> Result<()> {
+            let mint_config = &mut ctx.accounts.mint_config;
+
+            require!(min_amount > 0, SpinXError::InvalidAmount);
+            require!(max_amount == 0 || max_amount >= min_amount, SpinXError::InvalidAmount);
+
+            emit_cpi!(MintConfigUpdated {
+                admin: ctx.accounts.admin.key(),
+                mint: mint_config.mint,
+                old_min_amount: mint_config.min_amount,
+                new_min_amount: min_amount,
+                old_max_amount: mint_config.max_amount,
+                new_max_amount: max_amount,
+                old_coinflip_fee: mint_config.coinflip_fee,
+                new_coinflip_fee: coinflip_fee,
+            });
+
+            mint_config.min_amount = min_amount;
+            mint_config.max_amount = max_amount;
+            mint_config.coinflip_fee = coinflip_fee;
+
+            Ok(())
+        }
+
+        // Stops creates and joins in the mint. Waiting pools can still be closed by
+        // their creator or expired, joined pools settle or refund as usual.
+        pub fn disable_mint(ctx: Context<UpdateMintConfig>) -> Result<()> {
+            let mint_config = &mut ctx.accounts.mint_config;
+
+            mint_config.enabled = false;
+
+            emit_cpi!(MintEnabledUpdated {
+                admin: ctx.accounts.admin.key(),
+                mint: mint_config.mint,
+                enabled: false,
+            });
+
+            Ok(())
+        }
+
+        pub fn enable_mint(ctx: Context<UpdateMintConfig>) -> Result<()> {
+            let mint_config = &mut ctx.accounts.mint_config;
+
+            mint_config.enabled = true;
+
+            emit_cpi!(MintEnabledUpdated {
+                admin: ctx.accounts.admin.key(),
+                mint: mint_config.mint,
+                enabled: true,
+            });
+
+            Ok(())
+        }
+
+        // Grows a CoinflipPool created by an older program version to the current layout.
+        // Pools from before the mint allowlist were always SPINX pools.
+        pub fn migrate_coinflip_pool(ctx: Context<MigrateCoinflipPool>, pool_id: u64) -> Result<()> {
+            let coinflip_pool = &ctx.accounts.coinflip_pool;
+            let new_len = 8 + std::mem::size_of::<CoinflipPool>();
+
+            let old_len = {
+                let data = coinflip_pool.try_borrow_data()?;
+                require!(data.len() >= 8, SpinXError::InvalidAccountData);
+                require!(data[..8] == *CoinflipPool::DISCRIMINATOR, SpinXError::InvalidAccountData);
+
+                data.len()
+            };
+
+            if old_len < new_len {
+                resize_account(
+                    coinflip_pool.to_account_info(),
+                    ctx.accounts.payer.to_account_info(),
+                    ctx.accounts.system_program.to_account_info(),
+                    new_len
+                )?;
+            }
+
+            let mut data = coinflip_pool.try_borrow_mut_data()?;
+            let mut pool = CoinflipPool::try_deserialize(&mut &data[..])?;
+            require!(pool.pool_id == pool_id, SpinXError::InvalidAccountData);
+
+            if pool.mint == Pubkey::default() {
+                pool.mint = ctx.accounts.global_data.spinx_token;
+            }
+            pool.try_serialize(&mut &mut data[..])?;
+
+            Ok(())
+        }
+
+        pub fn create_coinflip(ctx: Context<CreateCoinflip>, set_number: u8, amount: u64, ttl: u64, allowed_joiner: Option<Pubkey>, joiner_stake: Option<StakeRange>) -> Result<()> {
+            let coinflip_pool = &mut ctx.accounts.coinflip_pool;
+            let global_data = &mut ctx.accounts.global_data;
+
+            let mint_config = &ctx.accounts.mint_config;
+
+            require!(!global_data.is_paused(PAUSE_CREATE_COINFLIP), SpinXError::InstructionPaused);
+            require!(set_number < 2, SpinXError::InvalidNumber);
+
+            // Extensions and the freeze authority can change after the mint was allowlisted
+            check_mint(&ctx.accounts.mint.to_account_info(), ctx.accounts.mint_config.approved_freeze_authority)?;
+
+            // The stake is what the escrow actually receives after any transfer fee
+            let net_amount = amount - transfer_fee(&ctx.accounts.mint.to_account_info(), amount)?;
+
+            require!( net_amount >= mint_config.min_amount, SpinXError::AmountTooSmall);
+            require!(mint_config.max_amount == 0 || net_amount <= mint_config.max_amount, SpinXError::AmountTooLarge);
+        
+            let fee = mint_config.coinflip_fee;
+
+            // Transfer the flat fee to treasury less the referrer's share, 0 disables it
+            let referral_fee = pay_fee(
+                ctx.accounts.creator.to_account_info(),
+                ctx.accounts.treasury_wallet.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+                &ctx.accounts.player_referral,
+                ctx.accounts.referrer_referral.as_mut(),
+                global_data.referral_share_bps,
+                fee
+            )?;
+            if let Some(referral_fee) = referral_fee {
+                emit_cpi!(referral_fee);
+            }
+
+            // Transfer amount SPL token to spl_escrow
+            let cpi_accounts = TransferChecked {
+                from: ctx.accounts.creator_ata.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.spl_escrow.to_account_info(),
+                authority: ctx.accounts.creator.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
+            token_interface::transfer_checked(cpi_ctx, amount, ctx.accounts.mint.decimals)?;
+
+            // Generate the random number
+            let timestamp = Clock::get()?.unix_timestamp;
+            let expiry_ts = global_data.pool_expiry(timestamp as u64, ttl)?;
+
+            // Assign the current pool_id to this coinflip pool
+            coinflip_pool.pool_id = global_data.next_pool_id;
+
+            // Increment the next_pool_id for the next coinflip
+            global_data.next_pool_id += 1;
+
+            coinflip_pool.start_ts = timestamp as u64;
+            coinflip_pool.expiry_ts = expiry_ts;
+            coinflip_pool.mint = ctx.accounts.mint.key();
+            coinflip_pool.creator_player = ctx.accounts.creator.key();
+            coinflip_pool.creator_amount = net_amount;
+            coinflip_pool.creator_ata = ctx.accounts.creator_ata.key();
+            coinflip_pool.creator_set_number = set_number;
+            coinflip_pool.pool_amount = net_amount;
+            coinflip_pool.rake_bps = global_data.rake_bps;
+            coinflip_pool.invite(allowed_joiner, timestamp as u64, global_data.invite_timeout())?;
+            coinflip_pool.set_joiner_stake(joiner_stake)?;
+            coinflip_pool.status = PoolStatus::Waiting;    
+            coinflip_pool.bump = ctx.bumps.coinflip_pool;
+            coinflip_pool.stake_tier = stake_tier(coinflip_pool.creator_amount);
+
+            ctx.accounts.creator_profile.open(ctx.accounts.creator.key(), ctx.accounts.mint.key(), ctx.bumps.creator_profile);
+
+            // Public pools show up in the lobby of their mint and stake tier
+            let pool_registry = &mut ctx.accounts.pool_registry;
+            pool_registry.open(coinflip_pool.mint, coinflip_pool.stake_tier, ctx.bumps.pool_registry);
+            pool_registry.list(coinflip_pool, timestamp as u64);
+
+            emit_cpi!(PoolCreated {
+                pool_id: coinflip_pool.pool_id,
+                pool: coinflip_pool.key(),
+                creator: coinflip_pool.creator_player,
+                mint: coinflip_pool.mint,
+                is_native: false,
+                amount: coinflip_pool.creator_amount,
+                set_number,
+                fee,
+                rake_bps: coinflip_pool.rake_bps,
+                expiry_ts: coinflip_pool.expiry_ts,
+                allowed_joiner: coinflip_pool.allowed_joiner,
+                weighted: coinflip_pool.weighted,
+                joiner_min_amount: coinflip_pool.joiner_min_amount,
+                joiner_max_amount: coinflip_pool.joiner_max_amount,
+            });
+
+            Ok(())
+        }
+
+        pub fn join_coinflip(ctx: Context<JoinCoinflip>, pool_id: u64, force: [u8; 32], set_number: u8, amount: u64) -> Result<()> {
+            let coinflip_pool = &mut ctx.accounts.coinflip_pool;        
+            let global_data = &mut ctx.accounts.global_data;
+            let fee = ctx.accounts.mint_config.coinflip_fee;
+        
+            require!(!global_data.is_paused(PAUSE_JOIN_COINFLIP), SpinXError::InstructionPaused);
+            coinflip_pool.check_joinable(ctx.accounts.joiner.key(), set_number, Clock::get()?.unix_timestamp as u64)?;
+
+            check_mint(&ctx.accounts.mint.to_account_info(), ctx.accounts.mint_config.approved_freeze_authority)?;
+
+            // Stakes are compared after transfer fees, amount is what the joiner sends
+            let net_amount = amount - transfer_fee(&ctx.accounts.mint.to_account_info(), amount)?;
+            coinflip_pool.check_joiner_amount(net_amount, &ctx.accounts.mint_config)?;
+
+            // Transfer amount SPL token to spl_escrow
+            let cpi_accounts = TransferChecked {
+                from: ctx.accounts.joiner_ata.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.spl_escrow.to_account_info(),
+                authority: ctx.accounts.joiner.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
+            token_interface::transfer_checked(cpi_ctx, amount, ctx.accounts.mint.decimals)?;
+
+            // Transfer the flat fee to treasury less the referrer's share, 0 disables it
+            let referral_fee = pay_fee(
+                ctx.accounts.joiner.to_account_info(),
+                ctx.accounts.treasury_wallet.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+                &ctx.accounts.player_referral,
+                ctx.accounts.referrer_referral.as_mut(),
+                global_data.referral_share_bps,
+                fee
+            )?;
+            if let Some(referral_fee) = referral_fee {
+                emit_cpi!(referral_fee);
+            }
+        
+            // Request randomness.
+            let commit_slot = randomness::request(
+                global_data,
+                RequestAccounts {
+                    payer: ctx.accounts.joiner.to_account_info(),
+                    random: ctx.accounts.random.to_account_info(),
+                    config: ctx.accounts.config.to_account_info(),
+                    treasury: ctx.accounts.treasury.to_account_info(),
+                    vrf: ctx.accounts.vrf.to_account_info(),
+                    system_program: ctx.accounts.system_program.to_account_info(),
+                },
+                force
+            )?;
+        
+            coinflip_pool.joiner_player = ctx.accounts.joiner.key();
+            coinflip_pool.joiner_amount = net_amount;
+            coinflip_pool.joiner_ata = ctx.accounts.joiner_ata.key();
+            coinflip_pool.joiner_set_number = set_number;
+            coinflip_pool.pool_amount += net_amount;
+            coinflip_pool.force = force;
+            coinflip_pool.randomness_provider = global_data.randomness_provider;
+            coinflip_pool.random = ctx.accounts.random.key();
+            coinflip_pool.commit_slot = commit_slot;
+            coinflip_pool.join_ts = Clock::get()?.unix_timestamp as u64;
+            coinflip_pool.status = PoolStatus::Processing;        
+
+            ctx.accounts.joiner_profile.open(ctx.accounts.joiner.key(), ctx.accounts.mint.key(), ctx.bumps.joiner_profile);
+
+            PoolRegistry::unlist_from(&ctx.accounts.pool_registry, pool_id)?;
+
+            emit_cpi!(PoolJoined {
+                pool_id,
+                pool: coinflip_pool.key(),
+                joiner: coinflip_pool.joiner_player,
+                amount: coinflip_pool.joiner_amount,
+                set_number,
+                fee,
+                pool_amount: coinflip_pool.pool_amount,
+            });
+
+            emit_cpi!(RandomnessRequested {
+                pool_id,
+                pool: coinflip_pool.key(),
+                force,
+                random: ctx.accounts.random.key(),
+                provider: coinflip_pool.randomness_provider,
+            });
+
+            Ok(())
+        }
+
+        pub fn close_coinflip(ctx: Context<CloseCoinflip>, pool_id: u64) -> Result<()> {
+            let coinflip_pool = &mut ctx.accounts.coinflip_pool;        
+        
+            require!(coinflip_pool.creator_player == ctx.accounts.signer.key(), SpinXError::InvalidCreator);
+            require!(coinflip_pool.joiner_player == Pubkey::default(), SpinXError::InvalidClaimStatus);
+            require!(coinflip_pool.status == PoolStatus::Waiting, SpinXError::InvalidPoolStatus);
+
+            let seeds = &[
+                    COINFLIP_SEED.as_bytes(), &pool_id.to_le_bytes(),
+                    &[coinflip_pool.bump],
+                ];
+            let signer = &[&seeds[..]]; 
+
+            let cpi_accounts = TransferChecked {
+                from: ctx.accounts.spl_escrow.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.creator_ata.to_account_info(),
+                authority: coinflip_pool.to_account_info(),
+            };
+
+            let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), cpi_accounts, signer);
+            token_interface::transfer_checked(cpi_ctx, coinflip_pool.creator_amount, ctx.accounts.mint.decimals)?;  
+
+            // Tokens sent to the escrow on top of the stake go back to the creator
+            sweep_escrow(
+                ctx.accounts.spl_escrow.to_account_info(),
+                ctx.accounts.mint.to_account_info(),
+                ctx.accounts.creator_ata.to_account_info(),
+                coinflip_pool.to_account_info(),
+                ctx.accounts.token_program.to_account_info(),
+                signer,
+                ctx.accounts.mint.decimals
+            )?;
+
+            // Give the escrow rent back to the creator who paid it, the pool itself is closed by the account constraint
+            close_escrow(
+                ctx.accounts.spl_escrow.to_account_info(),
+                ctx.accounts.mint.to_account_info(),
+                ctx.accounts.signer.to_account_info(),
+                coinflip_pool.to_account_info(),
+                ctx.accounts.token_program.to_account_info(),
+                signer
+            )?;
+
+            coinflip_pool.status = PoolStatus::Closed;
+            coinflip_pool.pool_amount = 0;
+
+            PoolRegistry::unlist_from(&ctx.accounts.pool_registry, pool_id)?;
+
+            emit_cpi!(PoolClosed {
+                pool_id,
+                pool: coinflip_pool.key(),
+                status: PoolStatus::Closed,
+                creator_refund: coinflip_pool.creator_amount,
+                joiner_refund: 0,
+            });
+
+            Ok(())
+        }
+
+        // Makes an invite-only pool public once the invited player had invite_timeout to join.
+        // Nothing moves, so it serves SPL and SOL pools alike.
+        pub fn open_coinflip(ctx: Context<OpenCoinflip>, pool_id: u64) -> Result<()> {
+            let coinflip_pool = &mut ctx.accounts.coinflip_pool;
+            let now = Clock::get()?.unix_timestamp as u64;
+
+            require!(coinflip_pool.creator_player == ctx.accounts.creator.key(), SpinXError::InvalidCreator);
+            require!(coinflip_pool.status == PoolStatus::Waiting, SpinXError::InvalidPoolStatus);
+            // Public pools have nothing to open
+            require!(coinflip_pool.allowed_joiner != Pubkey::default(), SpinXError::InvalidPoolStatus);
+            require!(now >= coinflip_pool.invite_expiry_ts, SpinXError::InviteNotExpired);
+
+            let allowed_joiner = coinflip_pool.allowed_joiner;
+            coinflip_pool.allowed_joiner = Pubkey::default();
+            coinflip_pool.invite_expiry_ts = 0;
+
+            let pool_registry = &mut ctx.accounts.pool_registry;
+            pool_registry.open(coinflip_pool.mint, coinflip_pool.stake_tier, ctx.bumps.pool_registry);
+            pool_registry.list(coinflip_pool, now);
+
+            emit_cpi!(PoolOpened {
+                pool_id,
+                pool: coinflip_pool.key(),
+                creator: coinflip_pool.creator_player,
+                allowed_joiner,
+            });
+
+            Ok(())
+        }
+
+        // Anyone can crank an expired, unjoined pool, the stake goes back to the creator.
+        // Never pausable.
+        pub fn expire_coinflip(ctx: Context<ExpireCoinflip>, pool_id: u64) -> Result<()> {
+            let coinflip_pool = &mut ctx.accounts.coinflip_pool;
+            let now = Clock::get()?.unix_timestamp as u64;
+
+            require!(coinflip_pool.status == PoolStatus::Waiting, SpinXError::InvalidPoolStatus);
+            require!(coinflip_pool.joiner_player == Pubkey::default(), SpinXError::AlreadyJoined);
+            require!(coinflip_pool.is_expired(now), SpinXError::PoolNotExpired);
+
+            let seeds = &[
+                    COINFLIP_SEED.as_bytes(), &pool_id.to_le_bytes(),
+                    &[coinflip_pool.bump],
+                ];
+            let signer = &[&seeds[..]];
+
+            let cpi_accounts = TransferChecked {
+                from: ctx.accounts.spl_escrow.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.creator_ata.to_account_info(),
+                authority: coinflip_pool.to_account_info(),
+            };
+
+            let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), cpi_accounts, signer);
+            token_interface::transfer_checked(cpi_ctx, coinflip_pool.creator_amount, ctx.accounts.mint.decimals)?;
+
+            // Tokens sent to the escrow on top of the stake go back to the creator
+            sweep_escrow(
+                ctx.accounts.spl_escrow.to_account_info(),
+                ctx.accounts.mint.to_account_info(),
+                ctx.accounts.creator_ata.to_account_info(),
+                coinflip_pool.to_account_info(),
+                ctx.accounts.token_program.to_account_info(),
+                signer,
+                ctx.accounts.mint.decimals
+            )?;
+
+            // Give the escrow rent back to the creator who paid it, the pool itself is closed by the account constraint
+            close_escrow(
+                ctx.accounts.spl_escrow.to_account_info(),
+                ctx.accounts.mint.to_account_info(),
+                ctx.accounts.creator.to_account_info(),
+                coinflip_pool.to_account_info(),
+                ctx.accounts.token_program.to_account_info(),
+                signer
+            )?;
+
+            coinflip_pool.status = PoolStatus::Expired;
+            coinflip_pool.pool_amount = 0;
+
+            PoolRegistry::unlist_from(&ctx.accounts.pool_registry, pool_id)?;
+
+            emit_cpi!(PoolClosed {
+                pool_id,
+                pool: coinflip_pool.key(),
+                status: PoolStatus::Expired,
+                creator_refund: coinflip_pool.creator_amount,
+                joiner_refund: 0,
+            });
+
+            Ok(())
+        }
+
+        pub fn result_coinflip(ctx: Context<ResultCoinflip>, pool_id: u64, force: [u8; 32]) -> Result<()> {
+            let coinflip_pool = &mut ctx.accounts.coinflip_pool;
+
+            require!(coinflip_pool.force == force, SpinXError::InvalidForce);
+            require!(coinflip_pool.status == PoolStatus::Processing, SpinXError::InvalidPoolStatus);
+
+            // The joiner committed the randomness, giving it up loses them the game
+            let now = Clock::get()?.unix_timestamp as u64;
+            let forfeited = randomness::forfeited(coinflip_pool.randomness_provider, coinflip_pool.commit_slot, &ctx.accounts.random)?;
+
+            let randomness = if forfeited {
+                0
+            } else {
+                match randomness::read_fulfilled(coinflip_pool.randomness_provider, coinflip_pool.commit_slot, &ctx.accounts.random)? {
+                    Some(randomness) => randomness,
+                    None => return err!(SpinXError::StillProcessing),
+                }
+            };
+
+            msg!("VRF result is: {}", randomness);
+
+            let seeds = &[
+                    COINFLIP_SEED.as_bytes(), &pool_id.to_le_bytes(),
+                    &[coinflip_pool.bump],
+                ];
+            let signer = &[&seeds[..]]; 
+
+            coinflip_pool.winner = if forfeited { coinflip_pool.creator_player } else { coinflip_pool.winner_for(randomness) };
+
+            // Rake goes to the treasury in the wagered token, the winner gets the rest
+            let rake = coinflip_pool.rake();
+            let payout = coinflip_pool.pool_amount - rake;
+
+            // A running season of the mint takes its share of the rake for the prize vault
+            let mut season = Season::load_live(&ctx.accounts.season, now)?;
+            let season_prize = season.as_ref().map_or(0, |season| season.prize_for(rake));
+
+            if rake > season_prize {
+                let cpi_accounts = TransferChecked {
+                    from: ctx.accounts.spl_escrow.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.treasury_ata.to_account_info(),
+                    authority: coinflip_pool.to_account_info(),
+                };
+
+                let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), cpi_accounts, signer);
+                token_interface::transfer_checked(cpi_ctx, rake - season_prize, ctx.accounts.mint.decimals)?;
+            }
+
+            if season_prize > 0 {
+                let cpi_accounts = TransferChecked {
+                    from: ctx.accounts.spl_escrow.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.season_vault.to_account_info(),
+                    authority: coinflip_pool.to_account_info(),
+                };
+
+                let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), cpi_accounts, signer);
+                token_interface::transfer_checked(cpi_ctx, season_prize, ctx.accounts.mint.decimals)?;
+            }
+
+            if coinflip_pool.winner == coinflip_pool.joiner_player { // Win Joiner
+
+                let cpi_accounts = TransferChecked {
+                    from: ctx.accounts.spl_escrow.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.joiner_ata.to_account_info(),
+                    authority: coinflip_pool.to_account_info(),
+                };
+            
+                let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), cpi_accounts, signer);
+                token_interface::transfer_checked(cpi_ctx, payout, ctx.accounts.mint.decimals)?;   
+
+            } else { // Win Creator
+                let cpi_accounts = TransferChecked {
+                    from: ctx.accounts.spl_escrow.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.creator_ata.to_account_info(),
+                    authority: coinflip_pool.to_account_info(),
+                };
+
+                let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), cpi_accounts, signer);
+                token_interface::transfer_checked(cpi_ctx, payout, ctx.accounts.mint.decimals)?;
+            }
+
+            // Tokens sent to the escrow on top of the stakes go to the treasury
+            sweep_escrow(
+                ctx.accounts.spl_escrow.to_account_info(),
+                ctx.accounts.mint.to_account_info(),
+                ctx.accounts.treasury_ata.to_account_info(),
+                coinflip_pool.to_account_info(),
+                ctx.accounts.token_program.to_account_info(),
+                signer,
+                ctx.accounts.mint.decimals
+            )?;
+
+            // Give the escrow rent back to the creator who paid it, the pool itself is closed by the account constraint
+            close_escrow(
+                ctx.accounts.spl_escrow.to_account_info(),
+                ctx.accounts.mint.to_account_info(),
+                ctx.accounts.creator.to_account_info(),
+                coinflip_pool.to_account_info(),
+                ctx.accounts.token_program.to_account_info(),
+                signer
+            )?;
+
+            // Lifetime stats and season scores, the winner's profit is the payout less their own stake
+            let creator_won = coinflip_pool.winner == coinflip_pool.creator_player;
+            PlayerProfile::record_on(&ctx.accounts.creator_profile, coinflip_pool.creator_amount, if creator_won { payout } else { 0 }, now, season.as_mut())?;
+            PlayerProfile::record_on(&ctx.accounts.joiner_profile, coinflip_pool.joiner_amount, if creator_won { 0 } else { payout }, now, season.as_mut())?;
+
+            if let Some(season) = season {
+                season.store(&ctx.accounts.season)?;
+
+                if season_prize > 0 {
+                    emit_cpi!(SeasonPrizeFunded {
+                        season_id: season.season_id,
+                        pool_id,
+                        amount: season_prize,
+                    });
+                }
+            }
+
+            coinflip_pool.status = PoolStatus::Finished;
+            coinflip_pool.pool_amount = 0;
+
+            msg!("Coinflip game in room {} has concluded, the winner is {}", pool_id, coinflip_pool.winner.to_string());        
+
+            emit_cpi!(PoolSettled {
+                pool_id,
+                pool: coinflip_pool.key(),
+                randomness,
+                result: coinflip_pool.winning_number(),
+                winner: coinflip_pool.winner,
+                loser: if coinflip_pool.winner == coinflip_pool.joiner_player {
+                    coinflip_pool.creator_player
+                } else {
+                    coinflip_pool.joiner_player
+                },
+                pool_amount: payout + rake,
+                payout,
+                rake,
+            });
+
+            Ok(())
+        }
+
+        // Anyone can refund both stakes once a joined pool has waited refund_timeout
+        // for randomness that never arrived. Never pausable.
+        pub fn refund_coinflip(ctx: Context<RefundCoinflip>, pool_id: u64) -> Result<()> {
+            let coinflip_pool = &mut ctx.accounts.coinflip_pool;
+            let now = Clock::get()?.unix_timestamp as u64;
+
+            require!(coinflip_pool.status == PoolStatus::Processing, SpinXError::InvalidPoolStatus);
+            require!(
+                now >= coinflip_pool.refund_deadline(ctx.accounts.global_data.refund_timeout()),
+                SpinXError::RefundNotAvailable
+            );
+
+            // A late fulfillment or a forfeit must be settled, otherwise the loser could refund instead
+            require!(
+                !randomness::forfeited(coinflip_pool.randomness_provider, coinflip_pool.commit_slot, &ctx.accounts.random)?,
+                SpinXError::RandomnessForfeited
+            );
+            let fulfilled = randomness::read_fulfilled(coinflip_pool.randomness_provider, coinflip_pool.commit_slot, &ctx.accounts.random)?;
+            require!(fulfilled.is_none(), SpinXError::RandomnessFulfilled);
+
+            let seeds = &[
+                    COINFLIP_SEED.as_bytes(), &pool_id.to_le_bytes(),
+                    &[coinflip_pool.bump],
+                ];
+            let signer = &[&seeds[..]];
+
+            let cpi_accounts = TransferChecked {
+                from: ctx.accounts.spl_escrow.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.creator_ata.to_account_info(),
+                authority: coinflip_pool.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), cpi_accounts, signer);
+            token_interface::transfer_checked(cpi_ctx, coinflip_pool.creator_amount, ctx.accounts.mint.decimals)?;
+
+            let cpi_accounts = TransferChecked {
+                from: ctx.accounts.spl_escrow.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.joiner_ata.to_account_info(),
+                authority: coinflip_pool.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), cpi_accounts, signer);
+            token_interface::transfer_checked(cpi_ctx, coinflip_pool.joiner_amount, ctx.accounts.mint.decimals)?;
+
+            // Tokens sent to the escrow on top of the stakes go to the creator
+            sweep_escrow(
+                ctx.accounts.spl_escrow.to_account_info(),
+                ctx.accounts.mint.to_account_info(),
+                ctx.accounts.creator_ata.to_account_info(),
+                coinflip_pool.to_account_info(),
+                ctx.accounts.token_program.to_account_info(),
+                signer,
+                ctx.accounts.mint.decimals
+            )?;
+
+            // Give the escrow rent back to the creator who paid it, the pool itself is closed by the account constraint
+            close_escrow(
+                ctx.accounts.spl_escrow.to_account_info(),
+                ctx.accounts.mint.to_account_info(),
+                ctx.accounts.creator.to_account_info(),
+                coinflip_pool.to_account_info(),
+                ctx.accounts.token_program.to_account_info(),
+                signer
+            )?;
+
+            coinflip_pool.status = PoolStatus::Refunded;
+            coinflip_pool.pool_amount = 0;
+
+            msg!("Coinflip game in room {} was refunded", pool_id);
+
+            emit_cpi!(PoolClosed {
+                pool_id,
+                pool: coinflip_pool.key(),
+                status: PoolStatus::Refunded,
+                creator_refund: coinflip_pool.creator_amount,
+                joiner_refund: coinflip_pool.joiner_amount,
+            });
+
+            Ok(())
+        }
+
+        // SOL pools, stakes are lamports held in a system owned PDA per pool.
+        // Limits and fee come from the native mint's MintConfig.
+        pub fn create_sol_coinflip(ctx: Context<CreateSolCoinflip>, set_number: u8, amount: u64, ttl: u64, allowed_joiner: Option<Pubkey>, joiner_stake: Option<StakeRange>) -> Result<()> {
+            let coinflip_pool = &mut ctx.accounts.coinflip_pool;
+            let global_data = &mut ctx.accounts.global_data;
+            let mint_config = &ctx.accounts.mint_config;
+
+            require!(!global_data.is_paused(PAUSE_CREATE_COINFLIP), SpinXError::InstructionPaused);
+            require!(set_number < 2, SpinXError::InvalidNumber);
+            require!(amount >= mint_config.min_amount, SpinXError::AmountTooSmall);
+            require!(mint_config.max_amount == 0 || amount <= mint_config.max_amount, SpinXError::AmountTooLarge);
+            // The escrow PDA is a plain system account, so it has to start rent exempt
+            require!(amount >= Rent::get()?.minimum_balance(0), SpinXError::AmountTooSmall);
+
+            let fee = mint_config.coinflip_fee;
+
+            // Transfer the flat fee to treasury less the referrer's share, 0 disables it
+            let referral_fee = pay_fee(
+                ctx.accounts.creator.to_account_info(),
+                ctx.accounts.treasury_wallet.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+                &ctx.accounts.player_referral,
+                ctx.accounts.referrer_referral.as_mut(),
+                global_data.referral_share_bps,
+                fee
+            )?;
+            if let Some(referral_fee) = referral_fee {
+                emit_cpi!(referral_fee);
+            }
+
+            // Transfer the stake to sol_escrow
+            sol_transfer_user(
+                ctx.accounts.creator.to_account_info(),
+                ctx.accounts.sol_escrow.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+                amount
+            )?;
+
+            let timestamp = Clock::get()?.unix_timestamp;
+            let expiry_ts = global_data.pool_expiry(timestamp as u64, ttl)?;
+
+            coinflip_pool.pool_id = global_data.next_pool_id;
+            global_data.next_pool_id += 1;
+
+            coinflip_pool.start_ts = timestamp as u64;
+            coinflip_pool.expiry_ts = expiry_ts;
+            coinflip_pool.mint = native_mint::ID;
+            coinflip_pool.is_native = true;
+            coinflip_pool.creator_player = ctx.accounts.creator.key();
+            coinflip_pool.creator_amount = amount;
+            coinflip_pool.creator_ata = ctx.accounts.creator.key();
+            coinflip_pool.creator_set_number = set_number;
+            coinflip_pool.pool_amount = amount;
+            coinflip_pool.rake_bps = global_data.rake_bps;
+            coinflip_pool.invite(allowed_joiner, timestamp as u64, global_data.invite_timeout())?;
+            coinflip_pool.set_joiner_stake(joiner_stake)?;
+            coinflip_pool.status = PoolStatus::Waiting;
+            coinflip_pool.bump = ctx.bumps.coinflip_pool;
+            coinflip_pool.stake_tier = stake_tier(coinflip_pool.creator_amount);
+
+            ctx.accounts.creator_profile.open(ctx.accounts.creator.key(), native_mint::ID, ctx.bumps.creator_profile);
+
+            // Public pools show up in the lobby of their mint and stake tier
+            let pool_registry = &mut ctx.accounts.pool_registry;
+            pool_registry.open(coinflip_pool.mint, coinflip_pool.stake_tier, ctx.bumps.pool_registry);
+            pool_registry.list(coinflip_pool, timestamp as u64);
+
+            emit_cpi!(PoolCreated {
+                pool_id: coinflip_pool.pool_id,
+                pool: coinflip_pool.key(),
+                creator: coinflip_pool.creator_player,
+                mint: coinflip_pool.mint,
+                is_native: true,
+                amount: coinflip_pool.creator_amount,
+                set_number,
+                fee,
+                rake_bps: coinflip_pool.rake_bps,
+                expiry_ts: coinflip_pool.expiry_ts,
+                allowed_joiner: coinflip_pool.allowed_joiner,
+                weighted: coinflip_pool.weighted,
+                joiner_min_amount: coinflip_pool.joiner_min_amount,
+                joiner_max_amount: coinflip_pool.joiner_max_amount,
+            });
+
+            Ok(())
+        }
+
+        pub fn join_sol_coinflip(ctx: Context<JoinSolCoinflip>, pool_id: u64, force: [u8; 32], set_number: u8, amount: u64) -> Result<()> {
+            let coinflip_pool = &mut ctx.accounts.coinflip_pool;
+            let global_data = &ctx.accounts.global_data;
+            let fee = ctx.accounts.mint_config.coinflip_fee;
+
+            require!(!global_data.is_paused(PAUSE_JOIN_COINFLIP), SpinXError::InstructionPaused);
+            coinflip_pool.check_joinable(ctx.accounts.joiner.key(), set_number, Clock::get()?.unix_timestamp as u64)?;
+            coinflip_pool.check_joiner_amount(amount, &ctx.accounts.mint_config)?;
+
+            // Transfer the stake to sol_escrow
+            sol_transfer_user(
+                ctx.accounts.joiner.to_account_info(),
+                ctx.accounts.sol_escrow.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+                amount
+            )?;
+
+            // Transfer the flat fee to treasury less the referrer's share, 0 disables it
+            let referral_fee = pay_fee(
+                ctx.accounts.joiner.to_account_info(),
+                ctx.accounts.treasury_wallet.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+                &ctx.accounts.player_referral,
+                ctx.accounts.referrer_referral.as_mut(),
+                global_data.referral_share_bps,
+                fee
+            )?;
+            if let Some(referral_fee) = referral_fee {
+                emit_cpi!(referral_fee);
+            }
+
+            // Request randomness.
+            let commit_slot = randomness::request(
+                global_data,
+                RequestAccounts {
+                    payer: ctx.accounts.joiner.to_account_info(),
+                    random: ctx.accounts.random.to_account_info(),
+                    config: ctx.accounts.config.to_account_info(),
+                    treasury: ctx.accounts.treasury.to_account_info(),
+                    vrf: ctx.accounts.vrf.to_account_info(),
+                    system_program: ctx.accounts.system_program.to_account_info(),
+                },
+                force
+            )?;
+
+            coinflip_pool.joiner_player = ctx.accounts.joiner.key();
+            coinflip_pool.joiner_amount = amount;
+            coinflip_pool.joiner_ata = ctx.accounts.joiner.key();
+            coinflip_pool.joiner_set_number = set_number;
+            coinflip_pool.pool_amount += amount;
+            coinflip_pool.force = force;
+            coinflip_pool.randomness_provider = global_data.randomness_provider;
+            coinflip_pool.random = ctx.accounts.random.key();
+            coinflip_pool.commit_slot = commit_slot;
+            coinflip_pool.join_ts = Clock::get()?.unix_timestamp as u64;
+            coinflip_pool.status = PoolStatus::Processing;
+
+            ctx.accounts.joiner_profile.open(ctx.accounts.joiner.key(), native_mint::ID, ctx.bumps.joiner_profile);
+
+            PoolRegistry::unlist_from(&ctx.accounts.pool_registry, pool_id)?;
+
+            emit_cpi!(PoolJoined {
+                pool_id,
+                pool: coinflip_pool.key(),
+                joiner: coinflip_pool.joiner_player,
+                amount: coinflip_pool.joiner_amount,
+                set_number,
+                fee,
+                pool_amount: coinflip_pool.pool_amount,
+            });
+
+            emit_cpi!(RandomnessRequested {
+                pool_id,
+                pool: coinflip_pool.key(),
+                force,
+                random: ctx.accounts.random.key(),
+                provider: coinflip_pool.randomness_provider,
+            });
+
+            Ok(())
+        }
+
+        pub fn close_sol_coinflip(ctx: Context<CloseSolCoinflip>, pool_id: u64) -> Result<()> {
+            let coinflip_pool = &mut ctx.accounts.coinflip_pool;
+
+            require!(coinflip_pool.creator_player == ctx.accounts.signer.key(), SpinXError::InvalidCreator);
+            require!(coinflip_pool.joiner_player == Pubkey::default(), SpinXError::InvalidClaimStatus);
+            require!(coinflip_pool.status == PoolStatus::Waiting, SpinXError::InvalidPoolStatus);
+
+            let pool_key = coinflip_pool.key();
+            let seeds = &[
+                    SOL_ESCROW_SEED.as_bytes(), pool_key.as_ref(),
+                    &[ctx.bumps.sol_escrow],
+                ];
+            let signer = &[&seeds[..]];
+
+            sol_transfer_with_signer(
+                ctx.accounts.sol_escrow.to_account_info(),
+                ctx.accounts.signer.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+                signer,
+                coinflip_pool.creator_amount
+            )?;
+
+            sweep_sol_escrow(
+                ctx.accounts.sol_escrow.to_account_info(),
+                ctx.accounts.treasury_wallet.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+                signer,
+            )?;
+
+            coinflip_pool.status = PoolStatus::Closed;
+            coinflip_pool.pool_amount = 0;
+
+            PoolRegistry::unlist_from(&ctx.accounts.pool_registry, pool_id)?;
+
+            emit_cpi!(PoolClosed {
+                pool_id,
+                pool: coinflip_pool.key(),
+                status: PoolStatus::Closed,
+                creator_refund: coinflip_pool.creator_amount,
+                joiner_refund: 0,
+            });
+
+            Ok(())
+        }
+
+        pub fn expire_sol_coinflip(ctx: Context<ExpireSolCoinflip>, pool_id: u64) -> Result<()> {
+            let coinflip_pool = &mut ctx.accounts.coinflip_pool;
+            let now = Clock::get()?.unix_timestamp as u64;
+
+            require!(coinflip_pool.status == PoolStatus::Waiting, SpinXError::InvalidPoolStatus);
+            require!(coinflip_pool.joiner_player == Pubkey::default(), SpinXError::AlreadyJoined);
+            require!(coinflip_pool.is_expired(now), SpinXError::PoolNotExpired);
+
+            let pool_key = coinflip_pool.key();
+            let seeds = &[
+                    SOL_ESCROW_SEED.as_bytes(), pool_key.as_ref(),
+                    &[ctx.bumps.sol_escrow],
+                ];
+            let signer = &[&seeds[..]];
+
+            sol_transfer_with_signer(
+                ctx.accounts.sol_escrow.to_account_info(),
+                ctx.accounts.creator.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+                signer,
+                coinflip_pool.creator_amount
+            )?;
+
+            sweep_sol_escrow(
+                ctx.accounts.sol_escrow.to_account_info(),
+                ctx.accounts.treasury_wallet.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+                signer,
+            )?;
+
+            coinflip_pool.status = PoolStatus::Expired;
+            coinflip_pool.pool_amount = 0;
+
+            PoolRegistry::unlist_from(&ctx.accounts.pool_registry, pool_id)?;
+
+            emit_cpi!(PoolClosed {
+                pool_id,
+                pool: coinflip_pool.key(),
+                status: PoolStatus::Expired,
+                creator_refund: coinflip_pool.creator_amount,
+                joiner_refund: 0,
+            });
+
+            Ok(())
+        }
+
+        pub fn result_sol_coinflip(ctx: Context<ResultSolCoinflip>, pool_id: u64, force: [u8; 32]) -> Result<()> {
+            let coinflip_pool = &mut ctx.accounts.coinflip_pool;
+
+            require!(coinflip_pool.force == force, SpinXError::InvalidForce);
+            require!(coinflip_pool.status == PoolStatus::Processing, SpinXError::InvalidPoolStatus);
+
+            // The joiner committed the randomness, giving it up loses them the game
+            let now = Clock::get()?.unix_timestamp as u64;
+            let forfeited = randomness::forfeited(coinflip_pool.randomness_provider, coinflip_pool.commit_slot, &ctx.accounts.random)?;
+
+            let randomness = if forfeited {
+                0
+            } else {
+                match randomness::read_fulfilled(coinflip_pool.randomness_provider, coinflip_pool.commit_slot, &ctx.accounts.random)? {
+                    Some(randomness) => randomness,
+                    None => return err!(SpinXError::StillProcessing),
+                }
+            };
+
+            msg!("VRF result is: {}", randomness);
+
+            let pool_key = coinflip_pool.key();
+            let seeds = &[
+                    SOL_ESCROW_SEED.as_bytes(), pool_key.as_ref(),
+                    &[ctx.bumps.sol_escrow],
+                ];
+            let signer = &[&seeds[..]];
+
+            coinflip_pool.winner = if forfeited { coinflip_pool.creator_player } else { coinflip_pool.winner_for(randomness) };
+
+            // Rake goes to the treasury and the winner gets the rest
+            let rake = coinflip_pool.rake();
+            let payout = coinflip_pool.pool_amount - rake;
+
+            if rake > 0 {
+                sol_transfer_with_signer(
+                    ctx.accounts.sol_escrow.to_account_info(),
+                    ctx.accounts.treasury_wallet.to_account_info(),
+                    ctx.accounts.system_program.to_account_info(),
+                    signer,
+                    rake
+                )?;
+            }
+
+            let winner = if coinflip_pool.winner == coinflip_pool.joiner_player {
+                ctx.accounts.joiner.to_account_info()
+            } else {
+                ctx.accounts.creator.to_account_info()
+            };
+
+            sol_transfer_with_signer(
+                ctx.accounts.sol_escrow.to_account_info(),
+                winner,
+                ctx.accounts.system_program.to_account_info(),
+                signer,
+                payout
+            )?;
+
+            sweep_sol_escrow(
+                ctx.accounts.sol_escrow.to_account_info(),
+                ctx.accounts.treasury_wallet.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+                signer,
+            )?;
+
+            // Lifetime stats, the winner's profit is the payout less their own stake
+            let creator_won = coinflip_pool.winner == coinflip_pool.creator_player;
+            PlayerProfile::record_on(&ctx.accounts.creator_profile, coinflip_pool.creator_amount, if creator_won { payout } else { 0 }, now, None)?;
+            PlayerProfile::record_on(&ctx.accounts.joiner_profile, coinflip_pool.joiner_amount, if creator_won { 0 } else { payout }, now, None)?;
+
+            coinflip_pool.status = PoolStatus::Finished;
+            coinflip_pool.pool_amount = 0;
+
+            msg!("Coinflip game in room {} has concluded, the winner is {}", pool_id, coinflip_pool.winner.to_string());
+
+            emit_cpi!(PoolSettled {
+                pool_id,
+                pool: coinflip_pool.key(),
+                randomness,
+                result: coinflip_pool.winning_number(),
+                winner: coinflip_pool.winner,
+                loser: if coinflip_pool.winner == coinflip_pool.joiner_player {
+                    coinflip_pool.creator_player
+                } else {
+                    coinflip_pool.joiner_player
+                },
+                pool_amount: payout + rake,
+                payout,
+                rake,
+            });
+
+            Ok(())
+        }
+
+        pub fn refund_sol_coinflip(ctx: Context<RefundSolCoinflip>, pool_id: u64) -> Result<()> {
+            let coinflip_pool = &mut ctx.accounts.coinflip_pool;
+            let now = Clock::get()?.unix_timestamp as u64;
+
+            require!(coinflip_pool.status == PoolStatus::Processing, SpinXError::InvalidPoolStatus);
+            require!(
+                now >= coinflip_pool.refund_deadline(ctx.accounts.global_data.refund_timeout()),
+                SpinXError::RefundNotAvailable
+            );
+
+            require!(
+                !randomness::forfeited(coinflip_pool.randomness_provider, coinflip_pool.commit_slot, &ctx.accounts.random)?,
+                SpinXError::RandomnessForfeited
+            );
+            let fulfilled = randomness::read_fulfilled(coinflip_pool.randomness_provider, coinflip_pool.commit_slot, &ctx.accounts.random)?;
+            require!(fulfilled.is_none(), SpinXError::RandomnessFulfilled);
+
+            let pool_key = coinflip_pool.key();
+            let seeds = &[
+                    SOL_ESCROW_SEED.as_bytes(), pool_key.as_ref(),
+                    &[ctx.bumps.sol_escrow],
+                ];
+            let signer = &[&seeds[..]];
+
+            sol_transfer_with_signer(
+                ctx.accounts.sol_escrow.to_account_info(),
+                ctx.accounts.creator.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+                signer,
+                coinflip_pool.creator_amount
+            )?;
+
+            sol_transfer_with_signer(
+                ctx.accounts.sol_escrow.to_account_info(),
+                ctx.accounts.joiner.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+                signer,
+                coinflip_pool.joiner_amount
+            )?;
+
+            sweep_sol_escrow(
+                ctx.accounts.sol_escrow.to_account_info(),
+                ctx.accounts.treasury_wallet.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+                signer,
+            )?;
+
+            coinflip_pool.status = PoolStatus::Refunded;
+            coinflip_pool.pool_amount = 0;
+
+            msg!("Coinflip game in room {} was refunded", pool_id);
+
+            emit_cpi!(PoolClosed {
+                pool_id,
+                pool: coinflip_pool.key(),
+                status: PoolStatus::Refunded,
+                creator_refund: coinflip_pool.creator_amount,
+                joiner_refund: coinflip_pool.joiner_amount,
+            });
+
+            Ok(())
+        }
+
+        // Jackpot rounds, any number of players deposit into one escrow until end_ts and a
+        // single randomness request picks the winner weighted by deposit. SPL mints only,
+        // limits and fee come from the mint's MintConfig and apply to every deposit.
+        pub fn create_jackpot(ctx: Context<CreateJackpot>, duration: u64) -> Result<()> {
+            let jackpot_round = &mut ctx.accounts.jackpot_round;
+            let global_data = &mut ctx.accounts.global_data;
+
+            require!(!global_data.is_paused(PAUSE_JACKPOT), SpinXError::InstructionPaused);
+            require!(duration > 0, SpinXError::InvalidExpiry);
+
+            check_mint(&ctx.accounts.mint.to_account_info(), ctx.accounts.mint_config.approved_freeze_authority)?;
+
+            let now = Clock::get()?.unix_timestamp as u64;
+
+            jackpot_round.round_id = global_data.next_jackpot_id;
+            global_data.next_jackpot_id += 1;
+
+            jackpot_round.creator = ctx.accounts.creator.key();
+            jackpot_round.mint = ctx.accounts.mint.key();
+            jackpot_round.start_ts = now;
+            // Rounds are bounded by max_pool_ttl like waiting pools
+            jackpot_round.end_ts = global_data.pool_expiry(now, duration)?;
+            jackpot_round.rake_bps = global_data.rake_bps;
+            jackpot_round.status = PoolStatus::Waiting;
+            jackpot_round.bump = ctx.bumps.jackpot_round;
+
+            emit_cpi!(JackpotCreated {
+                round_id: jackpot_round.round_id,
+                round: jackpot_round.key(),
+                creator: jackpot_round.creator,
+                mint: jackpot_round.mint,
+                rake_bps: jackpot_round.rake_bps,
+                end_ts: jackpot_round.end_ts,
+            });
+
+            Ok(())
+        }
+
+        // Adds to the player's entry, the account grows by one entry for a new player
+        pub fn enter_jackpot(ctx: Context<EnterJackpot>, round_id: u64, amount: u64) -> Result<()> {
+            let jackpot_round = &mut ctx.accounts.jackpot_round;
+            let mint_config = &ctx.accounts.mint_config;
+            let fee = mint_config.coinflip_fee;
+
+            require!(!ctx.accounts.global_data.is_paused(PAUSE_JACKPOT), SpinXError::InstructionPaused);
+            require!(jackpot_round.status == PoolStatus::Waiting, SpinXError::InvalidPoolStatus);
+            require!((Clock::get()?.unix_timestamp as u64) < jackpot_round.end_ts, SpinXError::JackpotClosed);
+
+            check_mint(&ctx.accounts.mint.to_account_info(), ctx.accounts.mint_config.approved_freeze_authority)?;
+
+            let net_amount = amount - transfer_fee(&ctx.accounts.mint.to_account_info(), amount)?;
+
+            require!(net_amount >= mint_config.min_amount, SpinXError::AmountTooSmall);
+            require!(mint_config.max_amount == 0 || net_amount <= mint_config.max_amount, SpinXError::AmountTooLarge);
+
+            let player_amount = jackpot_round.deposit(ctx.accounts.player.key(), net_amount)?;
+
+            let referral_fee = pay_fee(
+                ctx.accounts.player.to_account_info(),
+                ctx.accounts.treasury_wallet.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+                &ctx.accounts.player_referral,
+                ctx.accounts.referrer_referral.as_mut(),
+                ctx.accounts.global_data.referral_share_bps,
+                fee
+            )?;
+            if let Some(referral_fee) = referral_fee {
+                emit_cpi!(referral_fee);
+            }
+
+            let cpi_accounts = TransferChecked {
+                from: ctx.accounts.player_ata.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.spl_escrow.to_account_info(),
+                authority: ctx.accounts.player.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
+            token_interface::transfer_checked(cpi_ctx, amount, ctx.accounts.mint.decimals)?;
+
+            emit_cpi!(JackpotEntered {
+                round_id,
+                round: jackpot_round.key(),
+                player: ctx.accounts.player.key(),
+                amount: net_amount,
+                fee,
+                player_amount,
+                total_amount: jackpot_round.total_amount,
+                players: jackpot_round.entries.len() as u16,
+            });
+
+            Ok(())
+        }
+
+        // Permissionless once deposits close, the caller pays for the randomness request.
+        // Never pausable.
+        pub fn draw_jackpot(ctx: Context<DrawJackpot>, round_id: u64, force: [u8; 32]) -> Result<()> {
+            let jackpot_round = &mut ctx.accounts.jackpot_round;
+            let now = Clock::get()?.unix_timestamp as u64;
+
+            require!(jackpot_round.status == PoolStatus::Waiting, SpinXError::InvalidPoolStatus);
+            require!(now >= jackpot_round.end_ts, SpinXError::JackpotNotEnded);
+            require!(jackpot_round.entries.len() >= MIN_JACKPOT_PLAYERS, SpinXError::NotEnoughPlayers);
+
+            // Always ORAO. The drawer would commit and reveal Switchboard randomness, and could
+            // hold back a losing reveal to have the round refunded and played again.
+            randomness::request_orao(
+                RequestAccounts {
+                    payer: ctx.accounts.payer.to_account_info(),
+                    random: ctx.accounts.random.to_account_info(),
+                    config: ctx.accounts.config.to_account_info(),
+                    treasury: ctx.accounts.treasury.to_account_info(),
+                    vrf: ctx.accounts.vrf.to_account_info(),
+                    system_program: ctx.accounts.system_program.to_account_info(),
+                },
+                force
+            )?;
+
+            jackpot_round.force = force;
+            jackpot_round.randomness_provider = RandomnessProvider::Orao;
+            jackpot_round.random = ctx.accounts.random.key();
+            jackpot_round.commit_slot = 0;
+            jackpot_round.draw_ts = now;
+            jackpot_round.drawer = ctx.accounts.payer.key();
+            jackpot_round.status = PoolStatus::Processing;
+
+            emit_cpi!(JackpotDrawn {
+                round_id,
+                round: jackpot_round.key(),
+                force,
+                random: jackpot_round.random,
+                provider: jackpot_round.randomness_provider,
+                total_amount: jackpot_round.total_amount,
+                players: jackpot_round.entries.len() as u16,
+            });
+
+            Ok(())
+        }
+
+        // Pays the pot less rake to the drawn player, whose token account the caller passes as winner_ata
+        pub fn settle_jackpot(ctx: Context<SettleJackpot>, round_id: u64) -> Result<()> {
+            let jackpot_round = &mut ctx.accounts.jackpot_round;
+
+            require!(jackpot_round.status == PoolStatus::Processing, SpinXError::InvalidPoolStatus);
+
+            let randomness = match randomness::read_fulfilled(jackpot_round.randomness_provider, jackpot_round.commit_slot, &ctx.accounts.random)? {
+                Some(randomness) => randomness,
+                None => return err!(SpinXError::StillProcessing),
+            };
+
+            let winner = jackpot_round.winner_for(randomness);
+            require_keys_eq!(ctx.accounts.winner_ata.owner, winner.player, SpinXError::NotWinner);
+
+            let seeds = &[
+                    JACKPOT_SEED.as_bytes(), &round_id.to_le_bytes(),
+                    &[jackpot_round.bump],
+                ];
+            let signer = &[&seeds[..]];
+
+            let rake = jackpot_round.rake();
+            let payout = jackpot_round.total_amount - rake;
+
+            if rake > 0 {
+                let cpi_accounts = TransferChecked {
+                    from: ctx.accounts.spl_escrow.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.treasury_ata.to_account_info(),
+                    authority: jackpot_round.to_account_info(),
+                };
+                let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), cpi_accounts, signer);
+                token_interface::transfer_checked(cpi_ctx, rake, ctx.accounts.mint.decimals)?;
+            }
+
+            let cpi_accounts = TransferChecked {
+                from: ctx.accounts.spl_escrow.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.winner_ata.to_account_info(),
+                authority: jackpot_round.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), cpi_accounts, signer);
+            token_interface::transfer_checked(cpi_ctx, payout, ctx.accounts.mint.decimals)?;
+
+            // Tokens sent to the escrow on top of the deposits go to the treasury
+            sweep_escrow(
+                ctx.accounts.spl_escrow.to_account_info(),
+                ctx.accounts.mint.to_account_info(),
+                ctx.accounts.treasury_ata.to_account_info(),
+                jackpot_round.to_account_info(),
+                ctx.accounts.token_program.to_account_info(),
+                signer,
+                ctx.accounts.mint.decimals
+            )?;
+
+            // Give the escrow rent back to the creator who paid it, the round itself is closed by the account constraint
+            close_escrow(
+                ctx.accounts.spl_escrow.to_account_info(),
+                ctx.accounts.mint.to_account_info(),
+                ctx.accounts.creator.to_account_info(),
+                jackpot_round.to_account_info(),
+                ctx.accounts.token_program.to_account_info(),
+                signer
+            )?;
+
+            jackpot_round.winner = winner.player;
+            jackpot_round.status = PoolStatus::Finished;
+
+            msg!("Jackpot round {} has concluded, the winner is {}", round_id, winner.player.to_string());
+
+            emit_cpi!(JackpotSettled {
+                round_id,
+                round: jackpot_round.key(),
+                randomness,
+                winner: winner.player,
+                winner_amount: winner.amount,
+                total_amount: jackpot_round.total_amount,
+                payout,
+                rake,
+            });
+
+            Ok(())
+        }
+
+        // Permissionless. Refunds the deposits whose token accounts are passed as remaining accounts,
+        // so large rounds can be refunded over several transactions. The round and escrow close with
+        // the last deposit. Never pausable.
+        pub fn refund_jackpot<'info>(ctx: Context<'_, '_, 'info, 'info, RefundJackpot<'info>>, round_id: u64) -> Result<()> {
+            let jackpot_round = &mut ctx.accounts.jackpot_round;
+            let now = Clock::get()?.unix_timestamp as u64;
+
+            require!(
+                jackpot_round.is_refundable(now, ctx.accounts.global_data.refund_timeout()),
+                SpinXError::RefundNotAvailable
+            );
+
+            // A late fulfillment must be settled, otherwise the losers could refund instead
+            if jackpot_round.status == PoolStatus::Processing {
+                let fulfilled = randomness::read_fulfilled(jackpot_round.randomness_provider, jackpot_round.commit_slot, &ctx.accounts.random)?;
+                require!(fulfilled.is_none(), SpinXError::RandomnessFulfilled);
+            }
+
+            jackpot_round.status = PoolStatus::Refunded;
+
+            let seeds = &[
+                    JACKPOT_SEED.as_bytes(), &round_id.to_le_bytes(),
+                    &[jackpot_round.bump],
+                ];
+            let signer = &[&seeds[..]];
+
+            for player_account in ctx.remaining_accounts {
+                let player_ata = InterfaceAccount::<TokenAccount>::try_from(player_account)?;
+                require_keys_eq!(player_ata.mint, jackpot_round.mint, SpinXError::TokenNotAllowed);
+
+                let entry = jackpot_round.entries
+                    .iter_mut()
+                    .find(|entry| entry.player == player_ata.owner && entry.amount > 0)
+                    .ok_or(SpinXError::NotInJackpot)?;
+                let (player, amount) = (entry.player, entry.amount);
+                entry.amount = 0;
+                jackpot_round.total_amount -= amount;
+
+                let cpi_accounts = TransferChecked {
+                    from: ctx.accounts.spl_escrow.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: player_account.clone(),
+                    authority: jackpot_round.to_account_info(),
+                };
+                let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), cpi_accounts, signer);
+                token_interface::transfer_checked(cpi_ctx, amount, ctx.accounts.mint.decimals)?;
+
+                emit_cpi!(JackpotRefunded {
+                    round_id,
+                    round: jackpot_round.key(),
+                    player,
+                    amount,
+                    remaining_amount: jackpot_round.total_amount,
+                });
+            }
+
+            if jackpot_round.total_amount == 0 {
+                // Tokens sent to the escrow go to the treasury
+                sweep_escrow(
+                    ctx.accounts.spl_escrow.to_account_info(),
+                    ctx.accounts.mint.to_account_info(),
+                    ctx.accounts.treasury_ata.to_account_info(),
+                    jackpot_round.to_account_info(),
+                    ctx.accounts.token_program.to_account_info(),
+                    signer,
+                    ctx.accounts.mint.decimals
+                )?;
+                close_escrow(
+                    ctx.accounts.spl_escrow.to_account_info(),
+                    ctx.accounts.mint.to_account_info(),
+                    ctx.accounts.creator.to_account_info(),
+                    jackpot_round.to_account_info(),
+                    ctx.accounts.token_program.to_account_info(),
+                    signer
+                )?;
+                jackpot_round.close(ctx.accounts.creator.to_account_info())?;
+
+                msg!("Jackpot round {} was refunded", round_id);
+            }
+
+            Ok(())
+        }
+
+        // House vault, a per-mint bankroll that takes the other side of flip_vs_house.
+        // Anyone can provide liquidity for LP shares, the admin sets the edge and limits.
+        // Limits and fee of the mint's MintConfig apply to every flip.
+        pub fn create_house_vault(ctx: Context<CreateHouseVault>, edge_bps: u16, max_bet_bps: u16, withdrawal_delay: u64) -> Result<()> {
+            let house_vault = &mut ctx.accounts.house_vault;
+
+            HouseVault::check_config(edge_bps, max_bet_bps, withdrawal_delay)?;
+            check_mint(&ctx.accounts.mint.to_account_info(), ctx.accounts.mint_config.approved_freeze_authority)?;
+
+            house_vault.mint = ctx.accounts.mint.key();
+            house_vault.edge_bps = edge_bps;
+            house_vault.max_bet_bps = max_bet_bps;
+            house_vault.share_mint = ctx.accounts.share_mint.key();
+            house_vault.withdrawal_delay = withdrawal_delay;
+            house_vault.bump = ctx.bumps.house_vault;
+
+            emit_cpi!(HouseVaultCreated {
+                admin: ctx.accounts.admin.key(),
+                mint: house_vault.mint,
+                house_vault: house_vault.key(),
+                share_mint: house_vault.share_mint,
+                edge_bps,
+                max_bet_bps,
+                withdrawal_delay,
+            });
+
+            Ok(())
+        }
+
+        // Applies to flips made and withdrawals requested afterwards
+        pub fn update_house_vault(ctx: Context<UpdateHouseVault>, edge_bps: u16, max_bet_bps: u16, withdrawal_delay: u64) -> Result<()> {
+            let house_vault = &mut ctx.accounts.house_vault;
+
+            HouseVault::check_config(edge_bps, max_bet_bps, withdrawal_delay)?;
+
+            emit_cpi!(HouseVaultUpdated {
+                admin: ctx.accounts.admin.key(),
+                mint: house_vault.mint,
+                old_edge_bps: house_vault.edge_bps,
+                new_edge_bps: edge_bps,
+                old_max_bet_bps: house_vault.max_bet_bps,
+                new_max_bet_bps: max_bet_bps,
+                old_withdrawal_delay: house_vault.withdrawal_delay,
+                new_withdrawal_delay: withdrawal_delay,
+            });
+
+            house_vault.edge_bps = edge_bps;
+            house_vault.max_bet_bps = max_bet_bps;
+            house_vault.withdrawal_delay = withdrawal_delay;
+
+            Ok(())
+        }
+
+        // Mints shares for what the vault actually receives, priced on the balance before the deposit
+        pub fn deposit_house_liquidity(ctx: Context<DepositHouseLiquidity>, amount: u64) -> Result<()> {
+            let house_vault = &ctx.accounts.house_vault;
+
+            require!(!ctx.accounts.global_data.is_paused(PAUSE_HOUSE), SpinXError::InstructionPaused);
+
+            let balance = ctx.accounts.vault_ata.amount;
+            let share_supply = ctx.accounts.share_mint.supply;
+
+            let cpi_accounts = TransferChecked {
+                from: ctx.accounts.provider_ata.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.vault_ata.to_account_info(),
+                authority: ctx.accounts.provider.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
+            token_interface::transfer_checked(cpi_ctx, amount, ctx.accounts.mint.decimals)?;
+
+            ctx.accounts.vault_ata.reload()?;
+            let net_amount = ctx.accounts.vault_ata.amount - balance;
+
+            let shares = house_vault.shares_for_deposit(net_amount, balance, share_supply);
+            require!(shares > 0, SpinXError::InvalidAmount);
+
+            let seeds = &[
+                    HOUSE_VAULT_SEED.as_bytes(), house_vault.mint.as_ref(),
+                    &[house_vault.bump],
+                ];
+            let signer = &[&seeds[..]];
+
+            let cpi_accounts = MintTo {
+                mint: ctx.accounts.share_mint.to_account_info(),
+                to: ctx.accounts.provider_shares.to_account_info(),
+                authority: house_vault.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), cpi_accounts, signer);
+            token_interface::mint_to(cpi_ctx, shares)?;
+
+            emit_cpi!(HouseLiquidityDeposited {
+                mint: house_vault.mint,
+                provider: ctx.accounts.provider.key(),
+                amount: net_amount,
+                shares,
+                balance: ctx.accounts.vault_ata.amount,
+                locked_amount: house_vault.locked_amount,
+                share_supply: share_supply + shares,
+            });
+
+            Ok(())
+        }
+
+        // Starts the withdrawal delay, one request per LP at a time.
+        // The shares move to the vault, so they can't be requested and sold or requested twice.
+        pub fn request_house_withdrawal(ctx: Context<RequestHouseWithdrawal>, shares: u64) -> Result<()> {
+            let house_withdrawal = &mut ctx.accounts.house_withdrawal;
+            let house_vault = &ctx.accounts.house_vault;
+
+            require!(shares > 0, SpinXError::InvalidAmount);
+            require!(shares <= ctx.accounts.provider_shares.amount, SpinXError::InsufficientFunds);
+
+            let cpi_accounts = TransferChecked {
+                from: ctx.accounts.provider_shares.to_account_info(),
+                mint: ctx.accounts.share_mint.to_account_info(),
+                to: ctx.accounts.vault_shares.to_account_info(),
+                authority: ctx.accounts.provider.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
+            token_interface::transfer_checked(cpi_ctx, shares, ctx.accounts.share_mint.decimals)?;
+
+            house_withdrawal.house_vault = house_vault.key();
+            house_withdrawal.owner = ctx.accounts.provider.key();
+            house_withdrawal.shares = shares;
+            house_withdrawal.ready_ts = Clock::get()?.unix_timestamp as u64 + house_vault.withdrawal_delay;
+            house_withdrawal.expiry_ts = house_withdrawal.ready_ts + HOUSE_WITHDRAWAL_WINDOW;
+            house_withdrawal.bump = ctx.bumps.house_withdrawal;
+
+            emit_cpi!(HouseWithdrawalRequested {
+                mint: house_vault.mint,
+                provider: house_withdrawal.owner,
+                shares,
+                ready_ts: house_withdrawal.ready_ts,
+                expiry_ts: house_withdrawal.expiry_ts,
+            });
+
+            Ok(())
+        }
+
+        // Gives the held shares back
+        pub fn cancel_house_withdrawal(ctx: Context<CancelHouseWithdrawal>) -> Result<()> {
+            let house_vault = &ctx.accounts.house_vault;
+            let shares = ctx.accounts.house_withdrawal.shares;
+
+            let seeds = &[
+                    HOUSE_VAULT_SEED.as_bytes(), house_vault.mint.as_ref(),
+                    &[house_vault.bump],
+                ];
+            let signer = &[&seeds[..]];
+
+            let cpi_accounts = TransferChecked {
+                from: ctx.accounts.vault_shares.to_account_info(),
+                mint: ctx.accounts.share_mint.to_account_info(),
+                to: ctx.accounts.provider_shares.to_account_info(),
+                authority: house_vault.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), cpi_accounts, signer);
+            token_interface::transfer_checked(cpi_ctx, shares, ctx.accounts.share_mint.decimals)?;
+
+            emit_cpi!(HouseWithdrawalCancelled {
+                mint: house_vault.mint,
+                provider: ctx.accounts.provider.key(),
+                shares,
+            });
+
+            Ok(())
+        }
+
+        // Anyone can drop a request left unused past its window, the shares go back to the LP.
+        // Never pausable.
+        pub fn expire_house_withdrawal(ctx: Context<ExpireHouseWithdrawal>) -> Result<()> {
+            let house_vault = &ctx.accounts.house_vault;
+            let shares = ctx.accounts.house_withdrawal.shares;
+
+            require!(
+                Clock::get()?.unix_timestamp as u64 >= ctx.accounts.house_withdrawal.expiry_ts,
+                SpinXError::WithdrawalNotExpired
+            );
+
+            let seeds = &[
+                    HOUSE_VAULT_SEED.as_bytes(), house_vault.mint.as_ref(),
+                    &[house_vault.bump],
+                ];
+            let signer = &[&seeds[..]];
+
+            let cpi_accounts = TransferChecked {
+                from: ctx.accounts.vault_shares.to_account_info(),
+                mint: ctx.accounts.share_mint.to_account_info(),
+                to: ctx.accounts.provider_shares.to_account_info(),
+                authority: house_vault.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), cpi_accounts, signer);
+            token_interface::transfer_checked(cpi_ctx, shares, ctx.accounts.share_mint.decimals)?;
+
+            emit_cpi!(HouseWithdrawalCancelled {
+                mint: house_vault.mint,
+                provider: ctx.accounts.provider.key(),
+                shares,
+            });
+
+            Ok(())
+        }
+
+        // Burns the held shares for their value at completion, never pausable.
+        // Only the free balance can leave, so pending flips stay covered.
+        pub fn withdraw_house_liquidity(ctx: Context<WithdrawHouseLiquidity>) -> Result<()> {
+            let house_vault = &ctx.accounts.house_vault;
+            let house_withdrawal = &ctx.accounts.house_withdrawal;
+            let shares = house_withdrawal.shares;
+            let balance = ctx.accounts.vault_ata.amount;
+            let share_supply = ctx.accounts.share_mint.supply;
+            let now = Clock::get()?.unix_timestamp as u64;
+
+            require!(now >= house_withdrawal.ready_ts, SpinXError::WithdrawalNotReady);
+            require!(now < house_withdrawal.expiry_ts, SpinXError::WithdrawalExpired);
+
+            let amount = house_vault.amount_for_shares(shares, balance, share_supply);
+            require!(amount <= house_vault.free_amount(balance), SpinXError::InsufficientFunds);
+
+            let seeds = &[
+                    HOUSE_VAULT_SEED.as_bytes(), house_vault.mint.as_ref(),
+                    &[house_vault.bump],
+                ];
+            let signer = &[&seeds[..]];
+
+            let cpi_accounts = Burn {
+                mint: ctx.accounts.share_mint.to_account_info(),
+                from: ctx.accounts.vault_shares.to_account_info(),
+                authority: house_vault.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), cpi_accounts, signer);
+            token_interface::burn(cpi_ctx, shares)?;
+
+            let cpi_accounts = TransferChecked {
+                from: ctx.accounts.vault_ata.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.provider_ata.to_account_info(),
+                authority: house_vault.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), cpi_accounts, signer);
+            token_interface::transfer_checked(cpi_ctx, amount, ctx.accounts.mint.decimals)?;
+
+            emit_cpi!(HouseLiquidityWithdrawn {
+                mint: house_vault.mint,
+                provider: ctx.accounts.provider.key(),
+                amount,
+                shares,
+                balance: balance - amount,
+                locked_amount: house_vault.locked_amount,
+                share_supply: share_supply - shares,
+            });
+
+            Ok(())
+        }
+
+        // The player's stake is matched from the vault right away, randomness settles it like a joined pool
+        pub fn flip_vs_house(ctx: Context<FlipVsHouse>, set_number: u8, amount: u64, force: [u8; 32]) -> Result<()> {
+            let house_vault = &mut ctx.accounts.house_vault;
+            let house_flip = &mut ctx.accounts.house_flip;
+            let global_data = &ctx.accounts.global_data;
+            let mint_config = &ctx.accounts.mint_config;
+            let fee = mint_config.coinflip_fee;
+
+            require!(!global_data.is_paused(PAUSE_HOUSE), SpinXError::InstructionPaused);
+            require!(set_number < 2, SpinXError::InvalidNumber);
+
+            check_mint(&ctx.accounts.mint.to_account_info(), ctx.accounts.mint_config.approved_freeze_authority)?;
+
+            let net_amount = amount - transfer_fee(&ctx.accounts.mint.to_account_info(), amount)?;
+
+            require!(net_amount >= mint_config.min_amount, SpinXError::AmountTooSmall);
+            require!(mint_config.max_amount == 0 || net_amount <= mint_config.max_amount, SpinXError::AmountTooLarge);
+            // Measured before the stake arrives, so one flip never risks more than max_bet_bps of the bankroll
+            require!(net_amount <= house_vault.max_bet(ctx.accounts.vault_ata.amount), SpinXError::AmountTooLarge);
+
+            let referral_fee = pay_fee(
+                ctx.accounts.player.to_account_info(),
+                ctx.accounts.treasury_wallet.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+                &ctx.accounts.player_referral,
+                ctx.accounts.referrer_referral.as_mut(),
+                ctx.accounts.global_data.referral_share_bps,
+                fee
+            )?;
+            if let Some(referral_fee) = referral_fee {
+                emit_cpi!(referral_fee);
+            }
+
+            let cpi_accounts = TransferChecked {
+                from: ctx.accounts.player_ata.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.vault_ata.to_account_info(),
+                authority: ctx.accounts.player.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
+            token_interface::transfer_checked(cpi_ctx, amount, ctx.accounts.mint.decimals)?;
+
+            let commit_slot = randomness::request(
+                global_data,
+                RequestAccounts {
+                    payer: ctx.accounts.player.to_account_info(),
+                    random: ctx.accounts.random.to_account_info(),
+                    config: ctx.accounts.config.to_account_info(),
+                    treasury: ctx.accounts.treasury.to_account_info(),
+                    vrf: ctx.accounts.vrf.to_account_info(),
+                    system_program: ctx.accounts.system_program.to_account_info(),
+                },
+                force
+            )?;
+
+            house_flip.flip_id = house_vault.next_flip_id;
+            house_flip.house_vault = house_vault.key();
+            house_flip.player = ctx.accounts.player.key();
+            house_flip.mint = house_vault.mint;
+            house_flip.amount = net_amount;
+            house_flip.set_number = set_number;
+            house_flip.payout = house_vault.payout_for(net_amount);
+            house_flip.force = force;
+            house_flip.randomness_provider = global_data.randomness_provider;
+            house_flip.random = ctx.accounts.random.key();
+            house_flip.commit_slot = commit_slot;
+            house_flip.flip_ts = Clock::get()?.unix_timestamp as u64;
+            house_flip.bump = ctx.bumps.house_flip;
+
+            house_vault.next_flip_id += 1;
+            house_vault.locked_amount += house_flip.payout;
+
+            emit_cpi!(HouseFlipCreated {
+                flip_id: house_flip.flip_id,
+                flip: house_flip.key(),
+                mint: house_flip.mint,
+                player: house_flip.player,
+                amount: net_amount,
+                set_number,
+                fee,
+                payout: house_flip.payout,
+                force,
+                random: house_flip.random,
+                provider: house_flip.randomness_provider,
+            });
+
+            Ok(())
+        }
+
+        // Permissionless, the flip account's rent goes back to the player
+        pub fn settle_house_flip(ctx: Context<SettleHouseFlip>, flip_id: u64) -> Result<()> {
+            let house_vault = &mut ctx.accounts.house_vault;
+            let house_flip = &ctx.accounts.house_flip;
+
+            // The player committed the randomness, giving it up loses them the flip
+            let forfeited = randomness::forfeited(house_flip.randomness_provider, house_flip.commit_slot, &ctx.accounts.random)?;
+
+            let randomness = if forfeited {
+                0
+            } else {
+                match randomness::read_fulfilled(house_flip.randomness_provider, house_flip.commit_slot, &ctx.accounts.random)? {
+                    Some(randomness) => randomness,
+                    None => return err!(SpinXError::StillProcessing),
+                }
+            };
+
+            let player_won = !forfeited && house_flip.player_wins(randomness);
+
+            if player_won {
+                let seeds = &[
+                        HOUSE_VAULT_SEED.as_bytes(), house_vault.mint.as_ref(),
+                        &[house_vault.bump],
+                    ];
+                let signer = &[&seeds[..]];
+
+                let cpi_accounts = TransferChecked {
+                    from: ctx.accounts.vault_ata.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.player_ata.to_account_info(),
+                    authority: house_vault.to_account_info(),
+                };
+                let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), cpi_accounts, signer);
+                token_interface::transfer_checked(cpi_ctx, house_flip.payout, ctx.accounts.mint.decimals)?;
+            }
+
+            house_vault.locked_amount -= house_flip.payout;
+
+            msg!("House flip {} has concluded, the player {}", flip_id, if player_won { "won" } else { "lost" });
+
+            emit_cpi!(HouseFlipSettled {
+                flip_id,
+                flip: house_flip.key(),
+                mint: house_flip.mint,
+                player: house_flip.player,
+                randomness,
+                result: if forfeited { house_flip.set_number ^ 1 } else { (randomness % 2) as u8 },
+                player_won,
+                amount: house_flip.amount,
+                payout: if player_won { house_flip.payout } else { 0 },
+            });
+
+            Ok(())
+        }
+
+        // Anyone can return the stake once randomness has been pending for refund_timeout.
+        // Never pausable.
+        pub fn refund_house_flip(ctx: Context<RefundHouseFlip>, flip_id: u64) -> Result<()> {
+            let house_vault = &mut ctx.accounts.house_vault;
+            let house_flip = &ctx.accounts.house_flip;
+            let now = Clock::get()?.unix_timestamp as u64;
+
+            require!(
+                now >= house_flip.refund_deadline(ctx.accounts.global_data.refund_timeout()),
+                SpinXError::RefundNotAvailable
+            );
+
+            // A late fulfillment or a forfeit must be settled, otherwise a losing player could refund instead
+            require!(
+                !randomness::forfeited(house_flip.randomness_provider, house_flip.commit_slot, &ctx.accounts.random)?,
+                SpinXError::RandomnessForfeited
+            );
+            let fulfilled = randomness::read_fulfilled(house_flip.randomness_provider, house_flip.commit_slot, &ctx.accounts.random)?;
+            require!(fulfilled.is_none(), SpinXError::RandomnessFulfilled);
+
+            let seeds = &[
+                    HOUSE_VAULT_SEED.as_bytes(), house_vault.mint.as_ref(),
+                    &[house_vault.bump],
+                ];
+            let signer = &[&seeds[..]];
+
+            let cpi_accounts = TransferChecked {
+                from: ctx.accounts.vault_ata.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.player_ata.to_account_info(),
+                authority: house_vault.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), cpi_accounts, signer);
+            token_interface::transfer_checked(cpi_ctx, house_flip.amount, ctx.accounts.mint.decimals)?;
+
+            house_vault.locked_amount -= house_flip.payout;
+
+            msg!("House flip {} was refunded", flip_id);
+
+            emit_cpi!(HouseFlipRefunded {
+                flip_id,
+                flip: house_flip.key(),
+                mint: house_flip.mint,
+                player: house_flip.player,
+                amount: house_flip.amount,
+            });
+
+            Ok(())
+        }
+
+        // Registers the signer for referrals, so other players can bind them as their referrer
+        pub fn register_referral(ctx: Context<RegisterReferral>) -> Result<()> {
+            let player_referral = &mut ctx.accounts.player_referral;
+
+            player_referral.register(&mut ctx.accounts.global_data, ctx.accounts.player.key(), ctx.bumps.player_referral);
+
+            emit_cpi!(ReferralRegistered {
+                player: player_referral.player,
+                referral_id: player_referral.referral_id,
+            });
+
+            Ok(())
+        }
+
+        // Binds the signer's referrer, once. Registers the signer first if they haven't yet.
+        pub fn bind_referrer(ctx: Context<BindReferrer>, referrer: Pubkey) -> Result<()> {
+            let player = ctx.accounts.player.key();
+            let player_referral = &mut ctx.accounts.player_referral;
+            let referrer_referral = &mut ctx.accounts.referrer_referral;
+
+            require_keys_neq!(referrer, player, SpinXError::SelfReferral);
+            require!(player_referral.referrer == Pubkey::default(), SpinXError::ReferrerAlreadyBound);
+
+            if !player_referral.is_registered() {
+                player_referral.register(&mut ctx.accounts.global_data, player, ctx.bumps.player_referral);
+            }
+
+            // Referrals only point back in registration order, so no chain of them can loop
+            require!(referrer_referral.referral_id < player_referral.referral_id, SpinXError::ReferralCycle);
+
+            player_referral.referrer = referrer;
+            referrer_referral.referred_players += 1;
+
+            emit_cpi!(ReferrerBound {
+                player,
+                referrer,
+            });
+
+            Ok(())
+        }
+
+        // Pays out the fee shares accrued as a referrer, the account keeps its rent. Never pausable.
+        pub fn claim_referral_rewards(ctx: Context<ClaimReferralRewards>) -> Result<()> {
+            let player_referral = &mut ctx.accounts.player_referral;
+            let amount = player_referral.pending_rewards;
+
+            require!(amount > 0, SpinXError::NoReferralRewards);
+
+            player_referral.pending_rewards = 0;
+            player_referral.sub_lamports(amount)?;
+            ctx.accounts.player.add_lamports(amount)?;
+
+            emit_cpi!(ReferralRewardsClaimed {
+                referrer: player_referral.player,
+                amount,
+            });
+
+            Ok(())
+        }
+
+        // Seasons rank the players of one mint's coinflip pools, funded by a share of their rake.
+        // Native SOL pools pay their rake in lamports and take no part.
+        pub fn create_season(ctx: Context<CreateSeason>, start_ts: u64, end_ts: u64, metric: SeasonMetric, prize_share_bps: u16, payout_bps: Vec<u16>) -> Result<()> {
+            let season = &mut ctx.accounts.season;
+            let global_data = &mut ctx.accounts.global_data;
+
+            Season::check_config(start_ts, end_ts, prize_share_bps, &payout_bps)?;
+            require!(end_ts > Clock::get()?.unix_timestamp as u64, SpinXError::InvalidSeasonConfig);
+
+            global_data.last_season_id += 1;
+
+            season.season_id = global_data.last_season_id;
+            season.mint = ctx.accounts.mint.key();
+            season.start_ts = start_ts;
+            season.end_ts = end_ts;
+            season.metric = metric;
+            season.prize_share_bps = prize_share_bps;
+            season.payout_bps = payout_bps;
+            season.bump = ctx.bumps.season;
+
+            emit_cpi!(SeasonCreated {
+                season_id: season.season_id,
+                season: season.key(),
+                mint: season.mint,
+                start_ts,
+                end_ts,
+                metric,
+                prize_share_bps,
+                payout_bps: season.payout_bps.clone(),
+            });
+
+            Ok(())
+        }
+
+        // Permissionless once the season has ended, locks the rankings and the prize
+        pub fn finalize_season(ctx: Context<FinalizeSeason>) -> Result<()> {
+            let season = &mut ctx.accounts.season;
+
+            require!(!season.finalized, SpinXError::SeasonFinalized);
+            require!(Clock::get()?.unix_timestamp as u64 >= season.end_ts, SpinXError::SeasonNotEnded);
+
+            season.finalize(ctx.accounts.season_vault.amount);
+
+            emit_cpi!(SeasonFinalized {
+                season_id: season.season_id,
+                season: season.key(),
+                prize_amount: season.prize_amount,
+                winners: season.rankings.iter().map(|entry| entry.player).collect(),
+            });
+
+            Ok(())
+        }
+
+        // Pays a ranked player their rank's share of the prize, once
+        pub fn claim_season_prize(ctx: Context<ClaimSeasonPrize>) -> Result<()> {
+            let season = &mut ctx.accounts.season;
+            let player = ctx.accounts.player.key();
+
+            require!(season.finalized, SpinXError::SeasonNotFinalized);
+
+            let rank = season.rankings
+                .iter()
+                .position(|entry| entry.player == player)
+                .ok_or(SpinXError::NoSeasonPrize)?;
+            let amount = season.prize_of(rank);
+            require!(amount > 0 && !season.rankings[rank].claimed, SpinXError::NoSeasonPrize);
+
+            season.rankings[rank].claimed = true;
+
+            let seeds = &[
+                    SEASON_SEED.as_bytes(), season.mint.as_ref(),
+                    &[season.bump],
+                ];
+            let signer = &[&seeds[..]];
+
+            let cpi_accounts = TransferChecked {
+                from: ctx.accounts.season_vault.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.player_ata.to_account_info(),
+                authority: season.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), cpi_accounts, signer);
+            token_interface::transfer_checked(cpi_ctx, amount, ctx.accounts.mint.decimals)?;
+
+            emit_cpi!(SeasonPrizeClaimed {
+                season_id: season.season_id,
+                player,
+                rank: rank as u8,
+                amount,
+            });
+
+            Ok(())
+        }
+
+        // Once every prize is claimed, or the claim window is over, the rest of the vault goes to
+        // the treasury and the mint is free for the next season
+        pub fn close_season(ctx: Context<CloseSeason>) -> Result<()> {
+            let season = &ctx.accounts.season;
+            let now = Clock::get()?.unix_timestamp as u64;
+
+            require!(season.finalized, SpinXError::SeasonNotFinalized);
+            require!(
+                season.is_settled() || now >= season.end_ts + SEASON_CLAIM_WINDOW,
+                SpinXError::SeasonClaimsOpen
+            );
+
+            let seeds = &[
+                    SEASON_SEED.as_bytes(), season.mint.as_ref(),
+                    &[season.bump],
+                ];
+            let signer = &[&seeds[..]];
+
+            let swept_amount = ctx.accounts.season_vault.amount;
+            if swept_amount > 0 {
+                let cpi_accounts = TransferChecked {
+                    from: ctx.accounts.season_vault.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.treasury_ata.to_account_info(),
+                    authority: season.to_account_info(),
+                };
+                let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), cpi_accounts, signer);
+                token_interface::transfer_checked(cpi_ctx, swept_amount, ctx.accounts.mint.decimals)?;
+            }
+
+            // Give the vault rent back to the admin who paid it, the season itself is closed by the account constraint
+            close_escrow(
+                ctx.accounts.season_vault.to_account_info(),
+                ctx.accounts.mint.to_account_info(),
+                ctx.accounts.admin.to_account_info(),
+                season.to_account_info(),
+                ctx.accounts.token_program.to_account_info(),
+                signer
+            )?;
+
+            emit_cpi!(SeasonClosed {
+                season_id: season.season_id,
+                season: season.key(),
+                swept_amount,
+            });
+
+            Ok(())
+        }
+
+        // Test authority of the mock provider, the admin decides the outcome.
+        #[cfg(feature = "mock-vrf")]
+        pub fn mock_fulfill(ctx: Context<MockFulfill>, force: [u8; 32], randomness: [u8; 64]) -> Result<()> {
+            let random = &mut ctx.accounts.random;
+
+            require!(random.force == force, SpinXError::InvalidForce);
+            require!(!random.fulfilled, SpinXError::RandomnessFulfilled);
+
+            random.randomness = randomness;
+            random.fulfilled = true;
+
+            Ok(())
+        }
+    }
+}
+
+pub use handlers::*;
+
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        init,
+        space = 8 + std::mem::size_of::<GlobalData>(),
+        seeds = [GLOBAL_AUTHORITY_SEED.as_bytes()],
+        bump,
+        payer = admin
+    )]
+    pub global_data: Box<Account<'info, GlobalData>>,
+
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct UpdateGlobalData<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [GLOBAL_AUTHORITY_SEED.as_bytes()],
+        bump,
+        constraint = global_data.super_admin == admin.key() @ SpinXError::InvalidAdmin
+    )]
+    pub global_data: Box<Account<'info, GlobalData>>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+    pub new_admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [GLOBAL_AUTHORITY_SEED.as_bytes()],
+        bump,
+        constraint = global_data.pending_admin != Pubkey::default() @ SpinXError::NoPendingAdmin,
+        constraint = global_data.pending_admin == new_admin.key() @ SpinXError::InvalidAdmin
+    )]
+    pub global_data: Box<Account<'info, GlobalData>>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct Pause<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [GLOBAL_AUTHORITY_SEED.as_bytes()],
+        bump,
+        constraint = 
+            (global_data.super_admin == authority.key() ||
+            (global_data.pauser != Pubkey::default() && global_data.pauser == authority.key())) @ SpinXError::InvalidPauser
+    )]
+    pub global_data: Box<Account<'info, GlobalData>>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct MigrateGlobalData<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    /// CHECK: may still have an older, shorter layout, so discriminator and admin are checked by hand
+    #[account(
+        mut,
+        seeds = [GLOBAL_AUTHORITY_SEED.as_bytes()],
+        bump,
+        owner = crate::ID
+    )]
+    pub global_data: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct AddMint<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [GLOBAL_AUTHORITY_SEED.as_bytes()],
+        bump,
+        constraint = global_data.super_admin == admin.key() @ SpinXError::InvalidAdmin
+    )]
+    pub global_data: Box<Account<'info, GlobalData>>,
+
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        init,
+        space = 8 + std::mem::size_of::<MintConfig>(),
+        seeds = [MINT_CONFIG_SEED.as_bytes(), mint.key().as_ref()],
+        bump,
+        payer = admin
+    )]
+    pub mint_config: Box<Account<'info, MintConfig>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct UpdateMintConfig<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [GLOBAL_AUTHORITY_SEED.as_bytes()],
+        bump,
+        constraint = global_data.super_admin == admin.key() @ SpinXError::InvalidAdmin
+    )]
+    pub global_data: Box<Account<'info, GlobalData>>,
+
+    #[account(
+        mut,
+        seeds = [MINT_CONFIG_SEED.as_bytes(), mint_config.mint.as_ref()],
+        bump = mint_config.bump
+    )]
+    pub mint_config: Box<Account<'info, MintConfig>>,
+}
+
+#[derive(Accounts)]
+#[instruction(pool_id: u64)]
+pub struct MigrateCoinflipPool<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        seeds = [GLOBAL_AUTHORITY_SEED.as_bytes()],
+        bump
+    )]
+    pub global_data: Box<Account<'info, GlobalData>>,
+
+    /// CHECK: may still have an older, shorter layout, so the discriminator is checked by hand
+    #[account(
+        mut,
+        seeds = [COINFLIP_SEED.as_bytes(), pool_id.to_le_bytes().as_ref()],
+        bump,
+        owner = crate::ID
+    )]
+    pub coinflip_pool: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(set_number: u8, amount: u64)]
+pub struct CreateCoinflip<'info> {
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [GLOBAL_AUTHORITY_SEED.as_bytes()],
+        bump
+    )]
+    pub global_data: Box<Account<'info, GlobalData>>,
+
+    // Initialize player token account if it doesn't exist
+    #[account(
+        init_if_needed,
+        payer = creator,
+        associated_token::mint = mint,
+        associated_token::token_program = token_program,
+        associated_token::authority = creator
+    )]
+    pub creator_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mint::token_program = token_program)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        seeds = [MINT_CONFIG_SEED.as_bytes(), mint.key().as_ref()],
+        bump = mint_config.bump,
+        constraint = mint_config.enabled @ SpinXError::TokenNotAllowed
+    )]
+    pub mint_config: Box<Account<'info, MintConfig>>,
+
+    #[account(
+        init,
+        space = 8 + std::mem::size_of::<CoinflipPool>(),
+        seeds = [COINFLIP_SEED.as_bytes(), global_data.next_pool_id.to_le_bytes().as_ref()],
+        bump,
+        payer = creator
+    )]
+    pub coinflip_pool: Box<Account<'info, CoinflipPool>>,
+
+    #[account(
+        mut,
+        constraint = 
+            treasury_wallet.key() == global_data.treasury_wallet @ SpinXError::OwnerMismatch
+    )]
+    pub treasury_wallet: SystemAccount<'info>,
+
+    /// CHECK: PlayerReferral PDA of the player, only read when it exists. The referrer it names has to be passed too
+    #[account(
+        seeds = [PLAYER_REFERRAL_SEED.as_bytes(), creator.key().as_ref()],
+        bump
+    )]
+    pub player_referral: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub referrer_referral: Option<Account<'info, PlayerReferral>>,
+
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = 8 + std::mem::size_of::<PlayerProfile>(),
+        seeds = [PLAYER_PROFILE_SEED.as_bytes(), creator.key().as_ref(), mint.key().as_ref()],
+        bump
+    )]
+    pub creator_profile: Box<Account<'info, PlayerProfile>>,
+
+    #[account(
+        init,
+        payer = creator,
+        associated_token::mint = mint,
+        associated_token::token_program = token_program,
+        associated_token::authority = coinflip_pool
+    )]
+    pub spl_escrow: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK:` doc comment explaining why no checks through types are necessary.
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+
+    // Lobby shard the pool is listed in, by the stake the escrow receives after any transfer fee
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = PoolRegistry::space(),
+        seeds = [POOL_REGISTRY_SEED.as_bytes(), mint.key().as_ref(), [stake_tier(amount - transfer_fee(&mint.to_account_info(), amount)?)].as_ref()],
+        bump
+    )]
+    pub pool_registry: Box<Account<'info, PoolRegistry>>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(
+    pool_id: u64, force: [u8; 32]
+)]
+pub struct JoinCoinflip<'info> {
+    #[account(mut)]
+    pub joiner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [GLOBAL_AUTHORITY_SEED.as_bytes()],
+        bump
+    )]
+    pub global_data: Box<Account<'info, GlobalData>>,
+
+    // Initialize player token account if it doesn't exist
+    #[account(
+        init_if_needed,
+        payer = joiner,
+        associated_token::mint = mint,
+        associated_token::token_program = token_program,
+        associated_token::authority = joiner
+    )]
+    pub joiner_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        address = coinflip_pool.mint @ SpinXError::TokenNotAllowed,
+        mint::token_program = token_program
+    )]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        seeds = [MINT_CONFIG_SEED.as_bytes(), mint.key().as_ref()],
+        bump = mint_config.bump,
+        constraint = mint_config.enabled @ SpinXError::TokenNotAllowed
+    )]
+    pub mint_config: Box<Account<'info, MintConfig>>,
+
+    #[account(
+        mut,
+        seeds = [COINFLIP_SEED.as_bytes(), pool_id.to_le_bytes().as_ref()],
+        bump,
+        constraint = !coinflip_pool.is_native @ SpinXError::InvalidPoolAsset
+    )]
+    pub coinflip_pool: Account<'info, CoinflipPool>,
+
+    #[account(
+        mut,
+        constraint = 
+            treasury_wallet.key() == global_data.treasury_wallet @ SpinXError::OwnerMismatch
+    )]
+    pub treasury_wallet: SystemAccount<'info>,
+
+    /// CHECK: PlayerReferral PDA of the player, only read when it exists. The referrer it names has to be passed too
+    #[account(
+        seeds = [PLAYER_REFERRAL_SEED.as_bytes(), joiner.key().as_ref()],
+        bump
+    )]
+    pub player_referral: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub referrer_referral: Option<Account<'info, PlayerReferral>>,
+
+    #[account(
+        init_if_needed,
+        payer = joiner,
+        space = 8 + std::mem::size_of::<PlayerProfile>(),
+        seeds = [PLAYER_PROFILE_SEED.as_bytes(), joiner.key().as_ref(), mint.key().as_ref()],
+        bump
+    )]
+    pub joiner_profile: Box<Account<'info, PlayerProfile>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::token_program = token_program,
+        associated_token::authority = coinflip_pool
+    )]
+    pub spl_escrow: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: ORAO randomness account for force or the committed Switchboard one, checked by the provider
+    #[account(mut)]
+    pub random: UncheckedAccount<'info>,
+    /// CHECK: Provider treasury, checked by the provider
+    #[account(mut)]
+    pub treasury: UncheckedAccount<'info>,
+    /// CHECK: Provider config, checked by the provider
+    #[account(mut)]
+    pub config: UncheckedAccount<'info>,
+    /// CHECK: Provider program, checked by the provider
+    pub vrf: UncheckedAccount<'info>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// CHECK: PoolRegistry PDA the pool may be listed in, only updated when it exists
+    #[account(
+        mut,
+        seeds = [POOL_REGISTRY_SEED.as_bytes(), coinflip_pool.mint.as_ref(), [coinflip_pool.stake_tier].as_ref()],
+        bump
+    )]
+    pub pool_registry: UncheckedAccount<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(pool_id: u64, force: [u8; 32])]
+pub struct ResultCoinflip<'info> {
+
+    #[account(
+        mut,
+        close = creator,
+        seeds = [COINFLIP_SEED.as_bytes(), pool_id.to_le_bytes().as_ref()],
+        bump,
+        constraint = !coinflip_pool.is_native @ SpinXError::InvalidPoolAsset
+    )]
+    pub coinflip_pool: Account<'info, CoinflipPool>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::token_program = token_program,
+        associated_token::authority = coinflip_pool
+    )]
+    pub spl_escrow: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        address = coinflip_pool.mint @ SpinXError::TokenNotAllowed,
+        mint::token_program = token_program
+    )]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::token_program = token_program,
+        associated_token::authority = coinflip_pool.creator_player
+    )]
+    pub creator_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::token_program = token_program,
+        associated_token::authority = coinflip_pool.joiner_player
+    )]
+    pub joiner_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        seeds = [GLOBAL_AUTHORITY_SEED.as_bytes()],
+        bump
+    )]
+    pub global_data: Box<Account<'info, GlobalData>>,
+
+    // Receives the rake, any token account of the treasury wallet for this mint
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = global_data.treasury_wallet,
+        token::token_program = token_program
+    )]
+    pub treasury_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: Randomness, read through the provider
+    #[account(
+        address = coinflip_pool.random_account() @ SpinXError::InvalidRandomnessAccount
+    )]
+    pub random: UncheckedAccount<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+
+    #[account(mut, address = coinflip_pool.creator_player @ SpinXError::InvalidCreator)]
+    pub creator: SystemAccount<'info>,
+
+    /// CHECK: PlayerProfile PDA of the creator, only updated when it exists
+    #[account(
+        mut,
+        seeds = [PLAYER_PROFILE_SEED.as_bytes(), coinflip_pool.creator_player.as_ref(), coinflip_pool.mint.as_ref()],
+        bump
+    )]
+    pub creator_profile: UncheckedAccount<'info>,
+
+    /// CHECK: PlayerProfile PDA of the joiner, only updated when it exists
+    #[account(
+        mut,
+        seeds = [PLAYER_PROFILE_SEED.as_bytes(), coinflip_pool.joiner_player.as_ref(), coinflip_pool.mint.as_ref()],
+        bump
+    )]
+    pub joiner_profile: UncheckedAccount<'info>,
+
+    /// CHECK: Season PDA of the mint, only used while a season is live
+    #[account(
+        mut,
+        seeds = [SEASON_SEED.as_bytes(), coinflip_pool.mint.as_ref()],
+        bump
+    )]
+    pub season: UncheckedAccount<'info>,
+
+    /// CHECK: Prize vault of the season, the token program checks it when the season is funded
+    #[account(
+        mut,
+        address = get_associated_token_address_with_program_id(&season.key(), &coinflip_pool.mint, &token_program.key())
+    )]
+    pub season_vault: UncheckedAccount<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(pool_id: u64)]
+pub struct RefundCoinflip<'info> {
+    #[account(
+        mut,
+        close = creator,
+        seeds = [COINFLIP_SEED.as_bytes(), pool_id.to_le_bytes().as_ref()],
+        bump,
+        constraint = !coinflip_pool.is_native @ SpinXError::InvalidPoolAsset
+    )]
+    pub coinflip_pool: Box<Account<'info, CoinflipPool>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::token_program = token_program,
+        associated_token::authority = coinflip_pool
+    )]
+    pub spl_escrow: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        address = coinflip_pool.mint @ SpinXError::TokenNotAllowed,
+        mint::token_program = token_program
+    )]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::token_program = token_program,
+        associated_token::authority = coinflip_pool.creator_player
+    )]
+    pub creator_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::token_program = token_program,
+        associated_token::authority = coinflip_pool.joiner_player
+    )]
+    pub joiner_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        seeds = [GLOBAL_AUTHORITY_SEED.as_bytes()],
+        bump
+    )]
+    pub global_data: Box<Account<'info, GlobalData>>,
+
+    /// CHECK: Randomness, only read to make sure it was never fulfilled
+    #[account(
+        address = coinflip_pool.random_account() @ SpinXError::InvalidRandomnessAccount
+    )]
+    pub random: UncheckedAccount<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    #[account(mut, address = coinflip_pool.creator_player @ SpinXError::InvalidCreator)]
+    pub creator: SystemAccount<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(pool_id: u64)]
+pub struct ExpireCoinflip<'info> {
+    #[account(
+        mut,
+        close = creator,
+        seeds = [COINFLIP_SEED.as_bytes(), pool_id.to_le_bytes().as_ref()],
+        bump,
+        constraint = !coinflip_pool.is_native @ SpinXError::InvalidPoolAsset
+    )]
+    pub coinflip_pool: Box<Account<'info, CoinflipPool>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::token_program = token_program,
+        associated_token::authority = coinflip_pool
+    )]
+    pub spl_escrow: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        address = coinflip_pool.mint @ SpinXError::TokenNotAllowed,
+        mint::token_program = token_program
+    )]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::token_program = token_program,
+        associated_token::authority = coinflip_pool.creator_player
+    )]
+    pub creator_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    #[account(mut, address = coinflip_pool.creator_player @ SpinXError::InvalidCreator)]
+    pub creator: SystemAccount<'info>,
+
+    /// CHECK: PoolRegistry PDA the pool may be listed in, only updated when it exists
+    #[account(
+        mut,
+        seeds = [POOL_REGISTRY_SEED.as_bytes(), coinflip_pool.mint.as_ref(), [coinflip_pool.stake_tier].as_ref()],
+        bump
+    )]
+    pub pool_registry: UncheckedAccount<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(pool_id: u64)]
+pub struct OpenCoinflip<'info> {
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [COINFLIP_SEED.as_bytes(), pool_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub coinflip_pool: Box<Account<'info, CoinflipPool>>,
+
+    // Lobby shard the now public pool is listed in
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = PoolRegistry::space(),
+        seeds = [POOL_REGISTRY_SEED.as_bytes(), coinflip_pool.mint.as_ref(), [coinflip_pool.stake_tier].as_ref()],
+        bump
+    )]
+    pub pool_registry: Box<Account<'info, PoolRegistry>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(pool_id: u64)]
+pub struct CloseCoinflip<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    #[account(
+        mut,
+        close = signer,
+        seeds = [COINFLIP_SEED.as_bytes(), pool_id.to_le_bytes().as_ref()],
+        bump,
+        constraint = !coinflip_pool.is_native @ SpinXError::InvalidPoolAsset
+    )]
+    pub coinflip_pool: Account<'info, CoinflipPool>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::token_program = token_program,
+        associated_token::authority = coinflip_pool
+    )]
+    pub spl_escrow: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        address = coinflip_pool.mint @ SpinXError::TokenNotAllowed,
+        mint::token_program = token_program
+    )]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::token_program = token_program,
+        associated_token::authority = coinflip_pool.creator_player
+    )]
+    pub creator_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK:` doc comment explaining why no checks through types are necessary.
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: PoolRegistry PDA the pool may be listed in, only updated when it exists
+    #[account(
+        mut,
+        seeds = [POOL_REGISTRY_SEED.as_bytes(), coinflip_pool.mint.as_ref(), [coinflip_pool.stake_tier].as_ref()],
+        bump
+    )]
+    pub pool_registry: UncheckedAccount<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(set_number: u8, amount: u64)]
+pub struct CreateSolCoinflip<'info> {
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [GLOBAL_AUTHORITY_SEED.as_bytes()],
+        bump
+    )]
+    pub global_data: Box<Account<'info, GlobalData>>,
+
+    #[account(
+        seeds = [MINT_CONFIG_SEED.as_bytes(), native_mint::ID.as_ref()],
+        bump = mint_config.bump,
+        constraint = mint_config.enabled @ SpinXError::TokenNotAllowed
+    )]
+    pub mint_config: Box<Account<'info, MintConfig>>,
+
+    #[account(
+        init,
+        space = 8 + std::mem::size_of::<CoinflipPool>(),
+        seeds = [COINFLIP_SEED.as_bytes(), global_data.next_pool_id.to_le_bytes().as_ref()],
+        bump,
+        payer = creator
+    )]
+    pub coinflip_pool: Box<Account<'info, CoinflipPool>>,
+
+    #[account(
+        mut,
+        seeds = [SOL_ESCROW_SEED.as_bytes(), coinflip_pool.key().as_ref()],
+        bump
+    )]
+    pub sol_escrow: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        constraint = 
+            treasury_wallet.key() == global_data.treasury_wallet @ SpinXError::OwnerMismatch
+    )]
+    pub treasury_wallet: SystemAccount<'info>,
+
+    /// CHECK: PlayerReferral PDA of the player, only read when it exists. The referrer it names has to be passed too
+    #[account(
+        seeds = [PLAYER_REFERRAL_SEED.as_bytes(), creator.key().as_ref()],
+        bump
+    )]
+    pub player_referral: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub referrer_referral: Option<Account<'info, PlayerReferral>>,
+
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = 8 + std::mem::size_of::<PlayerProfile>(),
+        seeds = [PLAYER_PROFILE_SEED.as_bytes(), creator.key().as_ref(), native_mint::ID.as_ref()],
+        bump
+    )]
+    pub creator_profile: Box<Account<'info, PlayerProfile>>,
+
+    pub system_program: Program<'info, System>,
+
+    // Lobby shard the pool is listed in
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = PoolRegistry::space(),
+        seeds = [POOL_REGISTRY_SEED.as_bytes(), native_mint::ID.as_ref(), [stake_tier(amount)].as_ref()],
+        bump
+    )]
+    pub pool_registry: Box<Account<'info, PoolRegistry>>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(
+    pool_id: u64, force: [u8; 32]
+)]
+pub struct JoinSolCoinflip<'info> {
+    #[account(mut)]
+    pub joiner: Signer<'info>,
+
+    #[account(
+        seeds = [GLOBAL_AUTHORITY_SEED.as_bytes()],
+        bump
+    )]
+    pub global_data: Box<Account<'info, GlobalData>>,
+
+    #[account(
+        seeds = [MINT_CONFIG_SEED.as_bytes(), native_mint::ID.as_ref()],
+        bump = mint_config.bump,
+        constraint = mint_config.enabled @ SpinXError::TokenNotAllowed
+    )]
+    pub mint_config: Box<Account<'info, MintConfig>>,
+
+    #[account(
+        mut,
+        seeds = [COINFLIP_SEED.as_bytes(), pool_id.to_le_bytes().as_ref()],
+        bump,
+        constraint = coinflip_pool.is_native @ SpinXError::InvalidPoolAsset
+    )]
+    pub coinflip_pool: Box<Account<'info, CoinflipPool>>,
+
+    #[account(
+        mut,
+        seeds = [SOL_ESCROW_SEED.as_bytes(), coinflip_pool.key().as_ref()],
+        bump
+    )]
+    pub sol_escrow: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        constraint = 
+            treasury_wallet.key() == global_data.treasury_wallet @ SpinXError::OwnerMismatch
+    )]
+    pub treasury_wallet: SystemAccount<'info>,
+
+    /// CHECK: PlayerReferral PDA of the player, only read when it exists. The referrer it names has to be passed too
+    #[account(
+        seeds = [PLAYER_REFERRAL_SEED.as_bytes(), joiner.key().as_ref()],
+        bump
+    )]
+    pub player_referral: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub referrer_referral: Option<Account<'info, PlayerReferral>>,
+
+    #[account(
+        init_if_needed,
+        payer = joiner,
+        space = 8 + std::mem::size_of::<PlayerProfile>(),
+        seeds = [PLAYER_PROFILE_SEED.as_bytes(), joiner.key().as_ref(), native_mint::ID.as_ref()],
+        bump
+    )]
+    pub joiner_profile: Box<Account<'info, PlayerProfile>>,
+
+    /// CHECK: ORAO randomness account for force or the committed Switchboard one, checked by the provider
+    #[account(mut)]
+    pub random: UncheckedAccount<'info>,
+    /// CHECK: Provider treasury, checked by the provider
+    #[account(mut)]
+    pub treasury: UncheckedAccount<'info>,
+    /// CHECK: Provider config, checked by the provider
+    #[account(mut)]
+    pub config: UncheckedAccount<'info>,
+    /// CHECK: Provider program, checked by the provider
+    pub vrf: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: PoolRegistry PDA the pool may be listed in, only updated when it exists
+    #[account(
+        mut,
+        seeds = [POOL_REGISTRY_SEED.as_bytes(), coinflip_pool.mint.as_ref(), [coinflip_pool.stake_tier].as_ref()],
+        bump
+    )]
+    pub pool_registry: UncheckedAccount<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(pool_id: u64)]
+pub struct CloseSolCoinflip<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    #[account(
+        mut,
+        close = signer,
+        seeds = [COINFLIP_SEED.as_bytes(), pool_id.to_le_bytes().as_ref()],
+        bump,
+        constraint = coinflip_pool.is_native @ SpinXError::InvalidPoolAsset
+    )]
+    pub coinflip_pool: Box<Account<'info, CoinflipPool>>,
+
+    #[account(
+        mut,
+        seeds = [SOL_ESCROW_SEED.as_bytes(), coinflip_pool.key().as_ref()],
+        bump
+    )]
+    pub sol_escrow: SystemAccount<'info>,
+
+    #[account(
+        seeds = [GLOBAL_AUTHORITY_SEED.as_bytes()],
+        bump
+    )]
+    pub global_data: Box<Account<'info, GlobalData>>,
+
+    #[account(
+        mut,
+        constraint = 
+            treasury_wallet.key() == global_data.treasury_wallet @ SpinXError::OwnerMismatch
+    )]
+    pub treasury_wallet: SystemAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: PoolRegistry PDA the pool may be listed in, only updated when it exists
+    #[account(
+        mut,
+        seeds = [POOL_REGISTRY_SEED.as_bytes(), coinflip_pool.mint.as_ref(), [coinflip_pool.stake_tier].as_ref()],
+        bump
+    )]
+    pub pool_registry: UncheckedAccount<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(pool_id: u64, force: [u8; 32])]
+pub struct ResultSolCoinflip<'info> {
+    #[account(
+        mut,
+        close = creator,
+        seeds = [COINFLIP_SEED.as_bytes(), pool_id.to_le_bytes().as_ref()],
+        bump,
+        constraint = coinflip_pool.is_native @ SpinXError::InvalidPoolAsset
+    )]
+    pub coinflip_pool: Box<Account<'info, CoinflipPool>>,
+
+    #[account(
+        mut,
+        seeds = [SOL_ESCROW_SEED.as_bytes(), coinflip_pool.key().as_ref()],
+        bump
+    )]
+    pub sol_escrow: SystemAccount<'info>,
+
+    #[account(mut, address = coinflip_pool.creator_player @ SpinXError::InvalidCreator)]
+    pub creator: SystemAccount<'info>,
+
+    #[account(mut, address = coinflip_pool.joiner_player @ SpinXError::InvalidJoiner)]
+    pub joiner: SystemAccount<'info>,
+
+    #[account(
+        seeds = [GLOBAL_AUTHORITY_SEED.as_bytes()],
+        bump
+    )]
+    pub global_data: Box<Account<'info, GlobalData>>,
+
+    #[account(
+        mut,
+        constraint = 
+            treasury_wallet.key() == global_data.treasury_wallet @ SpinXError::OwnerMismatch
+    )]
+    pub treasury_wallet: SystemAccount<'info>,
+
+    /// CHECK: Randomness, read through the provider
+    #[account(
+        address = coinflip_pool.random_account() @ SpinXError::InvalidRandomnessAccount
+    )]
+    pub random: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: PlayerProfile PDA of the creator, only updated when it exists
+    #[account(
+        mut,
+        seeds = [PLAYER_PROFILE_SEED.as_bytes(), coinflip_pool.creator_player.as_ref(), coinflip_pool.mint.as_ref()],
+        bump
+    )]
+    pub creator_profile: UncheckedAccount<'info>,
+
+    /// CHECK: PlayerProfile PDA of the joiner, only updated when it exists
+    #[account(
+        mut,
+        seeds = [PLAYER_PROFILE_SEED.as_bytes(), coinflip_pool.joiner_player.as_ref(), coinflip_pool.mint.as_ref()],
+        bump
+    )]
+    pub joiner_profile: UncheckedAccount<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(pool_id: u64)]
+pub struct RefundSolCoinflip<'info> {
+    #[account(
+        mut,
+        close = creator,
+        seeds = [COINFLIP_SEED.as_bytes(), pool_id.to_le_bytes().as_ref()],
+        bump,
+        constraint = coinflip_pool.is_native @ SpinXError::InvalidPoolAsset
+    )]
+    pub coinflip_pool: Box<Account<'info, CoinflipPool>>,
+
+    #[account(
+        mut,
+        seeds = [SOL_ESCROW_SEED.as_bytes(), coinflip_pool.key().as_ref()],
+        bump
+    )]
+    pub sol_escrow: SystemAccount<'info>,
+
+    #[account(mut, address = coinflip_pool.creator_player @ SpinXError::InvalidCreator)]
+    pub creator: SystemAccount<'info>,
+
+    #[account(mut, address = coinflip_pool.joiner_player @ SpinXError::InvalidJoiner)]
+    pub joiner: SystemAccount<'info>,
+
+    #[account(
+        seeds = [GLOBAL_AUTHORITY_SEED.as_bytes()],
+        bump
+    )]
+    pub global_data: Box<Account<'info, GlobalData>>,
+
+    #[account(
+        mut,
+        constraint = 
+            treasury_wallet.key() == global_data.treasury_wallet @ SpinXError::OwnerMismatch
+    )]
+    pub treasury_wallet: SystemAccount<'info>,
+
+    /// CHECK: Randomness, only read to make sure it was never fulfilled
+    #[account(
+        address = coinflip_pool.random_account() @ SpinXError::InvalidRandomnessAccount
+    )]
+    pub random: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(pool_id: u64)]
+pub struct ExpireSolCoinflip<'info> {
+    #[account(
+        mut,
+        close = creator,
+        seeds = [COINFLIP_SEED.as_bytes(), pool_id.to_le_bytes().as_ref()],
+        bump,
+        constraint = coinflip_pool.is_native @ SpinXError::InvalidPoolAsset
+    )]
+    pub coinflip_pool: Box<Account<'info, CoinflipPool>>,
+
+    #[account(
+        mut,
+        seeds = [SOL_ESCROW_SEED.as_bytes(), coinflip_pool.key().as_ref()],
+        bump
+    )]
+    pub sol_escrow: SystemAccount<'info>,
+
+    #[account(mut, address = coinflip_pool.creator_player @ SpinXError::InvalidCreator)]
+    pub creator: SystemAccount<'info>,
+
+    #[account(
+        seeds = [GLOBAL_AUTHORITY_SEED.as_bytes()],
+        bump
+    )]
+    pub global_data: Box<Account<'info, GlobalData>>,
+
+    #[account(
+        mut,
+        constraint = 
+            treasury_wallet.key() == global_data.treasury_wallet @ SpinXError::OwnerMismatch
+    )]
+    pub treasury_wallet: SystemAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: PoolRegistry PDA the pool may be listed in, only updated when it exists
+    #[account(
+        mut,
+        seeds = [POOL_REGISTRY_SEED.as_bytes(), coinflip_pool.mint.as_ref(), [coinflip_pool.stake_tier].as_ref()],
+        bump
+    )]
+    pub pool_registry: UncheckedAccount<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CreateJackpot<'info> {
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [GLOBAL_AUTHORITY_SEED.as_bytes()],
+        bump
+    )]
+    pub global_data: Box<Account<'info, GlobalData>>,
+
+    #[account(mint::token_program = token_program)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        seeds = [MINT_CONFIG_SEED.as_bytes(), mint.key().as_ref()],
+        bump = mint_config.bump,
+        constraint = mint_config.enabled @ SpinXError::TokenNotAllowed
+    )]
+    pub mint_config: Box<Account<'info, MintConfig>>,
+
+    #[account(
+        init,
+        space = JackpotRound::space(0),
+        seeds = [JACKPOT_SEED.as_bytes(), global_data.next_jackpot_id.to_le_bytes().as_ref()],
+        bump,
+        payer = creator
+    )]
+    pub jackpot_round: Box<Account<'info, JackpotRound>>,
+
+    #[account(
+        init,
+        payer = creator,
+        associated_token::mint = mint,
+        associated_token::token_program = token_program,
+        associated_token::authority = jackpot_round
+    )]
+    pub spl_escrow: InterfaceAccount<'info, TokenAccount>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(round_id: u64)]
+pub struct EnterJackpot<'info> {
+    #[account(mut)]
+    pub player: Signer<'info>,
+
+    #[account(
+        seeds = [GLOBAL_AUTHORITY_SEED.as_bytes()],
+        bump
+    )]
+    pub global_data: Box<Account<'info, GlobalData>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::token_program = token_program,
+        associated_token::authority = player
+    )]
+    pub player_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        address = jackpot_round.mint @ SpinXError::TokenNotAllowed,
+        mint::token_program = token_program
+    )]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        seeds = [MINT_CONFIG_SEED.as_bytes(), mint.key().as_ref()],
+        bump = mint_config.bump,
+        constraint = mint_config.enabled @ SpinXError::TokenNotAllowed
+    )]
+    pub mint_config: Box<Account<'info, MintConfig>>,
+
+    // The player pays the rent of their new entry
+    #[account(
+        mut,
+        seeds = [JACKPOT_SEED.as_bytes(), round_id.to_le_bytes().as_ref()],
+        bump = jackpot_round.bump,
+        realloc = JackpotRound::space(jackpot_round.entries_with(&player.key())),
+        realloc::payer = player,
+        realloc::zero = false
+    )]
+    pub jackpot_round: Box<Account<'info, JackpotRound>>,
+
+    #[account(
+        mut,
+        constraint = 
+            treasury_wallet.key() == global_data.treasury_wallet @ SpinXError::OwnerMismatch
+    )]
+    pub treasury_wallet: SystemAccount<'info>,
+
+    /// CHECK: PlayerReferral PDA of the player, only read when it exists. The referrer it names has to be passed too
+    #[account(
+        seeds = [PLAYER_REFERRAL_SEED.as_bytes(), player.key().as_ref()],
+        bump
+    )]
+    pub player_referral: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub referrer_referral: Option<Account<'info, PlayerReferral>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::token_program = token_program,
+        associated_token::authority = jackpot_round
+    )]
+    pub spl_escrow: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(round_id: u64)]
+pub struct DrawJackpot<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [JACKPOT_SEED.as_bytes(), round_id.to_le_bytes().as_ref()],
+        bump = jackpot_round.bump
+    )]
+    pub jackpot_round: Box<Account<'info, JackpotRound>>,
+
+    /// CHECK: ORAO randomness account for force, checked by the provider
+    #[account(mut)]
+    pub random: UncheckedAccount<'info>,
+    /// CHECK: Provider treasury, checked by the provider
+    #[account(mut)]
+    pub treasury: UncheckedAccount<'info>,
+    /// CHECK: Provider config, checked by the provider
+    #[account(mut)]
+    pub config: UncheckedAccount<'info>,
+    /// CHECK: Provider program, checked by the provider
+    pub vrf: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(round_id: u64)]
+pub struct SettleJackpot<'info> {
+    #[account(
+        mut,
+        close = creator,
+        seeds = [JACKPOT_SEED.as_bytes(), round_id.to_le_bytes().as_ref()],
+        bump = jackpot_round.bump
+    )]
+    pub jackpot_round: Box<Account<'info, JackpotRound>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::token_program = token_program,
+        associated_token::authority = jackpot_round
+    )]
+    pub spl_escrow: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        address = jackpot_round.mint @ SpinXError::TokenNotAllowed,
+        mint::token_program = token_program
+    )]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    // Any token account of the drawn player for this mint, checked in the handler
+    #[account(
+        mut,
+        token::mint = mint,
+        token::token_program = token_program
+    )]
+    pub winner_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        seeds = [GLOBAL_AUTHORITY_SEED.as_bytes()],
+        bump
+    )]
+    pub global_data: Box<Account<'info, GlobalData>>,
+
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = global_data.treasury_wallet,
+        token::token_program = token_program
+    )]
+    pub treasury_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: Randomness, read through the provider
+    #[account(address = jackpot_round.random_account() @ SpinXError::InvalidRandomnessAccount)]
+    pub random: UncheckedAccount<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+
+    #[account(mut, address = jackpot_round.creator @ SpinXError::InvalidCreator)]
+    pub creator: SystemAccount<'info>,
+}
+
+// Token accounts of the players to refund follow as remaining accounts
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(round_id: u64)]
+pub struct RefundJackpot<'info> {
+    #[account(
+        mut,
+        seeds = [JACKPOT_SEED.as_bytes(), round_id.to_le_bytes().as_ref()],
+        bump = jackpot_round.bump
+    )]
+    pub jackpot_round: Box<Account<'info, JackpotRound>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::token_program = token_program,
+        associated_token::authority = jackpot_round
+    )]
+    pub spl_escrow: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        address = jackpot_round.mint @ SpinXError::TokenNotAllowed,
+        mint::token_program = token_program
+    )]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        seeds = [GLOBAL_AUTHORITY_SEED.as_bytes()],
+        bump
+    )]
+    pub global_data: Box<Account<'info, GlobalData>>,
+
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = global_data.treasury_wallet,
+        token::token_program = token_program
+    )]
+    pub treasury_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: Randomness of drawn rounds, only read to make sure it was never fulfilled or forfeited
+    #[account(address = jackpot_round.random_account() @ SpinXError::InvalidRandomnessAccount)]
+    pub random: UncheckedAccount<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    #[account(mut, address = jackpot_round.creator @ SpinXError::InvalidCreator)]
+    pub creator: SystemAccount<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CreateHouseVault<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [GLOBAL_AUTHORITY_SEED.as_bytes()],
+        bump,
+        constraint = global_data.super_admin == admin.key() @ SpinXError::InvalidAdmin
+    )]
+    pub global_data: Box<Account<'info, GlobalData>>,
+
+    #[account(mint::token_program = token_program)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    // Only allowlisted mints get a vault
+    #[account(
+        seeds = [MINT_CONFIG_SEED.as_bytes(), mint.key().as_ref()],
+        bump = mint_config.bump
+    )]
+    pub mint_config: Box<Account<'info, MintConfig>>,
+
+    #[account(
+        init,
+        space = 8 + std::mem::size_of::<HouseVault>(),
+        seeds = [HOUSE_VAULT_SEED.as_bytes(), mint.key().as_ref()],
+        bump,
+        payer = admin
+    )]
+    pub house_vault: Box<Account<'info, HouseVault>>,
+
+    #[account(
+        init,
+        payer = admin,
+        associated_token::mint = mint,
+        associated_token::token_program = token_program,
+        associated_token::authority = house_vault
+    )]
+    pub vault_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    // LP shares carry the decimals of the bankroll they are priced in
+    #[account(
+        init,
+        payer = admin,
+        seeds = [HOUSE_SHARES_SEED.as_bytes(), house_vault.key().as_ref()],
+        bump,
+        mint::decimals = mint.decimals,
+        mint::authority = house_vault,
+        mint::token_program = token_program
+    )]
+    pub share_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct UpdateHouseVault<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [GLOBAL_AUTHORITY_SEED.as_bytes()],
+        bump,
+        constraint = global_data.super_admin == admin.key() @ SpinXError::InvalidAdmin
+    )]
+    pub global_data: Box<Account<'info, GlobalData>>,
+
+    #[account(
+        mut,
+        seeds = [HOUSE_VAULT_SEED.as_bytes(), house_vault.mint.as_ref()],
+        bump = house_vault.bump
+    )]
+    pub house_vault: Box<Account<'info, HouseVault>>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct DepositHouseLiquidity<'info> {
+    #[account(mut)]
+    pub provider: Signer<'info>,
+
+    #[account(
+        seeds = [GLOBAL_AUTHORITY_SEED.as_bytes()],
+        bump
+    )]
+    pub global_data: Box<Account<'info, GlobalData>>,
+
+    #[account(
+        seeds = [HOUSE_VAULT_SEED.as_bytes(), house_vault.mint.as_ref()],
+        bump = house_vault.bump
+    )]
+    pub house_vault: Box<Account<'info, HouseVault>>,
+
+    #[account(
+        address = house_vault.mint @ SpinXError::TokenNotAllowed,
+        mint::token_program = token_program
+    )]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        address = house_vault.share_mint @ SpinXError::TokenNotAllowed
+    )]
+    pub share_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::token_program = token_program,
+        associated_token::authority = house_vault
+    )]
+    pub vault_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = provider,
+        token::token_program = token_program
+    )]
+    pub provider_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    // Initialize the provider's share account if it doesn't exist
+    #[account(
+        init_if_needed,
+        payer = provider,
+        associated_token::mint = share_mint,
+        associated_token::token_program = token_program,
+        associated_token::authority = provider
+    )]
+    pub provider_shares: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RequestHouseWithdrawal<'info> {
+    #[account(mut)]
+    pub provider: Signer<'info>,
+
+    #[account(
+        seeds = [HOUSE_VAULT_SEED.as_bytes(), house_vault.mint.as_ref()],
+        bump = house_vault.bump
+    )]
+    pub house_vault: Box<Account<'info, HouseVault>>,
+
+    #[account(
+        address = house_vault.share_mint @ SpinXError::TokenNotAllowed,
+        mint::token_program = token_program
+    )]
+    pub share_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        token::mint = share_mint,
+        token::authority = provider,
+        token::token_program = token_program
+    )]
+    pub provider_shares: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    // Holds the shares of every pending withdrawal
+    #[account(
+        init_if_needed,
+        payer = provider,
+        associated_token::mint = share_mint,
+        associated_token::token_program = token_program,
+        associated_token::authority = house_vault
+    )]
+    pub vault_shares: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init,
+        space = 8 + std::mem::size_of::<HouseWithdrawal>(),
+        seeds = [HOUSE_WITHDRAWAL_SEED.as_bytes(), house_vault.key().as_ref(), provider.key().as_ref()],
+        bump,
+        payer = provider
+    )]
+    pub house_withdrawal: Box<Account<'info, HouseWithdrawal>>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CancelHouseWithdrawal<'info> {
+    #[account(mut)]
+    pub provider: Signer<'info>,
+
+    #[account(
+        seeds = [HOUSE_VAULT_SEED.as_bytes(), house_vault.mint.as_ref()],
+        bump = house_vault.bump
+    )]
+    pub house_vault: Box<Account<'info, HouseVault>>,
+
+    #[account(
+        mut,
+        close = provider,
+        seeds = [HOUSE_WITHDRAWAL_SEED.as_bytes(), house_vault.key().as_ref(), provider.key().as_ref()],
+        bump = house_withdrawal.bump
+    )]
+    pub house_withdrawal: Box<Account<'info, HouseWithdrawal>>,
+
+    #[account(
+        address = house_vault.share_mint @ SpinXError::TokenNotAllowed,
+        mint::token_program = token_program
+    )]
+    pub share_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = share_mint,
+        associated_token::token_program = token_program,
+        associated_token::authority = house_vault
+    )]
+    pub vault_shares: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = share_mint,
+        token::authority = provider,
+        token::token_program = token_program
+    )]
+    pub provider_shares: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ExpireHouseWithdrawal<'info> {
+    #[account(
+        seeds = [HOUSE_VAULT_SEED.as_bytes(), house_vault.mint.as_ref()],
+        bump = house_vault.bump
+    )]
+    pub house_vault: Box<Account<'info, HouseVault>>,
+
+    #[account(
+        mut,
+        close = provider,
+        seeds = [HOUSE_WITHDRAWAL_SEED.as_bytes(), house_vault.key().as_ref(), provider.key().as_ref()],
+        bump = house_withdrawal.bump
+    )]
+    pub house_withdrawal: Box<Account<'info, HouseWithdrawal>>,
+
+    #[account(mut)]
+    pub provider: SystemAccount<'info>,
+
+    #[account(
+        address = house_vault.share_mint @ SpinXError::TokenNotAllowed,
+        mint::token_program = token_program
+    )]
+    pub share_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = share_mint,
+        associated_token::token_program = token_program,
+        associated_token::authority = house_vault
+    )]
+    pub vault_shares: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = share_mint,
+        token::authority = provider,
+        token::token_program = token_program
+    )]
+    pub provider_shares: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct WithdrawHouseLiquidity<'info> {
+    #[account(mut)]
+    pub provider: Signer<'info>,
+
+    #[account(
+        seeds = [HOUSE_VAULT_SEED.as_bytes(), house_vault.mint.as_ref()],
+        bump = house_vault.bump
+    )]
+    pub house_vault: Box<Account<'info, HouseVault>>,
+
+    #[account(
+        mut,
+        close = provider,
+        seeds = [HOUSE_WITHDRAWAL_SEED.as_bytes(), house_vault.key().as_ref(), provider.key().as_ref()],
+        bump = house_withdrawal.bump
+    )]
+    pub house_withdrawal: Box<Account<'info, HouseWithdrawal>>,
+
+    #[account(
+        address = house_vault.mint @ SpinXError::TokenNotAllowed,
+        mint::token_program = token_program
+    )]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        address = house_vault.share_mint @ SpinXError::TokenNotAllowed
+    )]
+    pub share_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::token_program = token_program,
+        associated_token::authority = house_vault
+    )]
+    pub vault_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = provider,
+        token::token_program = token_program
+    )]
+    pub provider_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = share_mint,
+        associated_token::token_program = token_program,
+        associated_token::authority = house_vault
+    )]
+    pub vault_shares: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
 }
 
-// This is your program's public key and it will update
-// automatically when you build the project.
-declare_id!("CK9bscEwv3uJRrtVFCaf55ascDR7ufgdk4udGsAWWbi8");
+#[event_cpi]
+#[derive(Accounts)]
+pub struct FlipVsHouse<'info> {
+    #[account(mut)]
+    pub player: Signer<'info>,
+
+    #[account(
+        seeds = [GLOBAL_AUTHORITY_SEED.as_bytes()],
+        bump
+    )]
+    pub global_data: Box<Account<'info, GlobalData>>,
 
-#[program]
-pub mod spinx {
-    use orao_solana_vrf::cpi::accounts::RequestV2;
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::token_program = token_program,
+        associated_token::authority = player
+    )]
+    pub player_ata: Box<InterfaceAccount<'info, TokenAccount>>,
 
-    use self::misc::current_state;
+    #[account(
+        address = house_vault.mint @ SpinXError::TokenNotAllowed,
+        mint::token_program = token_program
+    )]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
 
-    use super::*;
+    #[account(
+        seeds = [MINT_CONFIG_SEED.as_bytes(), mint.key().as_ref()],
+        bump = mint_config.bump,
+        constraint = mint_config.enabled @ SpinXError::TokenNotAllowed
+    )]
+    pub mint_config: Box<Account<'info, MintConfig>>,
 
-    pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
-        let global_data = &mut ctx.accounts.global_data;
-        global_data.super_admin = ctx.accounts.admin.key();
-        global_data.treasury_wallet = TREASURY_WALLET.parse::<Pubkey>().unwrap();
-        global_data.spinx_token = SPINX_TOKEN_ADDRESS.parse::<Pubkey>().unwrap();
-        global_data.coinflip_fee = COINFLIP_FEE;
-        global_data.min_amount = 10000000000;
-        global_data.next_pool_id = 1; // Initialize the pool ID count
+    #[account(
+        mut,
+        seeds = [HOUSE_VAULT_SEED.as_bytes(), house_vault.mint.as_ref()],
+        bump = house_vault.bump
+    )]
+    pub house_vault: Box<Account<'info, HouseVault>>,
 
-        Ok(())
-    }
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::token_program = token_program,
+        associated_token::authority = house_vault
+    )]
+    pub vault_ata: Box<InterfaceAccount<'info, TokenAccount>>,
 
+    #[account(
+        init,
+        space = 8 + std::mem::size_of::<HouseFlip>(),
+        seeds = [HOUSE_FLIP_SEED.as_bytes(), house_vault.key().as_ref(), house_vault.next_flip_id.to_le_bytes().as_ref()],
+        bump,
+        payer = player
+    )]
+    pub house_flip: Box<Account<'info, HouseFlip>>,
 
-    pub fn create_coinflip(ctx: Context<CreateCoinflip>, set_number: u8, amount: u64) -> Result<()> {
-        let coinflip_pool = &mut ctx.accounts.coinflip_pool;
-        let global_data = &mut ctx.accounts.global_data;
+    #[account(
+        mut,
+        constraint = 
+            treasury_wallet.key() == global_data.treasury_wallet @ SpinXError::OwnerMismatch
+    )]
+    pub treasury_wallet: SystemAccount<'info>,
 
-        require!( amount >= global_data.min_amount, SpinXError::AmountTooSmall);
-        
-        let fee = global_data.coinflip_fee;
-
-        // Transfer fee directly to treasury
-        sol_transfer_user(
-            ctx.accounts.creator.to_account_info().clone(), 
-            ctx.accounts.treasury_wallet.to_account_info().clone(), 
-            ctx.accounts.system_program.to_account_info().clone(), 
-            fee
-        )?;
-
-        // Transfer amount SPL token to spl_escrow
-        let cpi_accounts = Transfer {
-            from: ctx.accounts.creator_ata.to_account_info(),
-            to: ctx.accounts.spl_escrow.to_account_info(),
-            authority: ctx.accounts.creator.to_account_info(),
-        };
-        let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
-        token::transfer(cpi_ctx, amount)?;
-
-        // Generate the random number
-        let timestamp = Clock::get()?.unix_timestamp;
-
-        // Assign the current pool_id to this coinflip pool
-        coinflip_pool.pool_id = global_data.next_pool_id;
-
-        // Increment the next_pool_id for the next coinflip
-        global_data.next_pool_id += 1;
-
-        coinflip_pool.start_ts = timestamp as u64;
-        coinflip_pool.creator_player = ctx.accounts.creator.key();
-        coinflip_pool.creator_amount = amount;
-        coinflip_pool.creator_ata = ctx.accounts.creator_ata.key();
-        coinflip_pool.creator_set_number = set_number;
-        coinflip_pool.pool_amount = amount;
-        coinflip_pool.status = PoolStatus::Waiting;    
-        coinflip_pool.bump = ctx.bumps.coinflip_pool;
-
-        Ok(())
-    }
+    /// CHECK: PlayerReferral PDA of the player, only read when it exists. The referrer it names has to be passed too
+    #[account(
+        seeds = [PLAYER_REFERRAL_SEED.as_bytes(), player.key().as_ref()],
+        bump
+    )]
+    pub player_referral: UncheckedAccount<'info>,
 
-    pub fn join_coinflip(ctx: Context<JoinCoinflip>, pool_id: u64, force: [u8; 32], set_number: u8, amount: u64) -> Result<()> {
-        let coinflip_pool = &mut ctx.accounts.coinflip_pool;        
-        let global_data = &mut ctx.accounts.global_data;
-        let fee = global_data.coinflip_fee;
-        
-        require!(coinflip_pool.winner == Pubkey::default(), SpinXError::AlreadyDrawn);
-        require!(coinflip_pool.joiner_player == Pubkey::default(), SpinXError::AlreadyJoined);
-        require!(coinflip_pool.creator_player != ctx.accounts.joiner.key(), SpinXError::InvalidJoiner);
-        require!(coinflip_pool.creator_set_number != set_number, SpinXError::InvalidNumber);
-        require!(coinflip_pool.creator_amount == amount, SpinXError::InvalidAmount);
-        require!(set_number < 2, SpinXError::InvalidNumber); 
-
-        // Transfer amount SPL token to spl_escrow
-        let cpi_accounts = Transfer {
-            from: ctx.accounts.joiner_ata.to_account_info(),
-            to: ctx.accounts.spl_escrow.to_account_info(),
-            authority: ctx.accounts.joiner.to_account_info(),
-        };
-        let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
-        token::transfer(cpi_ctx, amount)?;
-
-        // Transfer fee directly to treasury
-        sol_transfer_user(
-            ctx.accounts.joiner.to_account_info().clone(), 
-            ctx.accounts.treasury_wallet.to_account_info().clone(), 
-            ctx.accounts.system_program.to_account_info().clone(), 
-            fee
-        )?;
-        
-        // Request randomness.
-        let cpi_program = ctx.accounts.vrf.to_account_info();
-        let cpi_accounts = RequestV2 {
-            payer: ctx.accounts.joiner.to_account_info(),
-            network_state: ctx.accounts.config.to_account_info(),
-            treasury: ctx.accounts.treasury.to_account_info(),
-            request: ctx.accounts.random.to_account_info(),
-            system_program: ctx.accounts.system_program.to_account_info(),
-        };
-
-        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
-        orao_solana_vrf::cpi::request_v2(cpi_ctx, force)?;
-        
-        coinflip_pool.joiner_player = ctx.accounts.joiner.key();
-        coinflip_pool.joiner_amount = amount;
-        coinflip_pool.joiner_ata = ctx.accounts.joiner_ata.key();
-        coinflip_pool.joiner_set_number = set_number;
-        coinflip_pool.pool_amount += amount;
-        coinflip_pool.force = force;
-        coinflip_pool.status = PoolStatus::Processing;        
-
-        Ok(())
-    }
+    #[account(mut)]
+    pub referrer_referral: Option<Account<'info, PlayerReferral>>,
 
-    pub fn close_coinflip(ctx: Context<CloseCoinflip>, pool_id: u64) -> Result<()> {
-        let coinflip_pool = &mut ctx.accounts.coinflip_pool;        
-        
-        require!(coinflip_pool.creator_player == ctx.accounts.signer.key(), SpinXError::InvalidCreator);
-        require!(coinflip_pool.joiner_player == Pubkey::default(), SpinXError::InvalidClaimStatus);
-
-        let seeds = &[
-                COINFLIP_SEED.as_bytes(), &pool_id.to_le_bytes(),
-                &[coinflip_pool.bump],
-            ];
-        let signer = &[&seeds[..]]; 
-
-        let cpi_accounts = Transfer {
-            from: ctx.accounts.spl_escrow.to_account_info(),
-            to: ctx.accounts.creator_ata.to_account_info(),
-            authority: coinflip_pool.to_account_info(),
-        };
-
-        let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), cpi_accounts, signer);
-        token::transfer(cpi_ctx, coinflip_pool.creator_amount)?;  
-
-        coinflip_pool.status = PoolStatus::Closed;
-        coinflip_pool.pool_amount = 0;
-        Ok(())
-    }
+    /// CHECK: ORAO randomness account for force or the committed Switchboard one, checked by the provider
+    #[account(mut)]
+    pub random: UncheckedAccount<'info>,
+    /// CHECK: Provider treasury, checked by the provider
+    #[account(mut)]
+    pub treasury: UncheckedAccount<'info>,
+    /// CHECK: Provider config, checked by the provider
+    #[account(mut)]
+    pub config: UncheckedAccount<'info>,
+    /// CHECK: Provider program, checked by the provider
+    pub vrf: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
 
-    pub fn result_coinflip(ctx: Context<ResultCoinflip>, pool_id: u64, force: [u8; 32]) -> Result<()> {
-        let coinflip_pool = &mut ctx.accounts.coinflip_pool;
-        let rand_acc = crate::misc::get_account_data(&ctx.accounts.random)?;        
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(flip_id: u64)]
+pub struct SettleHouseFlip<'info> {
+    #[account(
+        mut,
+        close = player,
+        seeds = [HOUSE_FLIP_SEED.as_bytes(), house_vault.key().as_ref(), flip_id.to_le_bytes().as_ref()],
+        bump = house_flip.bump
+    )]
+    pub house_flip: Box<Account<'info, HouseFlip>>,
 
-        require!(coinflip_pool.status == PoolStatus::Processing, SpinXError::InvalidPoolStatus);
+    #[account(
+        mut,
+        seeds = [HOUSE_VAULT_SEED.as_bytes(), house_vault.mint.as_ref()],
+        bump = house_vault.bump
+    )]
+    pub house_vault: Box<Account<'info, HouseVault>>,
 
-        let randomness = current_state(&rand_acc);
-        if randomness == 0 {
-            return err!(SpinXError::StillProcessing)
-        }
-        let result = (randomness % 2) as u8;
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::token_program = token_program,
+        associated_token::authority = house_vault
+    )]
+    pub vault_ata: Box<InterfaceAccount<'info, TokenAccount>>,
 
-        msg!("VRF result is: {}", randomness);
+    #[account(
+        address = house_vault.mint @ SpinXError::TokenNotAllowed,
+        mint::token_program = token_program
+    )]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
 
-        let seeds = &[
-                COINFLIP_SEED.as_bytes(), &pool_id.to_le_bytes(),
-                &[coinflip_pool.bump],
-            ];
-        let signer = &[&seeds[..]]; 
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::token_program = token_program,
+        associated_token::authority = house_flip.player
+    )]
+    pub player_ata: Box<InterfaceAccount<'info, TokenAccount>>,
 
-        if result == coinflip_pool.joiner_set_number { // Win Joiner
-            coinflip_pool.winner = coinflip_pool.joiner_player;
+    /// CHECK: Randomness, read through the provider
+    #[account(address = house_flip.random_account() @ SpinXError::InvalidRandomnessAccount)]
+    pub random: UncheckedAccount<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
 
-            let cpi_accounts = Transfer {
-                from: ctx.accounts.spl_escrow.to_account_info(),
-                to: ctx.accounts.joiner_ata.to_account_info(),
-                authority: coinflip_pool.to_account_info(),
-            };
-            
-            let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), cpi_accounts, signer);
-            token::transfer(cpi_ctx, coinflip_pool.pool_amount)?;   
+    #[account(mut, address = house_flip.player @ SpinXError::InvalidJoiner)]
+    pub player: SystemAccount<'info>,
+}
 
-        } else { // Win Creator
-            coinflip_pool.winner = coinflip_pool.creator_player;
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(flip_id: u64)]
+pub struct RefundHouseFlip<'info> {
+    #[account(
+        mut,
+        close = player,
+        seeds = [HOUSE_FLIP_SEED.as_bytes(), house_vault.key().as_ref(), flip_id.to_le_bytes().as_ref()],
+        bump = house_flip.bump
+    )]
+    pub house_flip: Box<Account<'info, HouseFlip>>,
 
-            let cpi_accounts = Transfer {
-                from: ctx.accounts.spl_escrow.to_account_info(),
-                to: ctx.accounts.creator_ata.to_account_info(),
-                authority: coinflip_pool.to_account_info(),
-            };
+    #[account(
+        mut,
+        seeds = [HOUSE_VAULT_SEED.as_bytes(), house_vault.mint.as_ref()],
+        bump = house_vault.bump
+    )]
+    pub house_vault: Box<Account<'info, HouseVault>>,
 
-            let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), cpi_accounts, signer);
-            token::transfer(cpi_ctx, coinflip_pool.pool_amount)?;
-        }
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::token_program = token_program,
+        associated_token::authority = house_vault
+    )]
+    pub vault_ata: Box<InterfaceAccount<'info, TokenAccount>>,
 
-        coinflip_pool.status = PoolStatus::Finished;
-        coinflip_pool.pool_amount = 0;
+    #[account(
+        address = house_vault.mint @ SpinXError::TokenNotAllowed,
+        mint::token_program = token_program
+    )]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
 
-        msg!("Coinflip game in room {} has concluded, the winner is {}", pool_id, coinflip_pool.winner.to_string());        
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::token_program = token_program,
+        associated_token::authority = house_flip.player
+    )]
+    pub player_ata: Box<InterfaceAccount<'info, TokenAccount>>,
 
-        Ok(())
-    }
+    #[account(
+        seeds = [GLOBAL_AUTHORITY_SEED.as_bytes()],
+        bump
+    )]
+    pub global_data: Box<Account<'info, GlobalData>>,
 
+    /// CHECK: Randomness, only read to make sure it was never fulfilled
+    #[account(address = house_flip.random_account() @ SpinXError::InvalidRandomnessAccount)]
+    pub random: UncheckedAccount<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
 
+    #[account(mut, address = house_flip.player @ SpinXError::InvalidJoiner)]
+    pub player: SystemAccount<'info>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
-pub struct Initialize<'info> {
+pub struct RegisterReferral<'info> {
     #[account(mut)]
-    pub admin: Signer<'info>,
+    pub player: Signer<'info>,
 
     #[account(
-        init,
-        space = 8 + std::mem::size_of::<GlobalData>(),
+        mut,
         seeds = [GLOBAL_AUTHORITY_SEED.as_bytes()],
-        bump,
-        payer = admin
+        bump
     )]
     pub global_data: Box<Account<'info, GlobalData>>,
 
+    #[account(
+        init,
+        payer = player,
+        space = 8 + std::mem::size_of::<PlayerReferral>(),
+        seeds = [PLAYER_REFERRAL_SEED.as_bytes(), player.key().as_ref()],
+        bump
+    )]
+    pub player_referral: Box<Account<'info, PlayerReferral>>,
 
     pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
-    pub rent: Sysvar<'info, Rent>,
 }
 
-
+#[event_cpi]
 #[derive(Accounts)]
-pub struct CreateCoinflip<'info> {
+#[instruction(referrer: Pubkey)]
+pub struct BindReferrer<'info> {
     #[account(mut)]
-    pub creator: Signer<'info>,
+    pub player: Signer<'info>,
 
     #[account(
         mut,
@@ -274,215 +4290,217 @@ pub struct CreateCoinflip<'info> {
     )]
     pub global_data: Box<Account<'info, GlobalData>>,
 
-    // Initialize player token account if it doesn't exist
     #[account(
         init_if_needed,
-        payer = creator,
-        associated_token::mint = spinx_mint,
-        associated_token::authority = creator
-    )]
-    pub creator_ata: Account<'info, TokenAccount>,
-
-    #[account(address = global_data.spinx_token)]
-    pub spinx_mint: Box<Account<'info, Mint>>,
-
-    #[account(
-        init,
-        space = 8 + std::mem::size_of::<CoinflipPool>(),
-        seeds = [COINFLIP_SEED.as_bytes(), global_data.next_pool_id.to_le_bytes().as_ref()],
-        bump,
-        payer = creator
+        payer = player,
+        space = 8 + std::mem::size_of::<PlayerReferral>(),
+        seeds = [PLAYER_REFERRAL_SEED.as_bytes(), player.key().as_ref()],
+        bump
     )]
-    pub coinflip_pool: Box<Account<'info, CoinflipPool>>,
+    pub player_referral: Box<Account<'info, PlayerReferral>>,
 
     #[account(
         mut,
-        constraint = 
-            treasury_wallet.key() == global_data.treasury_wallet @ SpinXError::OwnerMismatch
+        seeds = [PLAYER_REFERRAL_SEED.as_bytes(), referrer.as_ref()],
+        bump = referrer_referral.bump
     )]
-    pub treasury_wallet: SystemAccount<'info>,
+    pub referrer_referral: Box<Account<'info, PlayerReferral>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimReferralRewards<'info> {
+    #[account(mut)]
+    pub player: Signer<'info>,
 
     #[account(
-        init,
-        payer = creator,
-        associated_token::mint = spinx_mint,
-        associated_token::authority = coinflip_pool
+        mut,
+        seeds = [PLAYER_REFERRAL_SEED.as_bytes(), player.key().as_ref()],
+        bump = player_referral.bump
     )]
-    pub spl_escrow: Account<'info, TokenAccount>,
-
-    /// CHECK:` doc comment explaining why no checks through types are necessary.
-    pub associated_token_program: Program<'info, AssociatedToken>,
-    pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
+    pub player_referral: Box<Account<'info, PlayerReferral>>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
-#[instruction(
-    pool_id: u64, force: [u8; 32]
-)]
-pub struct JoinCoinflip<'info> {
+pub struct CreateSeason<'info> {
     #[account(mut)]
-    pub joiner: Signer<'info>,
+    pub admin: Signer<'info>,
 
     #[account(
         mut,
         seeds = [GLOBAL_AUTHORITY_SEED.as_bytes()],
-        bump
+        bump,
+        constraint = global_data.super_admin == admin.key() @ SpinXError::InvalidAdmin
     )]
     pub global_data: Box<Account<'info, GlobalData>>,
 
-    // Initialize player token account if it doesn't exist
     #[account(
-        init_if_needed,
-        payer = joiner,
-        associated_token::mint = spinx_mint,
-        associated_token::authority = joiner
+        mint::token_program = token_program,
+        constraint = mint.key() != native_mint::ID @ SpinXError::TokenNotAllowed
     )]
-    pub joiner_ata: Account<'info, TokenAccount>,
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
 
-    #[account(address = global_data.spinx_token)]
-    pub spinx_mint: Box<Account<'info, Mint>>,
+    // Only allowlisted mints get a season
+    #[account(
+        seeds = [MINT_CONFIG_SEED.as_bytes(), mint.key().as_ref()],
+        bump = mint_config.bump
+    )]
+    pub mint_config: Box<Account<'info, MintConfig>>,
 
     #[account(
-        mut,
-        seeds = [COINFLIP_SEED.as_bytes(), pool_id.to_le_bytes().as_ref()],
-        bump
+        init,
+        space = Season::space(),
+        seeds = [SEASON_SEED.as_bytes(), mint.key().as_ref()],
+        bump,
+        payer = admin
     )]
-    pub coinflip_pool: Account<'info, CoinflipPool>,
+    pub season: Box<Account<'info, Season>>,
 
     #[account(
-        mut,
-        constraint = 
-            treasury_wallet.key() == global_data.treasury_wallet @ SpinXError::OwnerMismatch
+        init,
+        payer = admin,
+        associated_token::mint = mint,
+        associated_token::token_program = token_program,
+        associated_token::authority = season
     )]
-    pub treasury_wallet: SystemAccount<'info>,
+    pub season_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
 
+#[event_cpi]
+#[derive(Accounts)]
+pub struct FinalizeSeason<'info> {
     #[account(
         mut,
-        associated_token::mint = spinx_mint,
-        associated_token::authority = coinflip_pool
+        seeds = [SEASON_SEED.as_bytes(), season.mint.as_ref()],
+        bump = season.bump
     )]
-    pub spl_escrow: Account<'info, TokenAccount>,
+    pub season: Box<Account<'info, Season>>,
 
-    /// CHECK:
     #[account(
-        mut,
-        seeds = [RANDOMNESS_ACCOUNT_SEED, &force],
-        bump,
-        seeds::program = orao_solana_vrf::ID
+        associated_token::mint = mint,
+        associated_token::token_program = token_program,
+        associated_token::authority = season
     )]
-    pub random: AccountInfo<'info>,
-    /// CHECK:
-    #[account(mut)]
-    pub treasury: AccountInfo<'info>,
-    
+    pub season_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
     #[account(
-        mut,
-        seeds = [CONFIG_ACCOUNT_SEED],
-        bump,
-        seeds::program = orao_solana_vrf::ID
+        address = season.mint @ SpinXError::TokenNotAllowed,
+        mint::token_program = token_program
     )]
-    pub config: Account<'info, NetworkState>,
-    
-    /// CHECK:` doc comment explaining why no checks through types are necessary.
-    pub vrf: Program<'info, OraoVrf>,
-    pub associated_token_program: Program<'info, AssociatedToken>,
-    pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
-#[instruction(pool_id: u64, force: [u8; 32])]
-pub struct ResultCoinflip<'info> {
+pub struct ClaimSeasonPrize<'info> {
+    pub player: Signer<'info>,
 
     #[account(
         mut,
-        seeds = [COINFLIP_SEED.as_bytes(), pool_id.to_le_bytes().as_ref()],
-        bump
+        seeds = [SEASON_SEED.as_bytes(), season.mint.as_ref()],
+        bump = season.bump
     )]
-    pub coinflip_pool: Account<'info, CoinflipPool>,
+    pub season: Box<Account<'info, Season>>,
 
     #[account(
         mut,
-        associated_token::mint = spinx_mint,
-        associated_token::authority = coinflip_pool
+        associated_token::mint = mint,
+        associated_token::token_program = token_program,
+        associated_token::authority = season
     )]
-    pub spl_escrow: Account<'info, TokenAccount>,
-
-    #[account(mut)]
-    pub spinx_mint: Box<Account<'info, Mint>>,
+    pub season_vault: Box<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
-        mut,
-        associated_token::mint = spinx_mint,
-        associated_token::authority = coinflip_pool.creator_player
+        address = season.mint @ SpinXError::TokenNotAllowed,
+        mint::token_program = token_program
     )]
-    pub creator_ata: Box<Account<'info, TokenAccount>>,
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
 
+    // Any token account of the player for this mint
     #[account(
         mut,
-        associated_token::mint = spinx_mint,
-        associated_token::authority = coinflip_pool.joiner_player
+        token::mint = mint,
+        token::authority = player,
+        token::token_program = token_program
     )]
-    pub joiner_ata: Box<Account<'info, TokenAccount>>,
+    pub player_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
 
-    /// CHECK: Treasury
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CloseSeason<'info> {
     #[account(mut)]
-    pub treasury: AccountInfo<'info>,
-    /// CHECK: Randomness
+    pub admin: Signer<'info>,
+
     #[account(
-        mut,
-        seeds = [RANDOMNESS_ACCOUNT_SEED, &force],
+        seeds = [GLOBAL_AUTHORITY_SEED.as_bytes()],
         bump,
-        seeds::program = orao_solana_vrf::ID,
-        constraint = coinflip_pool.force == force @  SpinXError::OwnerMismatch
+        constraint = global_data.super_admin == admin.key() @ SpinXError::InvalidAdmin
     )]
-    pub random: AccountInfo<'info>,
+    pub global_data: Box<Account<'info, GlobalData>>,
 
     #[account(
         mut,
-        seeds = [CONFIG_ACCOUNT_SEED.as_ref()],
-        bump,
-        seeds::program = orao_solana_vrf::ID
+        close = admin,
+        seeds = [SEASON_SEED.as_bytes(), season.mint.as_ref()],
+        bump = season.bump
     )]
-    pub config: Account<'info, NetworkState>,
-    pub vrf: Program<'info, OraoVrf>,
-    pub token_program: Program<'info, Token>,
-    pub system_program: Program<'info, System>,
-}
+    pub season: Box<Account<'info, Season>>,
 
-#[derive(Accounts)]
-#[instruction(pool_id: u64)]
-pub struct CloseCoinflip<'info> {
-    #[account(mut)]
-    pub signer: Signer<'info>,
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::token_program = token_program,
+        associated_token::authority = season
+    )]
+    pub season_vault: Box<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
         mut,
-        seeds = [COINFLIP_SEED.as_bytes(), pool_id.to_le_bytes().as_ref()],
-        bump
+        address = season.mint @ SpinXError::TokenNotAllowed,
+        mint::token_program = token_program
     )]
-    pub coinflip_pool: Account<'info, CoinflipPool>,
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
 
+    // Receives the unclaimed prizes, any token account of the treasury wallet for this mint
     #[account(
         mut,
-        associated_token::mint = spinx_mint,
-        associated_token::authority = coinflip_pool
+        token::mint = mint,
+        token::authority = global_data.treasury_wallet,
+        token::token_program = token_program
     )]
-    pub spl_escrow: Account<'info, TokenAccount>,
+    pub treasury_ata: Box<InterfaceAccount<'info, TokenAccount>>,
 
-    #[account(mut)]
-    pub spinx_mint: Box<Account<'info, Mint>>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[cfg(feature = "mock-vrf")]
+#[derive(Accounts)]
+#[instruction(force: [u8; 32])]
+pub struct MockFulfill<'info> {
+    pub admin: Signer<'info>,
 
     #[account(
-        mut,
-        associated_token::mint = spinx_mint,
-        associated_token::authority = coinflip_pool.creator_player
+        seeds = [GLOBAL_AUTHORITY_SEED.as_bytes()],
+        bump,
+        constraint = global_data.super_admin == admin.key() @ SpinXError::InvalidAdmin
     )]
-    pub creator_ata: Box<Account<'info, TokenAccount>>,
+    pub global_data: Box<Account<'info, GlobalData>>,
 
-    /// CHECK:` doc comment explaining why no checks through types are necessary.
-    pub associated_token_program: Program<'info, AssociatedToken>,
-    pub token_program: Program<'info, Token>,
-    pub system_program: Program<'info, System>,
+    #[account(
+        mut,
+        address = randomness::randomness_address(&force) @ SpinXError::InvalidRandomnessAccount
+    )]
+    pub random: Account<'info, randomness::MockRandomness>,
 }
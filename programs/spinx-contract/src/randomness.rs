@@ -0,0 +1,265 @@
+use std::mem::size_of;
+use anchor_lang::prelude::*;
+
+use crate::account::{GlobalData, RandomnessProvider};
+use crate::error::SpinXError;
+
+// Everything the game needs from a randomness provider lives here: where the
+// randomness account for a `force` seed lives, how to request it on join and
+// how to read it back on settlement. GlobalData picks ORAO or Switchboard for
+// new joins, jackpot draws always use ORAO. The `mock-vrf` feature swaps ORAO
+// for a local provider.
+
+/// Accounts handed to the provider when a joiner requests randomness.
+pub struct RequestAccounts<'info> {
+    pub payer: AccountInfo<'info>,
+    pub random: AccountInfo<'info>,
+    pub config: AccountInfo<'info>,
+    pub treasury: AccountInfo<'info>,
+    pub vrf: AccountInfo<'info>,
+    pub system_program: AccountInfo<'info>,
+}
+
+/// Takes the first 8 bytes of the fulfilled randomness, 0 counts as unfulfilled.
+fn to_u64(randomness: &[u8]) -> Option<u64> {
+    let value = u64::from_le_bytes(randomness[0..size_of::<u64>()].try_into().unwrap());
+
+    if value == 0 {
+        None
+    } else {
+        Some(value)
+    }
+}
+
+#[cfg(not(feature = "mock-vrf"))]
+mod orao {
+    use anchor_lang::prelude::*;
+    use orao_solana_vrf::cpi::accounts::RequestV2;
+    use orao_solana_vrf::state::RandomnessAccountData;
+    use orao_solana_vrf::{CONFIG_ACCOUNT_SEED, RANDOMNESS_ACCOUNT_SEED};
+
+    use super::{to_u64, RequestAccounts};
+    use crate::error::SpinXError;
+
+    pub fn randomness_address(force: &[u8; 32]) -> Pubkey {
+        Pubkey::find_program_address(&[RANDOMNESS_ACCOUNT_SEED, force], &orao_solana_vrf::ID).0
+    }
+
+    pub fn request(accounts: RequestAccounts, force: [u8; 32]) -> Result<()> {
+        require_keys_eq!(accounts.vrf.key(), orao_solana_vrf::ID, SpinXError::InvalidRandomnessAccount);
+        require_keys_eq!(
+            accounts.config.key(),
+            Pubkey::find_program_address(&[CONFIG_ACCOUNT_SEED], &orao_solana_vrf::ID).0,
+            SpinXError::InvalidRandomnessAccount
+        );
+
+        // ORAO checks the treasury against its own config
+        let cpi_accounts = RequestV2 {
+            payer: accounts.payer,
+            network_state: accounts.config,
+            treasury: accounts.treasury,
+            request: accounts.random,
+            system_program: accounts.system_program,
+        };
+
+        let cpi_ctx = CpiContext::new(accounts.vrf, cpi_accounts);
+        orao_solana_vrf::cpi::request_v2(cpi_ctx, force)
+    }
+
+    pub fn read_fulfilled(random: &AccountInfo) -> Result<Option<u64>> {
+        if random.data_is_empty() {
+            return Err(ProgramError::UninitializedAccount.into());
+        }
+
+        let account = RandomnessAccountData::try_deserialize(&mut &random.data.borrow()[..])?;
+
+        Ok(account.fulfilled_randomness().and_then(|randomness| to_u64(randomness)))
+    }
+}
+
+#[cfg(feature = "mock-vrf")]
+mod orao {
+    use anchor_lang::prelude::*;
+    use anchor_lang::system_program::{create_account, CreateAccount};
+
+    use super::{to_u64, MockRandomness, RequestAccounts};
+    use crate::utils::MOCK_RANDOMNESS_SEED;
+
+    pub fn randomness_address(force: &[u8; 32]) -> Pubkey {
+        Pubkey::find_program_address(&[MOCK_RANDOMNESS_SEED.as_bytes(), force], &crate::ID).0
+    }
+
+    // config, treasury and vrf are ignored, any account can be passed
+    pub fn request(accounts: RequestAccounts, force: [u8; 32]) -> Result<()> {
+        let (_, bump) = Pubkey::find_program_address(&[MOCK_RANDOMNESS_SEED.as_bytes(), &force], &crate::ID);
+        let seeds = &[MOCK_RANDOMNESS_SEED.as_bytes(), &force, &[bump]];
+        let signer = &[&seeds[..]];
+        let space = MockRandomness::LEN;
+
+        let cpi_ctx = CpiContext::new_with_signer(
+            accounts.system_program,
+            CreateAccount {
+                from: accounts.payer,
+                to: accounts.random.clone(),
+            },
+            signer,
+        );
+        create_account(cpi_ctx, Rent::get()?.minimum_balance(space), space as u64, &crate::ID)?;
+
+        let account = MockRandomness {
+            force,
+            randomness: [0; 64],
+            fulfilled: false,
+        };
+        account.try_serialize(&mut &mut accounts.random.data.borrow_mut()[..])
+    }
+
+    pub fn read_fulfilled(random: &AccountInfo) -> Result<Option<u64>> {
+        if random.data_is_empty() {
+            return Err(ProgramError::UninitializedAccount.into());
+        }
+
+        let account = MockRandomness::try_deserialize(&mut &random.data.borrow()[..])?;
+
+        if account.fulfilled {
+            Ok(to_u64(&account.randomness))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+/// Randomness account of the mock provider, fulfilled by the test authority
+/// through `mock_fulfill`.
+#[cfg(feature = "mock-vrf")]
+#[account]
+pub struct MockRandomness {
+    pub force: [u8; 32],
+    pub randomness: [u8; 64],
+    pub fulfilled: bool,
+}
+
+#[cfg(feature = "mock-vrf")]
+impl MockRandomness {
+    pub const LEN: usize = 8 + size_of::<MockRandomness>();
+}
+
+// Switchboard On-Demand, commit on join and reveal on settlement. The joiner
+// commits their randomness account in the same transaction as the join, the
+// value is final once an oracle reveals it for that commit. The joiner owns the
+// account and could close it or commit it again once they know they lost, so
+// doing either forfeits the game instead of refunding it.
+pub mod switchboard {
+    use anchor_lang::prelude::*;
+    use anchor_lang::system_program;
+
+    use super::{to_u64, RequestAccounts};
+    use crate::account::GlobalData;
+    use crate::error::SpinXError;
+    use crate::utils::SWITCHBOARD_PROGRAM_ID;
+
+    pub const DISCRIMINATOR: [u8; 8] = [10, 66, 229, 135, 220, 239, 217, 114];
+
+    /// Leading fields of the On-Demand RandomnessAccountData, the account is
+    /// zero-copy so this matches its byte layout after the discriminator.
+    #[derive(AnchorSerialize, AnchorDeserialize)]
+    pub struct RandomnessAccountData {
+        pub authority: Pubkey,
+        pub queue: Pubkey,
+        pub seed_slothash: [u8; 32],
+        pub seed_slot: u64,
+        pub oracle: Pubkey,
+        pub reveal_slot: u64,
+        pub value: [u8; 32],
+    }
+
+    fn load(random: &AccountInfo) -> Result<RandomnessAccountData> {
+        require_keys_eq!(*random.owner, SWITCHBOARD_PROGRAM_ID, SpinXError::InvalidRandomnessAccount);
+
+        let data = random.try_borrow_data()?;
+        require!(data.len() >= 8 && data[..8] == DISCRIMINATOR, SpinXError::InvalidRandomnessAccount);
+
+        Ok(RandomnessAccountData::deserialize(&mut &data[8..])?)
+    }
+
+    // Returns the committed seed slot, settlement only accepts a reveal for it
+    pub fn request(global_data: &GlobalData, accounts: RequestAccounts) -> Result<u64> {
+        let account = load(&accounts.random)?;
+        let clock = Clock::get()?;
+
+        require_keys_eq!(account.queue, global_data.switchboard_queue, SpinXError::InvalidRandomnessAccount);
+        require!(account.seed_slot + 1 == clock.slot, SpinXError::InvalidRandomnessAccount);
+        require!(account.reveal_slot < account.seed_slot, SpinXError::RandomnessFulfilled);
+
+        Ok(account.seed_slot)
+    }
+
+    // A recommitted account never fulfills the game, it is forfeited
+    pub fn read_fulfilled(commit_slot: u64, random: &AccountInfo) -> Result<Option<u64>> {
+        let account = load(random)?;
+
+        if account.seed_slot != commit_slot || account.reveal_slot <= account.seed_slot {
+            return Ok(None);
+        }
+
+        Ok(to_u64(&account.value))
+    }
+
+    // The committer closed the account or committed it again. A commit nobody revealed may
+    // just be an oracle outage, it is refunded after the deadline instead.
+    pub fn forfeited(commit_slot: u64, random: &AccountInfo) -> Result<bool> {
+        if random.data_is_empty() && *random.owner == system_program::ID {
+            return Ok(true);
+        }
+
+        let account = load(random)?;
+
+        Ok(account.seed_slot != commit_slot)
+    }
+}
+
+/// ORAO (or mock) randomness account for `force`.
+pub fn randomness_address(force: &[u8; 32]) -> Pubkey {
+    orao::randomness_address(force)
+}
+
+/// Requests ORAO (or mock) randomness for `force`, whatever GlobalData selects.
+pub fn request_orao(accounts: RequestAccounts, force: [u8; 32]) -> Result<()> {
+    require_keys_eq!(
+        accounts.random.key(),
+        randomness_address(&force),
+        SpinXError::InvalidRandomnessAccount
+    );
+
+    orao::request(accounts, force)
+}
+
+/// Requests randomness from the provider selected in GlobalData.
+/// Returns the Switchboard commit slot, 0 for ORAO.
+pub fn request(global_data: &GlobalData, accounts: RequestAccounts, force: [u8; 32]) -> Result<u64> {
+    match global_data.randomness_provider {
+        RandomnessProvider::Orao => {
+            request_orao(accounts, force)?;
+            Ok(0)
+        }
+        RandomnessProvider::Switchboard => switchboard::request(global_data, accounts),
+    }
+}
+
+/// Whether whoever committed the randomness gave the game up, only possible with
+/// Switchboard. Errors like `read_fulfilled`.
+pub fn forfeited(provider: RandomnessProvider, commit_slot: u64, random: &AccountInfo) -> Result<bool> {
+    match provider {
+        RandomnessProvider::Orao => Ok(false),
+        RandomnessProvider::Switchboard => switchboard::forfeited(commit_slot, random),
+    }
+}
+
+/// Fulfilled randomness of a pool or jackpot round, `None` while still pending.
+/// Errors if the account does not hold randomness of the recorded provider.
+pub fn read_fulfilled(provider: RandomnessProvider, commit_slot: u64, random: &AccountInfo) -> Result<Option<u64>> {
+    match provider {
+        RandomnessProvider::Orao => orao::read_fulfilled(random),
+        RandomnessProvider::Switchboard => switchboard::read_fulfilled(commit_slot, random),
+    }
+}
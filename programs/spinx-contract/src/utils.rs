@@ -2,6 +2,21 @@ use anchor_lang::prelude::*;
 use anchor_lang::solana_program::{
     program::invoke, program::invoke_signed
 };
+use anchor_spl::token_2022::spl_token_2022::{
+    self,
+    extension::{
+        default_account_state::DefaultAccountState,
+        transfer_fee::{TransferFeeAmount, TransferFeeConfig},
+        transfer_hook::TransferHook, BaseStateWithExtensions, ExtensionType, StateWithExtensions,
+    },
+    state::{Account as TokenAccountState, AccountState, Mint},
+};
+use anchor_spl::token_2022_extensions::{harvest_withheld_tokens_to_mint, HarvestWithheldTokensToMint};
+use anchor_spl::token_interface::{self, CloseAccount, TransferChecked};
+
+use crate::account::PlayerReferral;
+use crate::error::SpinXError;
+use crate::event::ReferralFeePaid;
 
 pub const SPINX_TOKEN_ADDRESS: &str = "4QAuuGj2mMjEPwsX61Sx9gwfNLcKVPotSWV3vUZfv28g";
 pub const TREASURY_WALLET: &str = "69QQYnDRZ386bbuMV7srfgh4D5dAR51SdyZ1wWtC3CKs";
@@ -12,6 +27,81 @@ pub const GAME_SEED: &str = "game-authority";
 pub const COINFLIP_SEED: &str = "coinflip-authority";
 pub const SPL_ESCROW_SEED: &str = "spl-escrow";
 pub const RANDOM_SEED: &str = "random";
+pub const MINT_CONFIG_SEED: &str = "mint-config";
+pub const SOL_ESCROW_SEED: &str = "sol-escrow";
+pub const MOCK_RANDOMNESS_SEED: &str = "mock-randomness";
+pub const JACKPOT_SEED: &str = "jackpot-round";
+pub const HOUSE_VAULT_SEED: &str = "house-vault";
+pub const HOUSE_FLIP_SEED: &str = "house-flip";
+pub const HOUSE_SHARES_SEED: &str = "house-shares";
+pub const HOUSE_WITHDRAWAL_SEED: &str = "house-withdrawal";
+pub const PLAYER_REFERRAL_SEED: &str = "player-referral";
+pub const PLAYER_PROFILE_SEED: &str = "player-profile";
+pub const SEASON_SEED: &str = "season";
+pub const POOL_REGISTRY_SEED: &str = "pool-registry";
+
+pub const BPS_DENOMINATOR: u64 = 10000;
+pub const MAX_RAKE_BPS: u16 = 1000;
+// Most of a flat fee that can go to the player's referrer instead of the treasury
+pub const MAX_REFERRAL_SHARE_BPS: u16 = 5000;
+
+// Most the house may keep from a winning flip's doubled stake
+pub const MAX_HOUSE_EDGE_BPS: u16 = 1000;
+// A single flip may never stake more than this share of the vault's free balance
+pub const MAX_HOUSE_BET_BPS: u16 = 500;
+// Longest an LP can be made to wait for a withdrawal
+pub const MAX_HOUSE_WITHDRAWAL_DELAY: u64 = 7 * 24 * 60 * 60;
+// How long a ready withdrawal can be completed, so a request can't be held open as an option
+pub const HOUSE_WITHDRAWAL_WINDOW: u64 = 24 * 60 * 60;
+// Shares and tokens added to both sides of the LP price
+pub const HOUSE_VIRTUAL_LIQUIDITY: u64 = 1_000_000;
+
+// Players per jackpot round, the bound keeps the entry list within one realloc and settlement cheap
+pub const MAX_JACKPOT_ENTRIES: usize = 100;
+// Fewer players leave nobody to win against, the round is refunded instead of drawn
+pub const MIN_JACKPOT_PLAYERS: usize = 2;
+
+// Waiting pools listed per registry shard
+pub const MAX_REGISTRY_POOLS: usize = 64;
+
+// Ranked players per season, also the most ranks a season can pay
+pub const MAX_SEASON_RANKS: usize = 10;
+// Most of the rake a season may pay back as prizes, so wash trading for volume always costs
+pub const MAX_SEASON_PRIZE_SHARE_BPS: u16 = 5000;
+// Seconds after a season ends that winners are guaranteed to be able to claim
+pub const SEASON_CLAIM_WINDOW: u64 = 30 * 24 * 60 * 60;
+
+// Switchboard On-Demand, randomness accounts are owned by its mainnet deployment
+// unless the program is built with the `devnet` feature
+#[cfg(not(feature = "devnet"))]
+pub const SWITCHBOARD_PROGRAM_ID: Pubkey = pubkey!("SBondMDrcV3K4kxZR1HNVT7osZxAHVHgYXL5Ze1oMUv");
+#[cfg(feature = "devnet")]
+pub const SWITCHBOARD_PROGRAM_ID: Pubkey = pubkey!("Aio4gaXjXzJNVLtzwtNVmSqGKpANtXhybbkhtAC94ji2");
+
+// Seconds a joined pool waits for VRF fulfillment before it can be refunded
+pub const DEFAULT_REFUND_TIMEOUT: u64 = 60 * 60;
+// Longest refund_timeout the admin can set, stakes are never locked for more than a week
+pub const MAX_REFUND_TIMEOUT: u64 = 7 * 24 * 60 * 60;
+// Seconds an invite-only pool stays reserved for the invited player
+pub const DEFAULT_INVITE_TIMEOUT: u64 = 60 * 60;
+// Longest invite_timeout the admin can set
+pub const MAX_INVITE_TIMEOUT: u64 = 7 * 24 * 60 * 60;
+// Longest lifetime of a waiting pool or jackpot round set by initialize and migrate_global_data
+pub const DEFAULT_MAX_POOL_TTL: u64 = 7 * 24 * 60 * 60;
+
+// Bits of GlobalData.pause_flags. Only entry points are pausable,
+// close_coinflip and result_coinflip must always stay callable.
+pub const PAUSE_CREATE_COINFLIP: u32 = 1 << 0;
+pub const PAUSE_JOIN_COINFLIP: u32 = 1 << 1;
+pub const PAUSE_JACKPOT: u32 = 1 << 2; // creating and entering jackpot rounds
+pub const PAUSE_HOUSE: u32 = 1 << 3; // new flips against the house
+pub const PAUSE_ALL: u32 = 1 << 31;
+
+// Registry shard of a stake, its order of magnitude in base units. Pools of 10 to 99.99 tokens
+// of a 9 decimal mint share tier 10, so a lobby of similar stakes is one account read.
+pub fn stake_tier(amount: u64) -> u8 {
+    amount.checked_ilog10().unwrap_or(0) as u8
+}
 
 // Here are some normal sample functions here
 pub fn sol_transfer_user<'a>(
@@ -25,6 +115,52 @@ pub fn sol_transfer_user<'a>(
     Ok(())
 }
 
+// Pays a flat fee from a player. When the player's PlayerReferral names a referrer, their share
+// is accrued on the referrer's PlayerReferral, which then has to be passed too, and the rest goes
+// to the treasury.
+pub fn pay_fee<'a>(
+    player: AccountInfo<'a>,
+    treasury_wallet: AccountInfo<'a>,
+    system_program: AccountInfo<'a>,
+    player_referral: &AccountInfo<'a>,
+    referrer_referral: Option<&mut Account<'a, PlayerReferral>>,
+    referral_share_bps: u16,
+    fee: u64,
+) -> Result<Option<ReferralFeePaid>> {
+    if fee == 0 {
+        return Ok(None);
+    }
+
+    let referrer = PlayerReferral::referrer_of(player_referral)?;
+    let mut referral_fee = None;
+
+    if referrer != Pubkey::default() {
+        let referrer_referral = referrer_referral.ok_or(SpinXError::InvalidReferrer)?;
+        require_keys_eq!(referrer_referral.player, referrer, SpinXError::InvalidReferrer);
+
+        let amount = fee * referral_share_bps as u64 / BPS_DENOMINATOR;
+        if amount > 0 {
+            sol_transfer_user(
+                player.clone(),
+                referrer_referral.to_account_info(),
+                system_program.clone(),
+                amount
+            )?;
+            referrer_referral.pending_rewards += amount;
+            referrer_referral.total_rewards += amount;
+
+            referral_fee = Some(ReferralFeePaid { player: player.key(), referrer, fee, amount });
+        }
+    }
+
+    let treasury_fee = fee - referral_fee.as_ref().map_or(0, |referral_fee| referral_fee.amount);
+    if treasury_fee > 0 {
+        sol_transfer_user(player, treasury_wallet, system_program, treasury_fee)?;
+    }
+
+    Ok(referral_fee)
+}
+
 pub fn sol_transfer_with_signer<'a>(
     source: AccountInfo<'a>,
     destination: AccountInfo<'a>,
@@ -35,4 +171,159 @@ pub fn sol_transfer_with_signer<'a>(
     let ix = anchor_lang::solana_program::system_instruction::transfer(source.key, destination.key, amount);
     invoke_signed(&ix, &[source, destination, system_program], signers)?;
     Ok(())
-}
\ No newline at end of file
+}
+
+// Empties a sol escrow into the treasury after its payouts. Anyone can send lamports to the
+// escrow, and whatever they left would keep it below rent exemption and fail the instruction.
+pub fn sweep_sol_escrow<'a>(
+    sol_escrow: AccountInfo<'a>,
+    treasury_wallet: AccountInfo<'a>,
+    system_program: AccountInfo<'a>,
+    signers: &[&[&[u8]]; 1],
+) -> Result<()> {
+    let leftover = sol_escrow.lamports();
+    if leftover > 0 {
+        sol_transfer_with_signer(sol_escrow, treasury_wallet, system_program, signers, leftover)?;
+    }
+    Ok(())
+}
+// Grows a program owned account to new_len, topping up rent from payer
+pub fn resize_account<'a>(
+    account: AccountInfo<'a>,
+    payer: AccountInfo<'a>,
+    system_program: AccountInfo<'a>,
+    new_len: usize,
+) -> Result<()> {
+    let rent_exempt = Rent::get()?.minimum_balance(new_len);
+    let lamports = account.lamports();
+    if rent_exempt > lamports {
+        sol_transfer_user(payer, account.clone(), system_program, rent_exempt - lamports)?;
+    }
+    account.resize(new_len)?;
+    Ok(())
+}
+
+// Rejects mints whose extensions or freeze authority could move, lock or block the escrowed stakes.
+// A freeze authority can freeze the escrow of any pool, so it is only accepted when the admin
+// approved that exact key for the mint (USDC and other regulated stablecoins have one).
+pub fn check_mint(mint: &AccountInfo, approved_freeze_authority: Pubkey) -> Result<()> {
+    let data = mint.try_borrow_data()?;
+    // Token-2022 reads classic SPL mints as mints without extensions
+    let state = StateWithExtensions::<Mint>::unpack(&data)?;
+
+    for extension in state.get_extension_types()? {
+        match extension {
+            // Can transfer or burn out of the escrow, or makes stakes impossible to pay out
+            ExtensionType::PermanentDelegate | ExtensionType::NonTransferable => {
+                return err!(SpinXError::UnsafeMintExtension);
+            }
+            // Hooks need extra accounts on every transfer and can block payouts
+            ExtensionType::TransferHook => {
+                let hook = state.get_extension::<TransferHook>()?;
+                if Option::<Pubkey>::from(hook.program_id).is_some() {
+                    return err!(SpinXError::UnsafeMintExtension);
+                }
+            }
+            // A frozen escrow could never pay out
+            ExtensionType::DefaultAccountState => {
+                let default_state = state.get_extension::<DefaultAccountState>()?;
+                if default_state.state == AccountState::Frozen as u8 {
+                    return err!(SpinXError::UnsafeMintExtension);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if let Some(freeze_authority) = Option::<Pubkey>::from(state.base.freeze_authority) {
+        require_keys_eq!(freeze_authority, approved_freeze_authority, SpinXError::FreezeAuthorityNotApproved);
+    }
+
+    Ok(())
+}
+
+// Fee withheld by the Token-2022 transfer fee extension when sending amount, 0 for other mints
+pub fn transfer_fee(mint: &AccountInfo, amount: u64) -> Result<u64> {
+    if *mint.owner != spl_token_2022::ID {
+        return Ok(0);
+    }
+
+    let data = mint.try_borrow_data()?;
+    let state = StateWithExtensions::<Mint>::unpack(&data)?;
+
+    if let Ok(fee_config) = state.get_extension::<TransferFeeConfig>() {
+        let epoch = Clock::get()?.epoch;
+        return Ok(fee_config.calculate_epoch_fee(epoch, amount).ok_or(SpinXError::InvalidAmount)?);
+    }
+
+    Ok(0)
+}
+
+// Sends what's left in an escrow token account after its payouts to destination. Escrows are
+// public associated token accounts, and tokens anyone sent to one would otherwise block its close.
+pub fn sweep_escrow<'a>(
+    escrow: AccountInfo<'a>,
+    mint: AccountInfo<'a>,
+    destination: AccountInfo<'a>,
+    authority: AccountInfo<'a>,
+    token_program: AccountInfo<'a>,
+    signers: &[&[&[u8]]; 1],
+    decimals: u8,
+) -> Result<()> {
+    let leftover = {
+        let data = escrow.try_borrow_data()?;
+        StateWithExtensions::<TokenAccountState>::unpack(&data)?.base.amount
+    };
+
+    if leftover > 0 {
+        let cpi_accounts = TransferChecked {
+            from: escrow,
+            mint,
+            to: destination,
+            authority,
+        };
+        let cpi_ctx = CpiContext::new_with_signer(token_program, cpi_accounts, signers);
+        token_interface::transfer_checked(cpi_ctx, leftover, decimals)?;
+    }
+    Ok(())
+}
+
+// Closes a drained escrow token account and sends its rent to destination.
+// Token-2022 refuses to close accounts holding withheld transfer fees, so those are harvested to the mint first.
+pub fn close_escrow<'a>(
+    escrow: AccountInfo<'a>,
+    mint: AccountInfo<'a>,
+    destination: AccountInfo<'a>,
+    authority: AccountInfo<'a>,
+    token_program: AccountInfo<'a>,
+    signers: &[&[&[u8]]; 1],
+) -> Result<()> {
+    if *token_program.key == spl_token_2022::ID {
+        let withheld = {
+            let data = escrow.try_borrow_data()?;
+            let state = StateWithExtensions::<TokenAccountState>::unpack(&data)?;
+            state
+                .get_extension::<TransferFeeAmount>()
+                .map(|fee_amount| u64::from(fee_amount.withheld_amount))
+                .unwrap_or(0)
+        };
+
+        if withheld > 0 {
+            let cpi_accounts = HarvestWithheldTokensToMint {
+                token_program_id: token_program.clone(),
+                mint,
+            };
+            let cpi_ctx = CpiContext::new(token_program.clone(), cpi_accounts);
+            harvest_withheld_tokens_to_mint(cpi_ctx, vec![escrow.clone()])?;
+        }
+    }
+
+    let cpi_accounts = CloseAccount {
+        account: escrow,
+        destination,
+        authority,
+    };
+    let cpi_ctx = CpiContext::new_with_signer(token_program, cpi_accounts, signers);
+    token_interface::close_account(cpi_ctx)?;
+    Ok(())
+}
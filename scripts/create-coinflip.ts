@@ -25,6 +25,7 @@ dotenv.config();
 // Constants
 const GLOBAL_AUTHORITY_SEED = "global-authority";
 const COINFLIP_SEED = "coinflip-authority";
+const POOL_REGISTRY_SEED = "pool-registry";
 const SPINX_TOKEN_ADDRESS = "4QAuuGj2mMjEPwsX61Sx9gwfNLcKVPotSWV3vUZfv28g";
 const TREASURY_WALLET = "69QQYnDRZ386bbuMV7srfgh4D5dAR51SdyZ1wWtC3CKs";
 
@@ -35,6 +36,10 @@ const CREATOR_PRIVATE_KEY = process.env.CREATOR_PRIVATE_KEY;
 // Coinflip parameters
 const COINFLIP_AMOUNT = new BN(10_000_000_000); // 10 tokens (adjust as needed)
 const SET_NUMBER = 1; // 1 for heads, 0 for tails
+const POOL_TTL = new BN(0); // seconds until anyone can expire the pool, 0 uses the global maximum
+const ALLOWED_JOINER: PublicKey | null = null; // only this wallet may join, null for a public pool
+// Weighted pool where the joiner may stake anything in this range and odds follow the stakes, null for an even flip
+const JOINER_STAKE: { minAmount: BN; maxAmount: BN } | null = null;
 
 async function createCoinflip() {
     console.log("🚀 Starting coinflip creation...");
@@ -127,6 +132,14 @@ async function createCoinflip() {
         );
         console.log("🏦 SPL Escrow:", splEscrow.toString());
 
+        // Lobby shard of the stake, its order of magnitude in base units
+        const stakeTier = COINFLIP_AMOUNT.isZero() ? 0 : COINFLIP_AMOUNT.toString().length - 1;
+        const [poolRegistry] = PublicKey.findProgramAddressSync(
+            [Buffer.from(POOL_REGISTRY_SEED), spinxMint.toBuffer(), Buffer.from([stakeTier])],
+            program.programId
+        );
+        console.log("📋 Pool Registry:", poolRegistry.toString());
+
         // Check creator SOL balance
         const creatorBalance = await connection.getBalance(creatorKeypair.publicKey);
         console.log("💎 Creator SOL balance:", creatorBalance / LAMPORTS_PER_SOL, "SOL");
@@ -152,15 +165,16 @@ async function createCoinflip() {
 
         // Create the coinflip transaction
         const tx = await program.methods
-            .createCoinflip(SET_NUMBER, COINFLIP_AMOUNT)
+            .createCoinflip(SET_NUMBER, COINFLIP_AMOUNT, POOL_TTL, ALLOWED_JOINER, JOINER_STAKE)
             .accounts({
                 creator: creatorKeypair.publicKey,
                 //@ts-ignore
                 globalData: globalDataPda,
                 creatorAta: creatorTokenAccount,
-                spinxMint: spinxMint,
+                mint: spinxMint,
                 coinflipPool: coinflipPool,
                 treasuryWallet: treasuryWallet,
+                poolRegistry,
                 splEscrow: splEscrow,
                 associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
                 systemProgram: SystemProgram.programId,
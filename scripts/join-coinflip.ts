@@ -26,6 +26,7 @@ dotenv.config();
 // Constants
 const GLOBAL_AUTHORITY_SEED = "global-authority";
 const COINFLIP_SEED = "coinflip-authority";
+const POOL_REGISTRY_SEED = "pool-registry";
 const SPINX_TOKEN_ADDRESS = "4QAuuGj2mMjEPwsX61Sx9gwfNLcKVPotSWV3vUZfv28g";
 const TREASURY_WALLET = "69QQYnDRZ386bbuMV7srfgh4D5dAR51SdyZ1wWtC3CKs";
 
@@ -165,6 +166,13 @@ async function joinCoinflip() {
         );
         console.log("🏦 SPL Escrow:", splEscrow.toString());
 
+        // Lobby shard the pool is listed in, the join takes it off
+        const { stakeTier } = await program.account.coinflipPool.fetch(coinflipPool);
+        const [poolRegistry] = PublicKey.findProgramAddressSync(
+            [Buffer.from(POOL_REGISTRY_SEED), spinxMint.toBuffer(), Buffer.from([stakeTier])],
+            program.programId
+        );
+
         // Check joiner SOL balance
         const joinerBalance = await connection.getBalance(joinerKeypair.publicKey);
         console.log("💎 Joiner SOL balance:", joinerBalance / LAMPORTS_PER_SOL, "SOL");
@@ -196,9 +204,10 @@ async function joinCoinflip() {
                 //@ts-ignore
                 globalData: globalDataPda,
                 joinerAta: joinerTokenAccount,
-                spinxMint: spinxMint,
+                mint: spinxMint,
                 coinflipPool: coinflipPool,
                 treasuryWallet: globalDataAccount.treasuryWallet,
+                poolRegistry,
                 splEscrow: splEscrow,
                 vrf: vrf.programId,
                 config: networkStateAccountAddress(),
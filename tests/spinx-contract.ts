@@ -86,6 +86,13 @@ describe("spinx", () => {
     const VAULT_SEED = "vault-authority";
     const COINFLIP_SEED = "coinflip-authority";
     const RANDOM_SEED = "random-seed";
+    const POOL_REGISTRY_SEED = "pool-registry";
+
+    // Lobby shard of a stake, its order of magnitude in base units like stake_tier in utils.rs
+    const poolRegistryAddress = (mint: PublicKey, amount: BN) => PublicKey.findProgramAddressSync(
+        [Buffer.from(POOL_REGISTRY_SEED), mint.toBuffer(), Buffer.from([amount.isZero() ? 0 : amount.toString().length - 1])],
+        program.programId
+    )[0];
 
     // Use the actual token address from the contract // Belle
     const SPINX_TOKEN_ADDRESS = "4QAuuGj2mMjEPwsX61Sx9gwfNLcKVPotSWV3vUZfv28g";
@@ -332,14 +339,15 @@ describe("spinx", () => {
 
             // Create the coinflip
             const tx = await program.methods
-                .createCoinflip(setNumber, coinflipAmount)
+                .createCoinflip(setNumber, coinflipAmount, new BN(0), null, null)
                 .accounts({
                     creator: creatorKeypair.publicKey,
                     globalData: globalData,
                     creatorAta: creatorTokenAccount,
-                    spinxMint: spinxMint,
+                    mint: spinxMint,
                     coinflipPool: coinflipPool,
                     treasuryWallet: new PublicKey(TREASURY_WALLET),
+                    poolRegistry: poolRegistryAddress(spinxMint, coinflipAmount),
                     splEscrow: splEscrow,
                     associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
                     systemProgram: SystemProgram.programId,
@@ -409,8 +417,9 @@ describe("spinx", () => {
                 signer: creatorKeypair.publicKey,
                 coinflipPool: coinflipPool,
                 splEscrow: splEscrow,
-                spinxMint: spinxMint,
+                mint: spinxMint,
                 creatorAta: creatorTokenAccount,
+                poolRegistry: poolRegistryAddress(spinxMint, coinflipAmount),
                 associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
                 systemProgram: SystemProgram.programId,
                 tokenProgram: TOKEN_PROGRAM_ID,
@@ -454,15 +463,16 @@ describe("spinx", () => {
 
             // Create the coinflip
             const tx = await program.methods
-                .createCoinflip(setNumber, coinflipAmount)
+                .createCoinflip(setNumber, coinflipAmount, new BN(0), null, null)
                 .accounts({
                     creator: creatorKeypair.publicKey,
                     globalData: globalData,
                     creatorAta: creatorTokenAccount,
-                    spinxMint: spinxMint,
+                    mint: spinxMint,
                     coinflipPool: coinflipPool,
                     treasuryWallet: new PublicKey(TREASURY_WALLET),
                     tokenAccount: creatorTokenAccount,
+                    poolRegistry: poolRegistryAddress(spinxMint, coinflipAmount),
                     splEscrow: splEscrow,
                     associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
                     systemProgram: SystemProgram.programId,
@@ -549,9 +559,10 @@ describe("spinx", () => {
                     joiner: joinerKeypair.publicKey,
                     globalData: globalData,
                     joinerAta: joinerTokenAccount,
-                    spinxMint: spinxMint,
+                    mint: spinxMint,
                     coinflipPool: coinflipPool,
                     treasuryWallet: new PublicKey(TREASURY_WALLET),
+                    poolRegistry: poolRegistryAddress(spinxMint, coinflipAmount),
                     splEscrow: splEscrow,
                     vrf: vrf.programId,
                     config: networkStateAccountAddress(),
@@ -629,20 +640,35 @@ describe("spinx", () => {
         const coinflipData = await program.account.coinflipPool.fetch(coinflipPool)
 
         const random = randomnessAccountAddress(Buffer.from(coinflipData.force));
-        const treasury = new PublicKey("9ZTHWWZDpB36UFe1vszf2KEpt83vwi27jDqtHQ7NSXyR");
+        const [creatorProfile] = PublicKey.findProgramAddressSync(
+            [Buffer.from("player-profile"), coinflipData.creatorPlayer.toBuffer(), spinxMint.toBuffer()],
+            program.programId
+        );
+        const [joinerProfile] = PublicKey.findProgramAddressSync(
+            [Buffer.from("player-profile"), coinflipData.joinerPlayer.toBuffer(), spinxMint.toBuffer()],
+            program.programId
+        );
+        const [season] = PublicKey.findProgramAddressSync(
+            [Buffer.from("season"), spinxMint.toBuffer()],
+            program.programId
+        );
 
         console.log("Program account data: ", await program.account.coinflipPool.fetch(coinflipPool))
 
         const tx = await program.methods.resultCoinflip(pool_id, coinflipData.force).accounts({
             coinflipPool: coinflipPool,
             splEscrow: splEscrow,
-            spinxMint: spinxMint,
+            mint: spinxMint,
             creatorAta: creatorTokenAccount,
             joinerAta: joinerTokenAccount,
-            treasury: treasury,
+            creator: coinflipData.creatorPlayer,
+            globalData: globalData,
+            treasuryAta: await getAssociatedTokenAddress(spinxMint, globalDataAccount.treasuryWallet),
             random,
-            config: networkStateAccountAddress(),
-            vrf: vrf.programId,
+            creatorProfile,
+            joinerProfile,
+            season,
+            seasonVault: await getAssociatedTokenAddress(spinxMint, season, true),
             systemProgram: SystemProgram.programId,
             tokenProgram: TOKEN_PROGRAM_ID,
         })
@@ -676,11 +702,25 @@ describe("spinx", () => {
             const wallet = provider.wallet.publicKey;
 
             const tx = await program.methods
-                .setGlobalData(newFee, newTreasuryWallet, minAmount)
+                .setFee(newFee)
                 .accounts({
                     admin: wallet,
                     globalData: globalData,
                 })
+                .postInstructions([
+                    await program.methods
+                        .setMinAmount(minAmount)
+                        .accounts({ admin: wallet, globalData: globalData })
+                        .instruction(),
+                    await program.methods
+                        .setTreasury(newTreasuryWallet)
+                        .accounts({ admin: wallet, globalData: globalData })
+                        .instruction(),
+                    await program.methods
+                        .setSpinxToken(spinxMint)
+                        .accounts({ admin: wallet, globalData: globalData })
+                        .instruction(),
+                ])
                 .rpc();
 
             console.log("Set fee transaction signature", tx);
@@ -691,6 +731,8 @@ describe("spinx", () => {
             // Verify the fee was updated correctly
             assert.equal(globalDataAccount.coinflipFee.toString(), newFee.toString());
             assert.equal(globalDataAccount.treasuryWallet.toString(), newTreasuryWallet.toString());
+            assert.equal(globalDataAccount.minAmount.toString(), minAmount.toString());
+            assert.equal(globalDataAccount.spinxToken.toString(), spinxMint.toString());
 
             console.log("Fee updated successfully to:", globalDataAccount.coinflipFee.toString());
             console.log("Treasury wallet updated to:", globalDataAccount.treasuryWallet.toString());